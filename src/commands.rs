@@ -0,0 +1,427 @@
+//! Slash command registry with completion.
+//!
+//! A [`CommandRegistry`] merges client-local commands (like `/clear`) with
+//! commands an ACP agent advertises through `available_commands_update`.
+//! Local commands take precedence when both define the same name.
+//!
+//! The registry implements [`Completer`], so it plugs into
+//! [`LineEditor`](crate::LineEditor) for tab completion and an inline hint
+//! popup while typing a command. [`CommandRegistry::format_help`] renders
+//! `/help` output.
+
+use std::collections::BTreeMap;
+
+use colored::Colorize;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::prompt::{Completer, Completion};
+use crate::width::display_width;
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Where a slash command comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandSource {
+    /// Handled by the client application.
+    Local,
+    /// Advertised by the agent via `available_commands_update`.
+    Agent,
+}
+
+/// A slash command, invoked as `/name [input]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlashCommand {
+    /// Command name without the leading `/`.
+    pub name: String,
+    /// One-line description shown in completion and `/help`.
+    pub description: String,
+    /// Placeholder describing the expected input (e.g. `query`), if any.
+    pub input_hint: Option<String>,
+    /// Whether the client or the agent handles the command.
+    pub source: CommandSource,
+}
+
+impl SlashCommand {
+    /// Create a client-local command.
+    pub fn local(name: impl Into<String>, description: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            description: description.into(),
+            input_hint: None,
+            source: CommandSource::Local,
+        }
+    }
+
+    /// Create an agent-advertised command.
+    pub fn agent(name: impl Into<String>, description: impl Into<String>) -> Self {
+        Self {
+            source: CommandSource::Agent,
+            ..Self::local(name, description)
+        }
+    }
+
+    /// Set the input placeholder shown after the command name.
+    pub fn with_input_hint(mut self, hint: impl Into<String>) -> Self {
+        self.input_hint = Some(hint.into());
+        self
+    }
+
+    /// Usage string like `/web <query>`.
+    pub fn usage(&self) -> String {
+        match &self.input_hint {
+            Some(hint) => format!("/{} <{}>", self.name, hint),
+            None => format!("/{}", self.name),
+        }
+    }
+}
+
+/// ACP `AvailableCommand` as sent in `available_commands_update`.
+#[derive(Debug, Deserialize)]
+struct AvailableCommand {
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    input: Option<AvailableCommandInput>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AvailableCommandInput {
+    hint: String,
+}
+
+/// Split a prompt like `/web rust async` into `("web", "rust async")`.
+///
+/// Returns None if `input` is not a slash command.
+pub fn parse_slash_command(input: &str) -> Option<(&str, &str)> {
+    let rest = input.trim_start().strip_prefix('/')?;
+    let (name, args) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    if name.is_empty() {
+        return None;
+    }
+    Some((name, args.trim()))
+}
+
+// ============================================================================
+// Registry
+// ============================================================================
+
+/// Maximum number of commands shown in the completion popup.
+const MAX_POPUP_ENTRIES: usize = 8;
+
+/// Merged set of local and agent-advertised slash commands.
+///
+/// # Example
+///
+/// ```
+/// use clemitui::{CommandRegistry, SlashCommand};
+/// use serde_json::json;
+///
+/// let mut registry = CommandRegistry::new();
+/// registry.register(SlashCommand::local("clear", "Clear the conversation"));
+/// registry
+///     .apply_available_commands_update(&json!({
+///         "sessionUpdate": "available_commands_update",
+///         "availableCommands": [
+///             {"name": "web", "description": "Search the web", "input": {"hint": "query"}}
+///         ]
+///     }))
+///     .unwrap();
+///
+/// let names: Vec<_> = registry.matching("").iter().map(|c| c.name.as_str()).collect();
+/// assert_eq!(names, ["clear", "web"]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CommandRegistry {
+    local: BTreeMap<String, SlashCommand>,
+    agent: BTreeMap<String, SlashCommand>,
+}
+
+impl CommandRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a client-local command, replacing any with the same name.
+    pub fn register(&mut self, command: SlashCommand) {
+        let command = SlashCommand {
+            source: CommandSource::Local,
+            ..command
+        };
+        self.local.insert(command.name.clone(), command);
+    }
+
+    /// Replace all agent-advertised commands.
+    pub fn set_agent_commands(&mut self, commands: impl IntoIterator<Item = SlashCommand>) {
+        self.agent = commands
+            .into_iter()
+            .map(|command| {
+                let command = SlashCommand {
+                    source: CommandSource::Agent,
+                    ..command
+                };
+                (command.name.clone(), command)
+            })
+            .collect();
+    }
+
+    /// Replace agent commands from an ACP `available_commands_update`.
+    ///
+    /// Accepts either the whole session update or just its
+    /// `availableCommands` array.
+    pub fn apply_available_commands_update(
+        &mut self,
+        update: &Value,
+    ) -> Result<(), serde_json::Error> {
+        let list = update.get("availableCommands").unwrap_or(update);
+        let commands: Vec<AvailableCommand> = serde_json::from_value(list.clone())?;
+        self.set_agent_commands(commands.into_iter().map(|command| {
+            let slash = SlashCommand::agent(command.name, command.description);
+            match command.input {
+                Some(input) => slash.with_input_hint(input.hint),
+                None => slash,
+            }
+        }));
+        Ok(())
+    }
+
+    /// Look up a command by name (local commands shadow agent ones).
+    pub fn get(&self, name: &str) -> Option<&SlashCommand> {
+        self.local.get(name).or_else(|| self.agent.get(name))
+    }
+
+    /// Look up the command invoked by a prompt like `/web rust`.
+    pub fn resolve<'a>(&self, input: &'a str) -> Option<(&SlashCommand, &'a str)> {
+        let (name, args) = parse_slash_command(input)?;
+        self.get(name).map(|command| (command, args))
+    }
+
+    /// All commands whose name starts with `prefix`, sorted by name.
+    pub fn matching(&self, prefix: &str) -> Vec<&SlashCommand> {
+        let mut commands: Vec<&SlashCommand> = self
+            .local
+            .values()
+            .chain(
+                self.agent
+                    .values()
+                    .filter(|command| !self.local.contains_key(&command.name)),
+            )
+            .filter(|command| command.name.starts_with(prefix))
+            .collect();
+        commands.sort_by(|a, b| a.name.cmp(&b.name));
+        commands
+    }
+
+    /// Check if the registry has no commands.
+    pub fn is_empty(&self) -> bool {
+        self.local.is_empty() && self.agent.is_empty()
+    }
+
+    /// Render `/help` output listing all commands.
+    pub fn format_help(&self) -> String {
+        let commands = self.matching("");
+        if commands.is_empty() {
+            return "No commands available.".to_string();
+        }
+        let width = commands
+            .iter()
+            .map(|command| display_width(&command.usage()))
+            .max()
+            .unwrap_or(0);
+        let mut out = format!("{}\n", "Commands:".bold());
+        for command in commands {
+            let usage = command.usage();
+            let padding = " ".repeat(width - display_width(&usage));
+            let source = match command.source {
+                CommandSource::Local => String::new(),
+                CommandSource::Agent => format!(" {}", "(agent)".dimmed()),
+            };
+            out.push_str(&format!(
+                "  {}{}  {}{}\n",
+                usage.cyan(),
+                padding,
+                command.description,
+                source
+            ));
+        }
+        out
+    }
+}
+
+/// The command name being typed, if the cursor is still inside it.
+fn typed_command_prefix(line: &str, cursor: usize) -> Option<&str> {
+    let before = line.get(..cursor)?;
+    let name = before.strip_prefix('/')?;
+    if name.contains(char::is_whitespace) || cursor != line.len() {
+        return None;
+    }
+    Some(name)
+}
+
+impl Completer for CommandRegistry {
+    fn complete(&self, line: &str, cursor: usize) -> (usize, Vec<Completion>) {
+        let Some(prefix) = typed_command_prefix(line, cursor) else {
+            return (cursor, Vec::new());
+        };
+        let candidates = self
+            .matching(prefix)
+            .into_iter()
+            .take(MAX_POPUP_ENTRIES)
+            .map(|command| Completion {
+                replacement: format!("/{}", command.name),
+                description: Some(command.description.clone()),
+            })
+            .collect();
+        (0, candidates)
+    }
+
+    fn hint(&self, line: &str, cursor: usize) -> Option<String> {
+        if cursor != line.len() {
+            return None;
+        }
+        if let Some(prefix) = typed_command_prefix(line, cursor) {
+            let first = self.matching(prefix).into_iter().next()?;
+            return Some(first.name[prefix.len()..].to_string());
+        }
+        // After `/name `, show the expected input until the user types it
+        let name = line.strip_prefix('/')?.strip_suffix(' ')?;
+        self.get(name)?
+            .input_hint
+            .as_ref()
+            .map(|hint| format!("<{}>", hint))
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_registry() -> CommandRegistry {
+        let mut registry = CommandRegistry::new();
+        registry.register(SlashCommand::local("clear", "Clear the conversation"));
+        registry.register(SlashCommand::local("compact", "Summarize the conversation"));
+        registry.register(SlashCommand::local("help", "Show available commands"));
+        registry.set_agent_commands([
+            SlashCommand::agent("web", "Search the web").with_input_hint("query"),
+            SlashCommand::agent("clear", "Agent-side clear"),
+        ]);
+        registry
+    }
+
+    #[test]
+    fn test_parse_slash_command() {
+        assert_eq!(parse_slash_command("/clear"), Some(("clear", "")));
+        assert_eq!(
+            parse_slash_command("/web  rust async "),
+            Some(("web", "rust async"))
+        );
+        assert_eq!(parse_slash_command("not a command"), None);
+        assert_eq!(parse_slash_command("/"), None);
+    }
+
+    #[test]
+    fn test_local_commands_shadow_agent_commands() {
+        let registry = sample_registry();
+        let clear = registry.get("clear").unwrap();
+        assert_eq!(clear.source, CommandSource::Local);
+        assert_eq!(clear.description, "Clear the conversation");
+
+        let names: Vec<_> = registry
+            .matching("c")
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(names, ["clear", "compact"]);
+    }
+
+    #[test]
+    fn test_resolve() {
+        let registry = sample_registry();
+        let (command, args) = registry.resolve("/web rust").unwrap();
+        assert_eq!(command.name, "web");
+        assert_eq!(args, "rust");
+        assert!(registry.resolve("/unknown").is_none());
+    }
+
+    #[test]
+    fn test_apply_available_commands_update() {
+        let mut registry = sample_registry();
+        registry
+            .apply_available_commands_update(&serde_json::json!({
+                "sessionUpdate": "available_commands_update",
+                "availableCommands": [
+                    {"name": "plan", "description": "Create a plan"},
+                    {"name": "test", "description": "Run tests", "input": {"hint": "filter"}}
+                ]
+            }))
+            .unwrap();
+        // The update replaces previous agent commands
+        assert!(registry.get("web").is_none());
+        assert_eq!(registry.get("plan").unwrap().source, CommandSource::Agent);
+        assert_eq!(registry.get("test").unwrap().usage(), "/test <filter>");
+
+        // A bare array is accepted too
+        registry
+            .apply_available_commands_update(&serde_json::json!([{"name": "only"}]))
+            .unwrap();
+        assert!(registry.get("plan").is_none());
+        assert!(registry.get("only").is_some());
+
+        assert!(
+            registry
+                .apply_available_commands_update(&serde_json::json!({"availableCommands": 3}))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_complete() {
+        let registry = sample_registry();
+        let (start, candidates) = registry.complete("/c", 2);
+        assert_eq!(start, 0);
+        let replacements: Vec<_> = candidates.iter().map(|c| c.replacement.as_str()).collect();
+        assert_eq!(replacements, ["/clear", "/compact"]);
+
+        // No completion once arguments are being typed, or outside commands
+        assert!(registry.complete("/web rust", 9).1.is_empty());
+        assert!(registry.complete("hello", 5).1.is_empty());
+    }
+
+    #[test]
+    fn test_hint() {
+        let registry = sample_registry();
+        assert_eq!(registry.hint("/he", 3), Some("lp".to_string()));
+        assert_eq!(registry.hint("/web ", 5), Some("<query>".to_string()));
+        assert_eq!(registry.hint("/web rust", 9), None);
+        assert_eq!(registry.hint("/zzz", 4), None);
+        // Cursor not at the end: no ghost text
+        assert_eq!(registry.hint("/he", 1), None);
+    }
+
+    #[test]
+    fn test_format_help() {
+        colored::control::set_override(false);
+        let help = sample_registry().format_help();
+        assert_eq!(
+            help,
+            "Commands:\n\
+             \x20 /clear        Clear the conversation\n\
+             \x20 /compact      Summarize the conversation\n\
+             \x20 /help         Show available commands\n\
+             \x20 /web <query>  Search the web (agent)\n"
+        );
+        assert_eq!(
+            CommandRegistry::new().format_help(),
+            "No commands available."
+        );
+        colored::control::unset_override();
+    }
+}
@@ -0,0 +1,904 @@
+//! ACP content blocks beyond plain text.
+//!
+//! Agent messages are made of content blocks: `text`, `image`, `audio`,
+//! `resource` (embedded contents) and `resource_link` (a reference).
+//! [`ContentBlock::from_acp`] parses one and [`format_content_block`] renders
+//! it:
+//!
+//! - text is rendered as markdown, like [`TextBuffer`](crate::TextBuffer);
+//! - images are drawn inline with the kitty graphics protocol (PNG), the
+//!   iTerm2 inline image protocol or sixel (PNG) when the terminal supports
+//!   one, and shown as a `[image: 640×480 png]` placeholder otherwise;
+//! - audio becomes a placeholder with format and size;
+//! - resource links show their title, MIME type and size, linked to the URI;
+//! - embedded text resources become fenced code blocks under their URI.
+//!
+//! # Usage
+//!
+//! ```
+//! use clemitui::{ContentBlock, format_content_block};
+//! use serde_json::json;
+//!
+//! let block = ContentBlock::from_acp(&json!({
+//!     "type": "resource_link",
+//!     "uri": "file:///work/README.md",
+//!     "name": "README.md",
+//!     "mimeType": "text/markdown",
+//!     "size": 2048
+//! }))
+//! .unwrap();
+//! print!("{}", format_content_block(&block));
+//! ```
+
+use std::env;
+use std::io::IsTerminal;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use colored::Colorize;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::hyperlink::{link_config, osc8};
+use crate::text_buffer::{render_markdown, render_markdown_nowrap};
+
+/// Base64 bytes per kitty graphics escape sequence.
+const KITTY_CHUNK_SIZE: usize = 4096;
+
+/// Largest width or height of a sixel image in pixels; bigger images are
+/// scaled down to fit.
+const SIXEL_MAX_SIZE: u32 = 800;
+
+/// Levels per channel of the sixel color cube (6×6×6 = 216 colors).
+const SIXEL_LEVELS: u32 = 6;
+
+// ============================================================================
+// Content Blocks
+// ============================================================================
+
+/// A link to a resource the client can fetch, from a `resource_link` block.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceLink {
+    /// Location of the resource.
+    pub uri: String,
+    /// Name of the resource, typically a file name.
+    pub name: String,
+    /// Human-readable title, shown instead of the name.
+    #[serde(default)]
+    pub title: Option<String>,
+    /// What the resource contains.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// MIME type, if known.
+    #[serde(default)]
+    pub mime_type: Option<String>,
+    /// Size in bytes, if known.
+    #[serde(default)]
+    pub size: Option<u64>,
+}
+
+/// Contents of a `resource` block.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged, rename_all_fields = "camelCase")]
+pub enum EmbeddedResource {
+    /// Text contents.
+    Text {
+        /// Location the contents came from.
+        uri: String,
+        /// MIME type, if known.
+        #[serde(default)]
+        mime_type: Option<String>,
+        /// The text.
+        text: String,
+    },
+    /// Binary contents.
+    Blob {
+        /// Location the contents came from.
+        uri: String,
+        /// MIME type, if known.
+        #[serde(default)]
+        mime_type: Option<String>,
+        /// Base64-encoded bytes.
+        blob: String,
+    },
+}
+
+/// A block of an agent message.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(
+    tag = "type",
+    rename_all = "snake_case",
+    rename_all_fields = "camelCase"
+)]
+pub enum ContentBlock {
+    /// Markdown text.
+    Text {
+        /// The text.
+        text: String,
+    },
+    /// An image.
+    Image {
+        /// Base64-encoded image data.
+        data: String,
+        /// MIME type, e.g. `image/png`.
+        mime_type: String,
+        /// Where the image came from, if anywhere.
+        #[serde(default)]
+        uri: Option<String>,
+    },
+    /// An audio clip.
+    Audio {
+        /// Base64-encoded audio data.
+        data: String,
+        /// MIME type, e.g. `audio/wav`.
+        mime_type: String,
+    },
+    /// Embedded resource contents.
+    Resource {
+        /// The contents.
+        resource: EmbeddedResource,
+    },
+    /// A reference to a resource.
+    ResourceLink(ResourceLink),
+}
+
+impl ContentBlock {
+    /// Parse an ACP content block, or None if it is malformed or of an
+    /// unknown type.
+    pub fn from_acp(block: &Value) -> Option<Self> {
+        Self::deserialize(block).ok()
+    }
+}
+
+// ============================================================================
+// Image Protocols
+// ============================================================================
+
+/// How the terminal can display images.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ImageProtocol {
+    /// The kitty graphics protocol (kitty, Ghostty). PNG only.
+    Kitty,
+    /// The iTerm2 inline image protocol (iTerm2, WezTerm).
+    Iterm2,
+    /// DEC sixel graphics (foot, mlterm, Windows Terminal, Konsole). PNG only.
+    Sixel,
+    /// No image support; images are shown as placeholders (default).
+    #[default]
+    None,
+}
+
+impl ImageProtocol {
+    /// The protocol supported by the environment described by `var`.
+    ///
+    /// Recognises kitty and Ghostty from `TERM`, `TERM_PROGRAM` and
+    /// `KITTY_WINDOW_ID`, iTerm2 and WezTerm from `TERM_PROGRAM`, and sixel
+    /// terminals from `TERM` (foot, mlterm, contour, yaft), `WT_SESSION` and
+    /// `KONSOLE_VERSION`. Inside tmux or screen no protocol is assumed, since
+    /// they do not forward image escapes by default.
+    pub fn detect_with(var: impl Fn(&str) -> Option<String>) -> Self {
+        if var("TMUX").is_some() || var("TERM").is_some_and(|term| term.starts_with("screen")) {
+            return Self::None;
+        }
+        let program = var("TERM_PROGRAM").unwrap_or_default();
+        let term = var("TERM").unwrap_or_default();
+        if program == "ghostty"
+            || term.contains("kitty")
+            || term.contains("ghostty")
+            || var("KITTY_WINDOW_ID").is_some()
+        {
+            Self::Kitty
+        } else if matches!(program.as_str(), "iTerm.app" | "WezTerm") {
+            Self::Iterm2
+        } else if ["foot", "mlterm", "contour", "yaft"]
+            .iter()
+            .any(|name| term.contains(name))
+            || var("WT_SESSION").is_some()
+            || var("KONSOLE_VERSION")
+                .and_then(|v| v.parse::<u32>().ok())
+                .is_some_and(|v| v >= 220400)
+        {
+            Self::Sixel
+        } else {
+            Self::None
+        }
+    }
+
+    /// The protocol implied by a primary device attributes (DA1) reply such
+    /// as `ESC [?62;4;22c`, for applications that query the terminal.
+    ///
+    /// Attribute 4 advertises sixel graphics.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clemitui::ImageProtocol;
+    ///
+    /// assert_eq!(ImageProtocol::from_device_attributes("\x1b[?62;4;22c"), ImageProtocol::Sixel);
+    /// assert_eq!(ImageProtocol::from_device_attributes("\x1b[?62;22c"), ImageProtocol::None);
+    /// ```
+    pub fn from_device_attributes(reply: &str) -> Self {
+        let attributes = reply
+            .trim_start_matches("\x1b[?")
+            .trim_end_matches('c')
+            .split(';');
+        if attributes.skip(1).any(|attribute| attribute == "4") {
+            Self::Sixel
+        } else {
+            Self::None
+        }
+    }
+
+    /// The protocol supported by stdout, detected once from the process
+    /// environment.
+    pub fn detect() -> Self {
+        static DETECTED: OnceLock<ImageProtocol> = OnceLock::new();
+        *DETECTED.get_or_init(|| {
+            if std::io::stdout().is_terminal() {
+                Self::detect_with(|name| env::var(name).ok())
+            } else {
+                Self::None
+            }
+        })
+    }
+}
+
+// ============================================================================
+// Formatting
+// ============================================================================
+
+/// Format a content block for the terminal's detected image protocol.
+pub fn format_content_block(block: &ContentBlock) -> String {
+    format_content_block_with(block, ImageProtocol::detect())
+}
+
+/// Format a content block, drawing images with `protocol`.
+///
+/// The result ends with a newline, or is empty for empty text.
+///
+/// # Example
+///
+/// ```
+/// use clemitui::{ContentBlock, ImageProtocol, format_content_block_with};
+///
+/// colored::control::set_override(false);
+/// // A 1×1 PNG
+/// let block = ContentBlock::Image {
+///     data: "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNk+M9QDwADhgGAWjR9awAAAABJRU5ErkJggg==".into(),
+///     mime_type: "image/png".into(),
+///     uri: None,
+/// };
+/// assert_eq!(
+///     format_content_block_with(&block, ImageProtocol::None),
+///     "[image: 1×1 png]\n"
+/// );
+/// colored::control::unset_override();
+/// ```
+pub fn format_content_block_with(block: &ContentBlock, protocol: ImageProtocol) -> String {
+    match block {
+        ContentBlock::Text { text } => {
+            let cwd = env::current_dir().ok();
+            let width = crossterm::terminal::size().map_or(80, |(columns, _)| columns as usize);
+            let rendered = render_markdown(text, &link_config(), cwd.as_deref(), width);
+            let trimmed = rendered.trim_end_matches('\n');
+            if trimmed.is_empty() {
+                String::new()
+            } else {
+                format!("{trimmed}\n")
+            }
+        }
+        ContentBlock::Image {
+            data, mime_type, ..
+        } => format_image(data, mime_type, protocol),
+        ContentBlock::Audio { data, mime_type } => {
+            let size = base64_decoded_len(data);
+            let label = format!("[audio: {} · {}]", subtype(mime_type), format_size(size));
+            format!("{}\n", label.dimmed())
+        }
+        ContentBlock::ResourceLink(link) => format_resource_link(link),
+        ContentBlock::Resource {
+            resource:
+                EmbeddedResource::Text {
+                    uri,
+                    mime_type,
+                    text,
+                },
+        } => {
+            let lang = code_language(uri, mime_type.as_deref());
+            let fence = if text.contains("```") { "~~~" } else { "```" };
+            let code = render_markdown_nowrap(&format!(
+                "{fence}{lang}\n{}\n{fence}",
+                text.trim_end_matches('\n')
+            ));
+            format!(
+                "{}\n{}\n",
+                link_uri(uri, uri).dimmed(),
+                code.trim_end_matches('\n')
+            )
+        }
+        ContentBlock::Resource {
+            resource:
+                EmbeddedResource::Blob {
+                    uri,
+                    mime_type,
+                    blob,
+                },
+        } => match mime_type {
+            Some(mime) if mime.starts_with("image/") => format_image(blob, mime, protocol),
+            _ => {
+                let mut details = vec![file_name(uri).to_string()];
+                details.extend(mime_type.clone());
+                details.push(format_size(base64_decoded_len(blob)));
+                let label = format!("[resource: {}]", details.join(" · "));
+                format!("{}\n", link_uri(uri, &label).dimmed())
+            }
+        },
+    }
+}
+
+/// Draw an image inline, or show a placeholder with its dimensions.
+fn format_image(data: &str, mime_type: &str, protocol: ImageProtocol) -> String {
+    let payload: String = data.chars().filter(|c| !c.is_ascii_whitespace()).collect();
+    let bytes = base64_decode(&payload);
+    let dimensions = bytes.as_deref().and_then(image_dimensions);
+    match (protocol, &bytes) {
+        (ImageProtocol::Kitty, Some(_)) if mime_type == "image/png" => {
+            let chunks: Vec<&str> = payload
+                .as_bytes()
+                .chunks(KITTY_CHUNK_SIZE)
+                .map(|chunk| std::str::from_utf8(chunk).unwrap_or_default())
+                .collect();
+            let mut out = String::new();
+            for (i, chunk) in chunks.iter().enumerate() {
+                let more = u8::from(i + 1 < chunks.len());
+                if i == 0 {
+                    out.push_str(&format!("\x1b_Ga=T,f=100,m={more};{chunk}\x1b\\"));
+                } else {
+                    out.push_str(&format!("\x1b_Gm={more};{chunk}\x1b\\"));
+                }
+            }
+            out.push('\n');
+            out
+        }
+        (ImageProtocol::Iterm2, Some(bytes)) => format!(
+            "\x1b]1337;File=inline=1;size={};preserveAspectRatio=1:{payload}\x07\n",
+            bytes.len()
+        ),
+        (ImageProtocol::Sixel, Some(bytes)) if mime_type == "image/png" => {
+            match decode_png(bytes) {
+                Some((width, height, pixels)) => {
+                    format!("{}\n", encode_sixel(width, height, &pixels))
+                }
+                None => format_image_placeholder(mime_type, dimensions),
+            }
+        }
+        _ => format_image_placeholder(mime_type, dimensions),
+    }
+}
+
+/// `[image: 640×480 png]`, or `[image: png]` without known dimensions.
+fn format_image_placeholder(mime_type: &str, dimensions: Option<(u32, u32)>) -> String {
+    let label = match dimensions {
+        Some((width, height)) => format!("[image: {width}×{height} {}]", subtype(mime_type)),
+        None => format!("[image: {}]", subtype(mime_type)),
+    };
+    format!("{}\n", label.dimmed())
+}
+
+/// `title (name) · mime · size`, linked to the URI, with the description
+/// on the next line.
+fn format_resource_link(link: &ResourceLink) -> String {
+    let title = link.title.as_deref().unwrap_or(&link.name);
+    let mut out = format!("↗ {}", link_uri(&link.uri, title).bold());
+    if title != link.name {
+        out.push_str(&format!(" ({})", link.name));
+    }
+    let mut details: Vec<String> = link.mime_type.iter().cloned().collect();
+    details.extend(link.size.map(format_size));
+    if !details.is_empty() {
+        out.push_str(&format!(
+            " {}",
+            format!("· {}", details.join(" · ")).dimmed()
+        ));
+    }
+    if !link_config().links_enabled() {
+        out.push_str(&format!(" {}", link.uri.dimmed()));
+    }
+    out.push('\n');
+    if let Some(description) = &link.description {
+        out.push_str(&format!("  {}\n", description.dimmed()));
+    }
+    out
+}
+
+/// `text` as an OSC 8 link to `uri` when hyperlinks are enabled.
+fn link_uri(uri: &str, text: &str) -> String {
+    if link_config().links_enabled() {
+        osc8(uri, text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// `png` from `image/png`.
+fn subtype(mime_type: &str) -> &str {
+    mime_type.split_once('/').map_or(mime_type, |(_, sub)| sub)
+}
+
+/// Last path segment of a URI.
+fn file_name(uri: &str) -> &str {
+    uri.rsplit('/').find(|s| !s.is_empty()).unwrap_or(uri)
+}
+
+/// Code fence language for a resource, from its extension or MIME type.
+fn code_language<'a>(uri: &'a str, mime_type: Option<&'a str>) -> &'a str {
+    Path::new(file_name(uri))
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .or_else(|| {
+            mime_type
+                .map(subtype)
+                .map(|sub| sub.strip_prefix("x-").unwrap_or(sub))
+                .filter(|sub| *sub != "plain")
+        })
+        .unwrap_or("")
+}
+
+/// Human-readable byte count: `512 B`, `1.5 KB`, `3.2 MB`.
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+// ============================================================================
+// Image Data
+// ============================================================================
+
+/// Decode standard base64, with or without padding. None if malformed.
+fn base64_decode(input: &str) -> Option<Vec<u8>> {
+    fn value(c: u8) -> Option<u32> {
+        match c {
+            b'A'..=b'Z' => Some((c - b'A') as u32),
+            b'a'..=b'z' => Some((c - b'a' + 26) as u32),
+            b'0'..=b'9' => Some((c - b'0' + 52) as u32),
+            b'+' => Some(62),
+            b'/' => Some(63),
+            _ => None,
+        }
+    }
+
+    let input = input.trim_end_matches('=').as_bytes();
+    if input.len() % 4 == 1 {
+        return None;
+    }
+    let mut out = Vec::with_capacity(input.len() * 3 / 4);
+    for chunk in input.chunks(4) {
+        let mut acc = 0u32;
+        for &c in chunk {
+            acc = (acc << 6) | value(c)?;
+        }
+        acc <<= 6 * (4 - chunk.len() as u32);
+        let bytes = acc.to_be_bytes();
+        out.extend_from_slice(&bytes[1..chunk.len()]);
+    }
+    Some(out)
+}
+
+/// Number of bytes `data` decodes to, without decoding it.
+fn base64_decoded_len(data: &str) -> u64 {
+    let len = data
+        .bytes()
+        .filter(|c| !c.is_ascii_whitespace() && *c != b'=')
+        .count() as u64;
+    len * 3 / 4
+}
+
+/// Decode a PNG into its width, height and RGBA pixels.
+fn decode_png(bytes: &[u8]) -> Option<(u32, u32, Vec<[u8; 4]>)> {
+    let mut decoder = png::Decoder::new(std::io::Cursor::new(bytes));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().ok()?;
+    let mut buf = vec![0; reader.output_buffer_size()?];
+    let info = reader.next_frame(&mut buf).ok()?;
+    let data = &buf[..info.buffer_size()];
+    let pixels = match info.color_type {
+        png::ColorType::Rgba => data
+            .chunks_exact(4)
+            .map(|p| [p[0], p[1], p[2], p[3]])
+            .collect(),
+        png::ColorType::Rgb => data
+            .chunks_exact(3)
+            .map(|p| [p[0], p[1], p[2], 255])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => data
+            .chunks_exact(2)
+            .map(|p| [p[0], p[0], p[0], p[1]])
+            .collect(),
+        png::ColorType::Grayscale => data.iter().map(|&v| [v, v, v, 255]).collect(),
+        png::ColorType::Indexed => return None,
+    };
+    Some((info.width, info.height, pixels))
+}
+
+/// Encode RGBA pixels as a sixel image.
+///
+/// Colors are mapped to a 6×6×6 cube, mostly transparent pixels are left
+/// unpainted, and images larger than [`SIXEL_MAX_SIZE`] are scaled down by
+/// sampling every n-th pixel.
+fn encode_sixel(width: u32, height: u32, pixels: &[[u8; 4]]) -> String {
+    let step = width.max(height).div_ceil(SIXEL_MAX_SIZE).max(1) as usize;
+    let (src_width, src_height) = (width as usize, height as usize);
+    let (width, height) = (src_width.div_ceil(step), src_height.div_ceil(step));
+    let level = |channel: u8| (channel as u32 * (SIXEL_LEVELS - 1) + 127) / 255;
+    let colors: Vec<Option<u32>> = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            let [r, g, b, a] = *pixels.get(y * step * src_width + x * step)?;
+            (a >= 128).then(|| (level(r) * SIXEL_LEVELS + level(g)) * SIXEL_LEVELS + level(b))
+        })
+        .collect();
+
+    // Transparent background (P2 = 1), 1:1 pixel aspect ratio
+    let mut out = format!("\x1bP0;1;0q\"1;1;{width};{height}");
+    let mut used = vec![false; (SIXEL_LEVELS * SIXEL_LEVELS * SIXEL_LEVELS) as usize];
+    for color in colors.iter().flatten() {
+        used[*color as usize] = true;
+    }
+    let percent = |level: u32| level * 100 / (SIXEL_LEVELS - 1);
+    for (color, _) in used.iter().enumerate().filter(|(_, used)| **used) {
+        let color = color as u32;
+        let (r, g, b) = (
+            color / (SIXEL_LEVELS * SIXEL_LEVELS),
+            color / SIXEL_LEVELS % SIXEL_LEVELS,
+            color % SIXEL_LEVELS,
+        );
+        out.push_str(&format!(
+            "#{color};2;{};{};{}",
+            percent(r),
+            percent(g),
+            percent(b)
+        ));
+    }
+
+    // Each band paints six pixel rows, one pass per color
+    for top in (0..height).step_by(6) {
+        let rows = top..(top + 6).min(height);
+        let mut band_colors: Vec<u32> = rows
+            .clone()
+            .flat_map(|y| colors[y * width..(y + 1) * width].iter().flatten().copied())
+            .collect();
+        band_colors.sort_unstable();
+        band_colors.dedup();
+        for (i, color) in band_colors.iter().enumerate() {
+            if i > 0 {
+                out.push('$');
+            }
+            out.push_str(&format!("#{color}"));
+            let sixels = (0..width).map(|x| {
+                let bits = rows
+                    .clone()
+                    .filter(|y| colors[y * width + x] == Some(*color))
+                    .fold(0u8, |bits, y| bits | 1 << (y - top));
+                (b'?' + bits) as char
+            });
+            push_run_length(&mut out, sixels);
+        }
+        out.push('-');
+    }
+    out.push_str("\x1b\\");
+    out
+}
+
+/// Append sixel characters, collapsing runs into `!count` repeats.
+fn push_run_length(out: &mut String, sixels: impl Iterator<Item = char>) {
+    let mut run: Option<(char, usize)> = None;
+    let flush = |out: &mut String, (sixel, count): (char, usize)| {
+        if count > 3 {
+            out.push_str(&format!("!{count}{sixel}"));
+        } else {
+            out.extend(std::iter::repeat_n(sixel, count));
+        }
+    };
+    for sixel in sixels {
+        run = match run {
+            Some((current, count)) if current == sixel => Some((current, count + 1)),
+            Some(previous) => {
+                flush(out, previous);
+                Some((sixel, 1))
+            }
+            None => Some((sixel, 1)),
+        };
+    }
+    if let Some(last) = run {
+        flush(out, last);
+    }
+}
+
+/// Pixel width and height from a PNG, GIF or JPEG header.
+fn image_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    let be16 = |i: usize| Some(u16::from_be_bytes(bytes.get(i..i + 2)?.try_into().ok()?) as u32);
+    let be32 = |i: usize| Some(u32::from_be_bytes(bytes.get(i..i + 4)?.try_into().ok()?));
+    let le16 = |i: usize| Some(u16::from_le_bytes(bytes.get(i..i + 2)?.try_into().ok()?) as u32);
+
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return Some((be32(16)?, be32(20)?));
+    }
+    if bytes.starts_with(b"GIF8") {
+        return Some((le16(6)?, le16(8)?));
+    }
+    if bytes.starts_with(&[0xFF, 0xD8]) {
+        // Walk the segments to the first start-of-frame marker
+        let mut i = 2;
+        while *bytes.get(i)? == 0xFF {
+            let marker = *bytes.get(i + 1)?;
+            let is_frame = matches!(marker, 0xC0..=0xCF) && !matches!(marker, 0xC4 | 0xC8 | 0xCC);
+            if is_frame {
+                return Some((be16(i + 7)?, be16(i + 5)?));
+            }
+            i += 2 + be16(i + 2)? as usize;
+        }
+    }
+    None
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// A 1×1 PNG.
+    const PNG: &str = "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNk+M9QDwADhgGAWjR9awAAAABJRU5ErkJggg==";
+
+    fn image(data: &str, mime_type: &str) -> ContentBlock {
+        ContentBlock::Image {
+            data: data.to_string(),
+            mime_type: mime_type.to_string(),
+            uri: None,
+        }
+    }
+
+    #[test]
+    fn test_from_acp() {
+        assert_eq!(
+            ContentBlock::from_acp(&json!({"type": "text", "text": "hi"})),
+            Some(ContentBlock::Text { text: "hi".into() })
+        );
+        assert_eq!(
+            ContentBlock::from_acp(&json!({
+                "type": "resource",
+                "resource": {"uri": "file:///a.rs", "mimeType": "text/x-rust", "text": "fn a() {}"}
+            })),
+            Some(ContentBlock::Resource {
+                resource: EmbeddedResource::Text {
+                    uri: "file:///a.rs".into(),
+                    mime_type: Some("text/x-rust".into()),
+                    text: "fn a() {}".into(),
+                }
+            })
+        );
+        let link = ContentBlock::from_acp(&json!({
+            "type": "resource_link", "uri": "https://x/y", "name": "y", "size": 10
+        }));
+        assert!(matches!(
+            link,
+            Some(ContentBlock::ResourceLink(ResourceLink {
+                size: Some(10),
+                ..
+            }))
+        ));
+        assert_eq!(ContentBlock::from_acp(&json!({"type": "video"})), None);
+        assert_eq!(ContentBlock::from_acp(&json!({"type": "image"})), None);
+    }
+
+    #[test]
+    fn test_detect_protocol() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(k, _)| *k == name)
+                    .map(|(_, v)| v.to_string())
+            }
+        };
+        assert_eq!(
+            ImageProtocol::detect_with(env(&[("TERM", "xterm-kitty")])),
+            ImageProtocol::Kitty
+        );
+        assert_eq!(
+            ImageProtocol::detect_with(env(&[("TERM_PROGRAM", "WezTerm")])),
+            ImageProtocol::Iterm2
+        );
+        assert_eq!(
+            ImageProtocol::detect_with(env(&[("TERM", "xterm-kitty"), ("TMUX", "/tmp/t")])),
+            ImageProtocol::None
+        );
+        assert_eq!(
+            ImageProtocol::detect_with(env(&[("TERM", "xterm-256color")])),
+            ImageProtocol::None
+        );
+        assert_eq!(
+            ImageProtocol::detect_with(env(&[("TERM", "foot")])),
+            ImageProtocol::Sixel
+        );
+        assert_eq!(
+            ImageProtocol::detect_with(env(&[("KONSOLE_VERSION", "230804")])),
+            ImageProtocol::Sixel
+        );
+        assert_eq!(
+            ImageProtocol::detect_with(env(&[("KONSOLE_VERSION", "210800")])),
+            ImageProtocol::None
+        );
+        assert_eq!(
+            ImageProtocol::from_device_attributes("\x1b[?64;1;2;4;6;22c"),
+            ImageProtocol::Sixel
+        );
+        // The first value is the terminal class, not an attribute
+        assert_eq!(
+            ImageProtocol::from_device_attributes("\x1b[?4;1c"),
+            ImageProtocol::None
+        );
+    }
+
+    #[test]
+    fn test_base64_and_dimensions() {
+        assert_eq!(base64_decode("aGVsbG8=").unwrap(), b"hello");
+        assert_eq!(base64_decode("aGVsbA").unwrap(), b"hell");
+        assert_eq!(base64_decode("a$==="), None);
+        assert_eq!(base64_decoded_len("aGVsbG8="), 5);
+        assert_eq!(image_dimensions(&base64_decode(PNG).unwrap()), Some((1, 1)));
+
+        let gif = b"GIF89a\x80\x02\xe0\x01";
+        assert_eq!(image_dimensions(gif), Some((640, 480)));
+        // SOI, an APP0 segment of length 4, then SOF0 with 480×640
+        let jpeg = [
+            0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00, 0xFF, 0xC0, 0x00, 0x11, 0x08, 0x01,
+            0xE0, 0x02, 0x80,
+        ];
+        assert_eq!(image_dimensions(&jpeg), Some((640, 480)));
+        assert_eq!(image_dimensions(b"nope"), None);
+    }
+
+    #[test]
+    fn test_image_protocols() {
+        colored::control::set_override(false);
+        let kitty = format_content_block_with(&image(PNG, "image/png"), ImageProtocol::Kitty);
+        assert_eq!(kitty, format!("\x1b_Ga=T,f=100,m=0;{PNG}\x1b\\\n"));
+
+        // Large images are split into chunks
+        let big = "A".repeat(KITTY_CHUNK_SIZE + 4);
+        let kitty = format_content_block_with(&image(&big, "image/png"), ImageProtocol::Kitty);
+        assert!(kitty.starts_with("\x1b_Ga=T,f=100,m=1;AAAA"));
+        assert!(kitty.ends_with("\x1b_Gm=0;AAAA\x1b\\\n"));
+
+        // Kitty only accepts PNG directly
+        let gif = "R0lGODlhgALgAQ==";
+        assert_eq!(
+            format_content_block_with(&image(gif, "image/gif"), ImageProtocol::Kitty),
+            "[image: 640×480 gif]\n"
+        );
+        assert_eq!(
+            format_content_block_with(&image(gif, "image/gif"), ImageProtocol::Iterm2),
+            format!("\x1b]1337;File=inline=1;size=10;preserveAspectRatio=1:{gif}\x07\n")
+        );
+        assert_eq!(
+            format_content_block_with(&image("???", "image/webp"), ImageProtocol::Iterm2),
+            "[image: webp]\n"
+        );
+        colored::control::unset_override();
+    }
+
+    #[test]
+    fn test_sixel() {
+        colored::control::set_override(false);
+        let sixel = format_content_block_with(&image(PNG, "image/png"), ImageProtocol::Sixel);
+        let (width, height, pixels) = decode_png(&base64_decode(PNG).unwrap()).unwrap();
+        assert_eq!((width, height), (1, 1));
+        assert_eq!(pixels, [[0, 255, 0, 127]]);
+        // The one pixel is more than half transparent, so nothing is painted
+        assert_eq!(sixel, "\x1bP0;1;0q\"1;1;1;1-\x1b\\\n");
+
+        // Sixel needs decoded pixels, so only PNG is drawn
+        let gif = "R0lGODlhgALgAQ==";
+        assert_eq!(
+            format_content_block_with(&image(gif, "image/gif"), ImageProtocol::Sixel),
+            "[image: 640×480 gif]\n"
+        );
+        colored::control::unset_override();
+    }
+
+    #[test]
+    fn test_encode_sixel() {
+        const RED: [u8; 4] = [255, 0, 0, 255];
+        const BLUE: [u8; 4] = [0, 0, 255, 255];
+        const CLEAR: [u8; 4] = [0, 0, 0, 0];
+        // Row 0 red, row 1 blue, rows 2-5 transparent, row 6 red
+        let mut pixels = vec![RED; 5];
+        pixels.extend([BLUE; 5]);
+        pixels.extend([CLEAR; 20]);
+        pixels.extend([RED; 5]);
+        assert_eq!(
+            encode_sixel(5, 7, &pixels),
+            "\x1bP0;1;0q\"1;1;5;7#5;2;0;0;100#180;2;100;0;0#5!5A$#180!5@-#180!5@-\x1b\\"
+        );
+
+        // Large images are sampled down to SIXEL_MAX_SIZE
+        let wide = vec![RED; 1600 * 2];
+        assert!(encode_sixel(1600, 2, &wide).starts_with("\x1bP0;1;0q\"1;1;800;1#"));
+    }
+
+    #[test]
+    fn test_run_length() {
+        let mut out = String::new();
+        push_run_length(&mut out, "??~~~~~A".chars());
+        assert_eq!(out, "??!5~A");
+    }
+
+    #[test]
+    fn test_resources_and_audio() {
+        colored::control::set_override(false);
+        let none = ImageProtocol::None;
+        let link = ResourceLink {
+            uri: "file:///work/spec.pdf".into(),
+            name: "spec.pdf".into(),
+            title: Some("Design spec".into()),
+            description: Some("Architecture overview".into()),
+            mime_type: Some("application/pdf".into()),
+            size: Some(1536),
+        };
+        let out = format_content_block_with(&ContentBlock::ResourceLink(link), none);
+        let out = crate::width::strip_ansi(&out).into_owned();
+        assert!(
+            out.starts_with("↗ Design spec (spec.pdf) · application/pdf · 1.5 KB"),
+            "got: {out:?}"
+        );
+        assert!(out.ends_with("\n  Architecture overview\n"), "got: {out:?}");
+
+        let blob = ContentBlock::Resource {
+            resource: EmbeddedResource::Blob {
+                uri: "file:///work/data.bin".into(),
+                mime_type: None,
+                blob: "AAAAAAAA".into(),
+            },
+        };
+        let out = format_content_block_with(&blob, none);
+        assert!(
+            crate::width::strip_ansi(&out).starts_with("[resource: data.bin · 6 B]"),
+            "got: {out:?}"
+        );
+
+        let audio = ContentBlock::Audio {
+            data: "A".repeat(4000),
+            mime_type: "audio/wav".into(),
+        };
+        assert_eq!(
+            format_content_block_with(&audio, none),
+            "[audio: wav · 2.9 KB]\n"
+        );
+        colored::control::unset_override();
+    }
+
+    #[test]
+    fn test_code_language_and_size() {
+        assert_eq!(code_language("file:///src/main.rs", None), "rs");
+        assert_eq!(
+            code_language("mem://notes", Some("text/x-python")),
+            "python"
+        );
+        assert_eq!(code_language("mem://notes", Some("text/plain")), "");
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(3 * 1024 * 1024 + 200 * 1024), "3.2 MB");
+    }
+}
@@ -0,0 +1,398 @@
+//! Cost estimation from token usage and a per-model pricing table.
+//!
+//! A [`PricingTable`] maps model names to [`ModelPricing`] rates (USD per
+//! million tokens) and can be loaded from a TOML file:
+//!
+//! ```toml
+//! [models."claude-sonnet-4"]
+//! input = 3.00
+//! output = 15.00
+//! cached = 0.30
+//!
+//! [models."gemini-2.5-flash"]
+//! input = 0.30
+//! output = 2.50
+//! ```
+//!
+//! A [`CostTracker`] accumulates per-turn and running totals, which are
+//! displayed with [`format_cost`](crate::format_cost).
+
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Add, AddAssign};
+use std::path::Path;
+
+use serde::Deserialize;
+
+// ============================================================================
+// Token Usage
+// ============================================================================
+
+/// Token counts reported for a single request (or accumulated over many).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenUsage {
+    /// Uncached input (prompt) tokens.
+    pub input: u64,
+    /// Output (completion) tokens.
+    pub output: u64,
+    /// Input tokens served from the prompt cache.
+    pub cached: u64,
+}
+
+impl TokenUsage {
+    /// Create a usage record from input, output and cached token counts.
+    pub fn new(input: u64, output: u64, cached: u64) -> Self {
+        Self {
+            input,
+            output,
+            cached,
+        }
+    }
+
+    /// Total number of tokens across all categories.
+    pub fn total(&self) -> u64 {
+        self.input + self.output + self.cached
+    }
+}
+
+impl Add for TokenUsage {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            input: self.input + rhs.input,
+            output: self.output + rhs.output,
+            cached: self.cached + rhs.cached,
+        }
+    }
+}
+
+impl AddAssign for TokenUsage {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+// ============================================================================
+// Pricing
+// ============================================================================
+
+/// Tokens per pricing unit (rates are quoted per million tokens).
+const TOKENS_PER_RATE_UNIT: f64 = 1_000_000.0;
+
+/// Per-model token rates in USD per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct ModelPricing {
+    /// Rate for uncached input tokens.
+    pub input: f64,
+    /// Rate for output tokens.
+    pub output: f64,
+    /// Rate for cached input tokens. Falls back to `input` when not set.
+    #[serde(default)]
+    pub cached: Option<f64>,
+}
+
+impl ModelPricing {
+    /// Create pricing from input, output and cached rates (USD per million tokens).
+    pub fn new(input: f64, output: f64, cached: Option<f64>) -> Self {
+        Self {
+            input,
+            output,
+            cached,
+        }
+    }
+
+    /// Cost in USD of the given usage at these rates.
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        let cached_rate = self.cached.unwrap_or(self.input);
+        (usage.input as f64 * self.input
+            + usage.output as f64 * self.output
+            + usage.cached as f64 * cached_rate)
+            / TOKENS_PER_RATE_UNIT
+    }
+}
+
+/// Error loading a [`PricingTable`].
+#[derive(Debug)]
+pub enum PricingError {
+    /// The pricing file could not be read.
+    Io(std::io::Error),
+    /// The pricing file is not valid TOML or has the wrong shape.
+    Parse(toml::de::Error),
+}
+
+impl fmt::Display for PricingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "failed to read pricing table: {e}"),
+            Self::Parse(e) => write!(f, "invalid pricing table: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for PricingError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Parse(e) => Some(e),
+        }
+    }
+}
+
+/// Pricing rates keyed by model name.
+///
+/// Lookups first try an exact match, then the longest configured name that
+/// is a prefix of the requested model (so `claude-sonnet-4` also prices
+/// `claude-sonnet-4-20250514`).
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct PricingTable {
+    #[serde(default)]
+    models: HashMap<String, ModelPricing>,
+}
+
+impl PricingTable {
+    /// Create an empty pricing table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a pricing table from TOML text.
+    pub fn from_toml_str(text: &str) -> Result<Self, PricingError> {
+        toml::from_str(text).map_err(PricingError::Parse)
+    }
+
+    /// Load a pricing table from a TOML file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PricingError> {
+        let text = std::fs::read_to_string(path).map_err(PricingError::Io)?;
+        Self::from_toml_str(&text)
+    }
+
+    /// Add or replace the rates for a model.
+    pub fn insert(&mut self, model: impl Into<String>, pricing: ModelPricing) {
+        self.models.insert(model.into(), pricing);
+    }
+
+    /// Look up the rates for a model (exact match, then longest prefix).
+    pub fn get(&self, model: &str) -> Option<&ModelPricing> {
+        if let Some(pricing) = self.models.get(model) {
+            return Some(pricing);
+        }
+        self.models
+            .iter()
+            .filter(|(name, _)| model.starts_with(name.as_str()))
+            .max_by_key(|(name, _)| name.len())
+            .map(|(_, pricing)| pricing)
+    }
+
+    /// Cost in USD of the given usage, or None if the model has no pricing.
+    pub fn cost(&self, model: &str, usage: &TokenUsage) -> Option<f64> {
+        self.get(model).map(|pricing| pricing.cost(usage))
+    }
+
+    /// Check if the table has no models.
+    pub fn is_empty(&self) -> bool {
+        self.models.is_empty()
+    }
+}
+
+// ============================================================================
+// Cost Tracking
+// ============================================================================
+
+/// Accumulates token usage and cost for the current turn and the session.
+///
+/// # Example
+///
+/// ```
+/// use clemitui::{CostTracker, ModelPricing, PricingTable, TokenUsage};
+///
+/// let mut table = PricingTable::new();
+/// table.insert("my-model", ModelPricing::new(3.0, 15.0, None));
+///
+/// let mut tracker = CostTracker::new();
+/// tracker.record(&table, "my-model", TokenUsage::new(1_000_000, 0, 0));
+/// assert_eq!(tracker.turn_cost(), 3.0);
+///
+/// tracker.start_turn();
+/// assert_eq!(tracker.turn_cost(), 0.0);
+/// assert_eq!(tracker.total_cost(), 3.0);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CostTracker {
+    turn_usage: TokenUsage,
+    total_usage: TokenUsage,
+    turn_cost: f64,
+    total_cost: f64,
+    unpriced: bool,
+}
+
+impl CostTracker {
+    /// Create a tracker with zero usage.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reset per-turn counters. Call at the start of each agent turn.
+    pub fn start_turn(&mut self) {
+        self.turn_usage = TokenUsage::default();
+        self.turn_cost = 0.0;
+    }
+
+    /// Record usage for a request, pricing it with the given table.
+    ///
+    /// Usage for models missing from the table is still counted, but adds no
+    /// cost and marks the totals as incomplete (see [`Self::is_complete`]).
+    pub fn record(&mut self, table: &PricingTable, model: &str, usage: TokenUsage) {
+        let cost = table.cost(model, &usage);
+        if cost.is_none() {
+            self.unpriced = true;
+        }
+        self.add(usage, cost.unwrap_or(0.0));
+    }
+
+    /// Record usage with an already-known cost (e.g. reported by the agent).
+    pub fn add(&mut self, usage: TokenUsage, cost: f64) {
+        self.turn_usage += usage;
+        self.total_usage += usage;
+        self.turn_cost += cost;
+        self.total_cost += cost;
+    }
+
+    /// Token usage for the current turn.
+    pub fn turn_usage(&self) -> TokenUsage {
+        self.turn_usage
+    }
+
+    /// Token usage for the whole session.
+    pub fn total_usage(&self) -> TokenUsage {
+        self.total_usage
+    }
+
+    /// Cost in USD for the current turn.
+    pub fn turn_cost(&self) -> f64 {
+        self.turn_cost
+    }
+
+    /// Cost in USD for the whole session.
+    pub fn total_cost(&self) -> f64 {
+        self.total_cost
+    }
+
+    /// Whether every recorded request had pricing available.
+    pub fn is_complete(&self) -> bool {
+        !self.unpriced
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_TOML: &str = r#"
+[models."claude-sonnet-4"]
+input = 3.0
+output = 15.0
+cached = 0.3
+
+[models."claude-sonnet-4-5"]
+input = 4.0
+output = 20.0
+
+[models."flash"]
+input = 0.5
+output = 2.0
+"#;
+
+    #[test]
+    fn test_token_usage_total_and_add() {
+        let mut usage = TokenUsage::new(10, 20, 5);
+        assert_eq!(usage.total(), 35);
+        usage += TokenUsage::new(1, 2, 3);
+        assert_eq!(usage, TokenUsage::new(11, 22, 8));
+    }
+
+    #[test]
+    fn test_model_pricing_cost() {
+        let pricing = ModelPricing::new(3.0, 15.0, Some(0.3));
+        let usage = TokenUsage::new(1_000_000, 100_000, 2_000_000);
+        // 3.0 + 1.5 + 0.6
+        assert!((pricing.cost(&usage) - 5.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_model_pricing_cached_falls_back_to_input() {
+        let pricing = ModelPricing::new(2.0, 8.0, None);
+        let usage = TokenUsage::new(0, 0, 500_000);
+        assert!((pricing.cost(&usage) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_pricing_table_from_toml() {
+        let table = PricingTable::from_toml_str(SAMPLE_TOML).unwrap();
+        let sonnet = table.get("claude-sonnet-4").unwrap();
+        assert_eq!(sonnet.cached, Some(0.3));
+        let flash = table.get("flash").unwrap();
+        assert_eq!(flash.cached, None);
+        assert!(table.get("unknown-model").is_none());
+    }
+
+    #[test]
+    fn test_pricing_table_longest_prefix_match() {
+        let table = PricingTable::from_toml_str(SAMPLE_TOML).unwrap();
+        assert_eq!(table.get("claude-sonnet-4-20250514").unwrap().input, 3.0);
+        assert_eq!(table.get("claude-sonnet-4-5-20250929").unwrap().input, 4.0);
+    }
+
+    #[test]
+    fn test_pricing_table_invalid_toml() {
+        let err = PricingTable::from_toml_str("[models.x]\ninput = \"cheap\"").unwrap_err();
+        assert!(matches!(err, PricingError::Parse(_)));
+        assert!(err.to_string().contains("invalid pricing table"));
+    }
+
+    #[test]
+    fn test_pricing_table_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pricing.toml");
+        std::fs::write(&path, SAMPLE_TOML).unwrap();
+        let table = PricingTable::load(&path).unwrap();
+        assert!(!table.is_empty());
+
+        let missing = PricingTable::load(dir.path().join("missing.toml")).unwrap_err();
+        assert!(matches!(missing, PricingError::Io(_)));
+    }
+
+    #[test]
+    fn test_cost_tracker_turns() {
+        let table = PricingTable::from_toml_str(SAMPLE_TOML).unwrap();
+        let mut tracker = CostTracker::new();
+
+        tracker.record(&table, "flash", TokenUsage::new(1_000_000, 0, 0));
+        tracker.record(&table, "flash", TokenUsage::new(0, 1_000_000, 0));
+        assert!((tracker.turn_cost() - 2.5).abs() < 1e-9);
+
+        tracker.start_turn();
+        tracker.record(&table, "flash", TokenUsage::new(2_000_000, 0, 0));
+        assert!((tracker.turn_cost() - 1.0).abs() < 1e-9);
+        assert!((tracker.total_cost() - 3.5).abs() < 1e-9);
+        assert_eq!(
+            tracker.total_usage(),
+            TokenUsage::new(3_000_000, 1_000_000, 0)
+        );
+        assert!(tracker.is_complete());
+    }
+
+    #[test]
+    fn test_cost_tracker_unpriced_model() {
+        let table = PricingTable::new();
+        let mut tracker = CostTracker::new();
+        tracker.record(&table, "mystery", TokenUsage::new(100, 100, 0));
+        assert_eq!(tracker.total_cost(), 0.0);
+        assert_eq!(tracker.total_usage().total(), 200);
+        assert!(!tracker.is_complete());
+    }
+}
@@ -0,0 +1,500 @@
+//! Line diffs of file contents.
+//!
+//! [`FileDiff::new`] compares two versions of a file line by line (Myers'
+//! algorithm) and groups the changes into hunks with surrounding context;
+//! [`format_diff`] renders them with line numbers, red removals and green
+//! additions.
+//!
+//! # Usage
+//!
+//! ```
+//! use clemitui::{FileDiff, format_diff};
+//!
+//! let diff = FileDiff::new("src/main.rs", "a\nb\nc\n", "a\nB\nc\n");
+//! assert_eq!((diff.added, diff.removed), (1, 1));
+//! print!("{}", format_diff(&diff));
+//! ```
+
+use std::path::PathBuf;
+
+use colored::Colorize;
+
+/// Unchanged lines shown around each change.
+const CONTEXT_LINES: usize = 3;
+
+/// Edit distance beyond which the changed region is shown as replaced
+/// wholesale instead of searching for a minimal diff.
+const MAX_EDIT_DISTANCE: usize = 2000;
+
+/// Diff lines [`format_diff`] shows before folding the rest.
+const MAX_DIFF_LINES: usize = 400;
+
+// ============================================================================
+// Diff Model
+// ============================================================================
+
+/// Whether a diff line is unchanged, added or removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffLineKind {
+    /// Present in both versions.
+    Context,
+    /// Only in the new version.
+    Added,
+    /// Only in the old version.
+    Removed,
+}
+
+/// One line of a hunk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffLine {
+    /// How the line changed.
+    pub kind: DiffLineKind,
+    /// Line text without its newline.
+    pub text: String,
+    /// 1-based line number in the old version (None for added lines).
+    pub old_line: Option<usize>,
+    /// 1-based line number in the new version (None for removed lines).
+    pub new_line: Option<usize>,
+}
+
+/// A group of nearby changes with context, like a unified diff hunk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffHunk {
+    /// 1-based first old line (0 when the hunk has no old lines).
+    pub old_start: usize,
+    /// Old lines covered.
+    pub old_len: usize,
+    /// 1-based first new line (0 when the hunk has no new lines).
+    pub new_start: usize,
+    /// New lines covered.
+    pub new_len: usize,
+    /// Lines in order.
+    pub lines: Vec<DiffLine>,
+}
+
+/// Changes between two versions of a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileDiff {
+    /// File the diff belongs to.
+    pub path: PathBuf,
+    /// Hunks in file order; empty when nothing changed.
+    pub hunks: Vec<DiffHunk>,
+    /// Number of added lines.
+    pub added: usize,
+    /// Number of removed lines.
+    pub removed: usize,
+    /// Whether the file does not exist yet.
+    pub is_new_file: bool,
+}
+
+impl FileDiff {
+    /// Diff `old` against `new`.
+    pub fn new(path: impl Into<PathBuf>, old: &str, new: &str) -> Self {
+        let old_lines: Vec<&str> = old.lines().collect();
+        let new_lines: Vec<&str> = new.lines().collect();
+        let ops = diff_ops(&old_lines, &new_lines);
+        let added = ops.iter().filter(|op| matches!(op, Op::Insert(_))).count();
+        let removed = ops.iter().filter(|op| matches!(op, Op::Delete(_))).count();
+        Self {
+            path: path.into(),
+            hunks: build_hunks(&ops, &old_lines, &new_lines),
+            added,
+            removed,
+            is_new_file: false,
+        }
+    }
+
+    /// Diff for creating a file with `content`.
+    pub fn new_file(path: impl Into<PathBuf>, content: &str) -> Self {
+        Self {
+            is_new_file: true,
+            ..Self::new(path, "", content)
+        }
+    }
+
+    /// Whether the versions are identical (line endings aside).
+    pub fn is_empty(&self) -> bool {
+        self.hunks.is_empty()
+    }
+}
+
+// ============================================================================
+// Myers Diff
+// ============================================================================
+
+/// Edit script step, by line index into the old or new version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Shortest edit script from `a` to `b`.
+fn diff_ops(a: &[&str], b: &[&str]) -> Vec<Op> {
+    // Common prefix and suffix need no search
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (a_mid, b_mid) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let mut ops: Vec<Op> = (0..prefix).map(|i| Op::Equal(i, i)).collect();
+    match myers(a_mid, b_mid) {
+        Some(mid) => ops.extend(mid.into_iter().map(|op| match op {
+            Op::Equal(i, j) => Op::Equal(i + prefix, j + prefix),
+            Op::Delete(i) => Op::Delete(i + prefix),
+            Op::Insert(j) => Op::Insert(j + prefix),
+        })),
+        None => {
+            ops.extend((0..a_mid.len()).map(|i| Op::Delete(i + prefix)));
+            ops.extend((0..b_mid.len()).map(|j| Op::Insert(j + prefix)));
+        }
+    }
+    ops.extend((0..suffix).map(|i| Op::Equal(a.len() - suffix + i, b.len() - suffix + i)));
+    ops
+}
+
+/// Myers' O((N+M)D) diff, or None if the edit distance exceeds
+/// [`MAX_EDIT_DISTANCE`].
+fn myers(a: &[&str], b: &[&str]) -> Option<Vec<Op>> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = (a.len() + b.len()).min(MAX_EDIT_DISTANCE) as isize;
+    // Furthest x on each diagonal k, indexed by k + offset
+    let offset = max + 1;
+    let mut v = vec![0isize; 2 * offset as usize + 1];
+    // v before each round d, kept for diagonals -(d+1)..=(d+1)
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    for d in 0..=max {
+        trace.push(v[(offset - d - 1) as usize..=(offset + d + 1) as usize].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let i = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && v[i - 1] < v[i + 1]) {
+                v[i + 1]
+            } else {
+                v[i - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[i] = x;
+            if x >= n && y >= m {
+                return Some(backtrack(&trace, n, m));
+            }
+        }
+    }
+    None
+}
+
+/// Walk the saved rounds back from (n, m) to recover the edit script.
+fn backtrack(trace: &[Vec<isize>], n: isize, m: isize) -> Vec<Op> {
+    let mut ops = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let at = |k: isize| v[(k + d + 1) as usize];
+        let k = x - y;
+        let (prev_x, prev_y) = if d == 0 {
+            (0, 0)
+        } else {
+            let prev_k = if k == -d || (k != d && at(k - 1) < at(k + 1)) {
+                k + 1
+            } else {
+                k - 1
+            };
+            (at(prev_k), at(prev_k) - prev_k)
+        };
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            ops.push(Op::Equal(x as usize, y as usize));
+        }
+        if d > 0 {
+            if x == prev_x {
+                ops.push(Op::Insert(prev_y as usize));
+            } else {
+                ops.push(Op::Delete(prev_x as usize));
+            }
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    ops.reverse();
+    ops
+}
+
+/// Group an edit script into hunks with [`CONTEXT_LINES`] of context.
+fn build_hunks(ops: &[Op], old: &[&str], new: &[&str]) -> Vec<DiffHunk> {
+    let changes: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, Op::Equal(..)))
+        .map(|(i, _)| i)
+        .collect();
+
+    // Merge changes whose context windows touch
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for &i in &changes {
+        let start = i.saturating_sub(CONTEXT_LINES);
+        let end = (i + CONTEXT_LINES + 1).min(ops.len());
+        match ranges.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => ranges.push((start, end)),
+        }
+    }
+
+    ranges
+        .into_iter()
+        .map(|(start, end)| {
+            let lines: Vec<DiffLine> = ops[start..end]
+                .iter()
+                .map(|&op| match op {
+                    Op::Equal(i, j) => DiffLine {
+                        kind: DiffLineKind::Context,
+                        text: old[i].to_string(),
+                        old_line: Some(i + 1),
+                        new_line: Some(j + 1),
+                    },
+                    Op::Delete(i) => DiffLine {
+                        kind: DiffLineKind::Removed,
+                        text: old[i].to_string(),
+                        old_line: Some(i + 1),
+                        new_line: None,
+                    },
+                    Op::Insert(j) => DiffLine {
+                        kind: DiffLineKind::Added,
+                        text: new[j].to_string(),
+                        old_line: None,
+                        new_line: Some(j + 1),
+                    },
+                })
+                .collect();
+            let old_len = lines.iter().filter(|l| l.old_line.is_some()).count();
+            let new_len = lines.iter().filter(|l| l.new_line.is_some()).count();
+            // Position of the hunk in each file, even when it has no lines there
+            let (old_before, new_before) =
+                ops[..start].iter().fold((0, 0), |(o, n), op| match op {
+                    Op::Equal(..) => (o + 1, n + 1),
+                    Op::Delete(_) => (o + 1, n),
+                    Op::Insert(_) => (o, n + 1),
+                });
+            DiffHunk {
+                old_start: if old_len == 0 {
+                    old_before
+                } else {
+                    old_before + 1
+                },
+                old_len,
+                new_start: if new_len == 0 {
+                    new_before
+                } else {
+                    new_before + 1
+                },
+                new_len,
+                lines,
+            }
+        })
+        .collect()
+}
+
+// ============================================================================
+// Formatting
+// ============================================================================
+
+/// Format a diff for display before a write.
+///
+/// Starts with a `path +added -removed` header (`(new file)` when creating),
+/// then each hunk's `@@` range and its lines with line numbers. Diffs longer
+/// than 400 lines are cut with a `… N more lines` marker.
+///
+/// # Example
+///
+/// ```
+/// use clemitui::{FileDiff, format_diff};
+///
+/// colored::control::set_override(false);
+/// let diff = FileDiff::new("notes.txt", "one\ntwo\n", "one\n2\n");
+/// assert_eq!(
+///     format_diff(&diff),
+///     "  notes.txt +1 -1\n  @@ -1,2 +1,2 @@\n  1   one\n  2 - two\n  2 + 2\n"
+/// );
+/// colored::control::unset_override();
+/// ```
+pub fn format_diff(diff: &FileDiff) -> String {
+    let mut out = format!(
+        "  {} {} {}",
+        diff.path.display().to_string().bold(),
+        format!("+{}", diff.added).green(),
+        format!("-{}", diff.removed).red()
+    );
+    if diff.is_new_file {
+        out.push_str(&format!(" {}", "(new file)".dimmed()));
+    } else if diff.is_empty() {
+        out.push_str(&format!(" {}", "(no changes)".dimmed()));
+    }
+    out.push('\n');
+
+    let number_width = diff
+        .hunks
+        .iter()
+        .flat_map(|hunk| &hunk.lines)
+        .filter_map(|line| line.new_line.max(line.old_line))
+        .max()
+        .map_or(1, |n| n.to_string().len());
+
+    let total: usize = diff.hunks.iter().map(|hunk| hunk.lines.len() + 1).sum();
+    let mut shown = 0;
+    'hunks: for hunk in &diff.hunks {
+        for line in std::iter::once(None).chain(hunk.lines.iter().map(Some)) {
+            if shown == MAX_DIFF_LINES {
+                out.push_str(&format!(
+                    "  {}\n",
+                    format!("… {} more lines", total - shown).dimmed()
+                ));
+                break 'hunks;
+            }
+            shown += 1;
+            let Some(line) = line else {
+                let range = format!(
+                    "@@ -{},{} +{},{} @@",
+                    hunk.old_start, hunk.old_len, hunk.new_start, hunk.new_len
+                );
+                out.push_str(&format!("  {}\n", range.cyan()));
+                continue;
+            };
+            let (number, sign) = match line.kind {
+                DiffLineKind::Context => (line.new_line, ' '),
+                DiffLineKind::Added => (line.new_line, '+'),
+                DiffLineKind::Removed => (line.old_line, '-'),
+            };
+            let number = format!("{:>number_width$}", number.unwrap_or(0)).dimmed();
+            let body = format!("{} {}", sign, line.text);
+            let body = match line.kind {
+                DiffLineKind::Context => body.dimmed(),
+                DiffLineKind::Added => body.green(),
+                DiffLineKind::Removed => body.red(),
+            };
+            out.push_str(&format!("  {} {}\n", number, body));
+        }
+    }
+    out
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Apply the diff's hunks to `old` and check the result is `new`.
+    fn roundtrip(old: &str, new: &str) -> FileDiff {
+        let diff = FileDiff::new("f", old, new);
+        let old_lines: Vec<&str> = old.lines().collect();
+        let mut out: Vec<String> = Vec::new();
+        let mut next_old = 0;
+        for hunk in &diff.hunks {
+            // old_start is 1-based, except for hunks that only insert
+            let start = if hunk.old_len == 0 {
+                hunk.old_start
+            } else {
+                hunk.old_start - 1
+            };
+            out.extend(old_lines[next_old..start].iter().map(|s| s.to_string()));
+            for line in &hunk.lines {
+                if line.kind != DiffLineKind::Removed {
+                    out.push(line.text.clone());
+                }
+            }
+            next_old = start + hunk.old_len;
+        }
+        out.extend(old_lines[next_old..].iter().map(|s| s.to_string()));
+        assert_eq!(out, new.lines().collect::<Vec<_>>(), "diff: {diff:#?}");
+        diff
+    }
+
+    #[test]
+    fn test_identical() {
+        let diff = roundtrip("a\nb\n", "a\nb\n");
+        assert!(diff.is_empty());
+        assert_eq!((diff.added, diff.removed), (0, 0));
+    }
+
+    #[test]
+    fn test_minimal_edits() {
+        let diff = roundtrip("a\nb\nc\nd\n", "a\nc\nd\ne\n");
+        assert_eq!((diff.added, diff.removed), (1, 1));
+        let diff = roundtrip("x\ny\nz\n", "p\nx\nq\nz\nr\n");
+        assert_eq!((diff.added, diff.removed), (3, 1));
+        roundtrip("", "new\nfile\n");
+        roundtrip("gone\n", "");
+        roundtrip("a\nb\na\nb\na\n", "b\na\nb\na\nb\n");
+    }
+
+    #[test]
+    fn test_hunks_split_by_context() {
+        let old: String = (1..=20).map(|i| format!("{i}\n")).collect();
+        let edit = |changes: &[(usize, &str)]| -> String {
+            (1..=20)
+                .map(|i| match changes.iter().find(|(n, _)| *n == i) {
+                    Some((_, text)) => format!("{text}\n"),
+                    None => format!("{i}\n"),
+                })
+                .collect()
+        };
+        let new = edit(&[(2, "two"), (18, "eighteen")]);
+        let diff = roundtrip(&old, &new);
+        assert_eq!(diff.hunks.len(), 2);
+        assert_eq!((diff.hunks[0].old_start, diff.hunks[0].old_len), (1, 5));
+        assert_eq!(
+            (
+                diff.hunks[1].old_start,
+                diff.hunks[1].old_len,
+                diff.hunks[1].new_start
+            ),
+            (15, 6, 15)
+        );
+
+        // Changes within twice the context merge into one hunk
+        let new = edit(&[(5, "five"), (10, "ten")]);
+        assert_eq!(roundtrip(&old, &new).hunks.len(), 1);
+    }
+
+    #[test]
+    fn test_large_distance_falls_back_to_replace() {
+        let old: String = (0..2500).map(|i| format!("old {i}\n")).collect();
+        let new: String = (0..2500).map(|i| format!("new {i}\n")).collect();
+        let diff = FileDiff::new("f", &old, &new);
+        assert_eq!((diff.added, diff.removed), (2500, 2500));
+    }
+
+    #[test]
+    fn test_format_new_file_and_truncation() {
+        colored::control::set_override(false);
+        let diff = FileDiff::new_file("a.txt", "x\n");
+        assert_eq!(
+            format_diff(&diff),
+            "  a.txt +1 -0 (new file)\n  @@ -0,0 +1,1 @@\n  1 + x\n"
+        );
+
+        let content: String = (0..500).map(|i| format!("{i}\n")).collect();
+        let out = format_diff(&FileDiff::new_file("big", &content));
+        assert!(
+            out.ends_with("  … 101 more lines\n"),
+            "got: {}",
+            &out[out.len() - 40..]
+        );
+        assert_eq!(
+            format_diff(&FileDiff::new("same", "a\n", "a\n")),
+            "  same +0 -0 (no changes)\n"
+        );
+        colored::control::unset_override();
+    }
+}
@@ -0,0 +1,450 @@
+//! Client-side ACP file system methods.
+//!
+//! Agents read and write files through the client with `fs/read_text_file`
+//! and `fs/write_text_file`, so that edits go through the editor rather than
+//! behind its back. An [`FsHandler`] implements both methods on the local
+//! file system:
+//!
+//! - reads honor the optional 1-based `line` and `limit` parameters;
+//! - unsaved editor buffers registered with [`FsHandler::set_override`] are
+//!   read instead of the file on disk;
+//! - before writing, the new content is diffed against the current content
+//!   and the [`FileDiff`] is passed to an optional [`WriteApprover`], which
+//!   can show it with [`format_diff`](crate::format_diff) and ask the user
+//!   for permission.
+//!
+//! # Usage
+//!
+//! ```no_run
+//! use clemitui::{FileDiff, FsHandler, format_diff};
+//! use serde_json::json;
+//!
+//! let handler = FsHandler::new().with_approver(|diff: &FileDiff| {
+//!     print!("{}", format_diff(diff));
+//!     true
+//! });
+//! handler
+//!     .handle(
+//!         "fs/write_text_file",
+//!         &json!({"sessionId": "s1", "path": "/tmp/notes.txt", "content": "hello\n"}),
+//!     )
+//!     .unwrap();
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::diff::FileDiff;
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Errors from the file system methods.
+#[derive(Debug)]
+pub enum FsError {
+    /// The path is not absolute, as ACP requires.
+    RelativePath(PathBuf),
+    /// Reading or writing the file failed.
+    Io {
+        /// The file being accessed.
+        path: PathBuf,
+        /// The underlying error.
+        source: io::Error,
+    },
+    /// The approver declined the write.
+    Rejected(PathBuf),
+    /// The request parameters are malformed.
+    InvalidParams(serde_json::Error),
+    /// The method is not an `fs/*` method.
+    UnknownMethod(String),
+}
+
+impl FsError {
+    /// JSON-RPC error code for this error.
+    pub fn code(&self) -> i64 {
+        match self {
+            Self::UnknownMethod(_) => -32601,
+            Self::RelativePath(_) | Self::InvalidParams(_) => -32602,
+            Self::Io { .. } | Self::Rejected(_) => -32603,
+        }
+    }
+}
+
+impl fmt::Display for FsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RelativePath(path) => write!(f, "path must be absolute: {}", path.display()),
+            Self::Io { path, source } => write!(f, "{}: {source}", path.display()),
+            Self::Rejected(path) => write!(f, "write to {} was rejected", path.display()),
+            Self::InvalidParams(e) => write!(f, "invalid file system request: {e}"),
+            Self::UnknownMethod(method) => write!(f, "unknown method: {method}"),
+        }
+    }
+}
+
+impl std::error::Error for FsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::InvalidParams(e) => Some(e),
+            Self::RelativePath(_) | Self::Rejected(_) | Self::UnknownMethod(_) => None,
+        }
+    }
+}
+
+// ============================================================================
+// Requests
+// ============================================================================
+
+/// Parameters of `fs/read_text_file`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadTextFileRequest {
+    /// Absolute path of the file.
+    pub path: PathBuf,
+    /// 1-based line to start reading at.
+    #[serde(default)]
+    pub line: Option<u32>,
+    /// Maximum number of lines to read.
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+impl ReadTextFileRequest {
+    /// Request the whole file.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            line: None,
+            limit: None,
+        }
+    }
+
+    /// Start reading at a 1-based line.
+    pub fn with_line(mut self, line: u32) -> Self {
+        self.line = Some(line);
+        self
+    }
+
+    /// Read at most `limit` lines.
+    pub fn with_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
+
+/// Parameters of `fs/write_text_file`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct WriteTextFileRequest {
+    /// Absolute path of the file.
+    pub path: PathBuf,
+    /// New content of the whole file.
+    pub content: String,
+}
+
+impl WriteTextFileRequest {
+    /// Request to replace the file with `content`.
+    pub fn new(path: impl Into<PathBuf>, content: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            content: content.into(),
+        }
+    }
+}
+
+// ============================================================================
+// Write Approval
+// ============================================================================
+
+/// Decides whether a write may proceed, given what it changes.
+///
+/// Implemented for closures, so a client can pass a function that shows the
+/// diff and asks for permission.
+pub trait WriteApprover: Send + Sync {
+    /// Return true to write the file.
+    fn approve(&self, diff: &FileDiff) -> bool;
+}
+
+impl<F> WriteApprover for F
+where
+    F: Fn(&FileDiff) -> bool + Send + Sync,
+{
+    fn approve(&self, diff: &FileDiff) -> bool {
+        self(diff)
+    }
+}
+
+// ============================================================================
+// File System Handler
+// ============================================================================
+
+/// Implements the ACP `fs/*` methods on the local file system.
+#[derive(Default)]
+pub struct FsHandler {
+    overrides: Mutex<HashMap<PathBuf, String>>,
+    approver: Option<Box<dyn WriteApprover>>,
+}
+
+impl fmt::Debug for FsHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FsHandler")
+            .field("overrides", &self.overrides().keys().collect::<Vec<_>>())
+            .field("approver", &self.approver.is_some())
+            .finish()
+    }
+}
+
+impl FsHandler {
+    /// Create a handler that writes without asking.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask `approver` before each write.
+    pub fn with_approver(mut self, approver: impl WriteApprover + 'static) -> Self {
+        self.approver = Some(Box::new(approver));
+        self
+    }
+
+    /// Serve `content` for `path` instead of the file on disk, as for an
+    /// editor buffer with unsaved changes.
+    pub fn set_override(&self, path: impl Into<PathBuf>, content: impl Into<String>) {
+        self.overrides().insert(path.into(), content.into());
+    }
+
+    /// Stop overriding `path`; returns the buffer content if there was one.
+    pub fn clear_override(&self, path: &Path) -> Option<String> {
+        self.overrides().remove(path)
+    }
+
+    /// Dispatch an `fs/*` JSON-RPC request and return its result.
+    pub fn handle(&self, method: &str, params: &Value) -> Result<Value, FsError> {
+        match method {
+            "fs/read_text_file" => {
+                let request =
+                    ReadTextFileRequest::deserialize(params).map_err(FsError::InvalidParams)?;
+                Ok(json!({"content": self.read_text_file(&request)?}))
+            }
+            "fs/write_text_file" => {
+                let request =
+                    WriteTextFileRequest::deserialize(params).map_err(FsError::InvalidParams)?;
+                self.write_text_file(&request).map(|_| json!({}))
+            }
+            _ => Err(FsError::UnknownMethod(method.to_string())),
+        }
+    }
+
+    /// Read a file, or the requested range of its lines.
+    pub fn read_text_file(&self, request: &ReadTextFileRequest) -> Result<String, FsError> {
+        let content = self
+            .current_content(&request.path)?
+            .ok_or_else(|| FsError::Io {
+                path: request.path.clone(),
+                source: io::ErrorKind::NotFound.into(),
+            })?;
+        if request.line.is_none() && request.limit.is_none() {
+            return Ok(content);
+        }
+        let skip = request
+            .line
+            .map_or(0, |line| line.saturating_sub(1) as usize);
+        let take = request.limit.map_or(usize::MAX, |limit| limit as usize);
+        Ok(content
+            .split_inclusive('\n')
+            .skip(skip)
+            .take(take)
+            .collect())
+    }
+
+    /// Diff a prospective write against the current content.
+    pub fn diff(&self, request: &WriteTextFileRequest) -> Result<FileDiff, FsError> {
+        Ok(match self.current_content(&request.path)? {
+            Some(old) => FileDiff::new(&request.path, &old, &request.content),
+            None => FileDiff::new_file(&request.path, &request.content),
+        })
+    }
+
+    /// Write a file after the approver (if any) accepts its diff, creating
+    /// missing parent directories. Returns the diff that was applied.
+    ///
+    /// The write goes to disk, so any override of the path is dropped.
+    pub fn write_text_file(&self, request: &WriteTextFileRequest) -> Result<FileDiff, FsError> {
+        let diff = self.diff(request)?;
+        if let Some(approver) = &self.approver
+            && !approver.approve(&diff)
+        {
+            return Err(FsError::Rejected(request.path.clone()));
+        }
+        let io_error = |source| FsError::Io {
+            path: request.path.clone(),
+            source,
+        };
+        if let Some(parent) = request.path.parent() {
+            fs::create_dir_all(parent).map_err(io_error)?;
+        }
+        fs::write(&request.path, &request.content).map_err(io_error)?;
+        self.overrides().remove(&request.path);
+        Ok(diff)
+    }
+
+    /// The override or on-disk content of `path`, or None if it does not exist.
+    fn current_content(&self, path: &Path) -> Result<Option<String>, FsError> {
+        if !path.is_absolute() {
+            return Err(FsError::RelativePath(path.to_path_buf()));
+        }
+        if let Some(content) = self.overrides().get(path) {
+            return Ok(Some(content.clone()));
+        }
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(Some(content)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(source) => Err(FsError::Io {
+                path: path.to_path_buf(),
+                source,
+            }),
+        }
+    }
+
+    fn overrides(&self) -> MutexGuard<'_, HashMap<PathBuf, String>> {
+        self.overrides.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[test]
+    fn test_read_line_range() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("f.txt");
+        fs::write(&path, "one\ntwo\nthree\nfour").unwrap();
+        let handler = FsHandler::new();
+
+        let read = |request: ReadTextFileRequest| handler.read_text_file(&request).unwrap();
+        assert_eq!(
+            read(ReadTextFileRequest::new(&path)),
+            "one\ntwo\nthree\nfour"
+        );
+        assert_eq!(
+            read(ReadTextFileRequest::new(&path).with_line(2).with_limit(2)),
+            "two\nthree\n"
+        );
+        assert_eq!(read(ReadTextFileRequest::new(&path).with_line(4)), "four");
+        assert_eq!(read(ReadTextFileRequest::new(&path).with_limit(1)), "one\n");
+        assert_eq!(read(ReadTextFileRequest::new(&path).with_line(9)), "");
+    }
+
+    #[test]
+    fn test_overrides_shadow_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("f.txt");
+        fs::write(&path, "saved\n").unwrap();
+        let handler = FsHandler::new();
+
+        handler.set_override(&path, "unsaved\n");
+        let request = ReadTextFileRequest::new(&path);
+        assert_eq!(handler.read_text_file(&request).unwrap(), "unsaved\n");
+        let diff = handler
+            .diff(&WriteTextFileRequest::new(&path, "unsaved\nmore\n"))
+            .unwrap();
+        assert_eq!((diff.added, diff.removed), (1, 0));
+
+        assert_eq!(handler.clear_override(&path).as_deref(), Some("unsaved\n"));
+        assert_eq!(handler.read_text_file(&request).unwrap(), "saved\n");
+    }
+
+    #[test]
+    fn test_write_creates_and_drops_override() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sub/new.txt");
+        let handler = FsHandler::new();
+
+        let diff = handler
+            .write_text_file(&WriteTextFileRequest::new(&path, "a\n"))
+            .unwrap();
+        assert!(diff.is_new_file);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "a\n");
+
+        handler.set_override(&path, "buffer\n");
+        handler
+            .write_text_file(&WriteTextFileRequest::new(&path, "b\n"))
+            .unwrap();
+        assert_eq!(
+            handler
+                .read_text_file(&ReadTextFileRequest::new(&path))
+                .unwrap(),
+            "b\n"
+        );
+    }
+
+    #[test]
+    fn test_approver_gates_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("f.txt");
+        fs::write(&path, "old\n").unwrap();
+        let allow = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&allow);
+        let handler = FsHandler::new()
+            .with_approver(move |diff: &FileDiff| diff.removed == 1 && flag.load(Ordering::SeqCst));
+
+        let request = WriteTextFileRequest::new(&path, "new\n");
+        let err = handler.write_text_file(&request).unwrap_err();
+        assert!(matches!(err, FsError::Rejected(_)));
+        assert_eq!(err.code(), -32603);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "old\n");
+
+        allow.store(true, Ordering::SeqCst);
+        handler.write_text_file(&request).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new\n");
+    }
+
+    #[test]
+    fn test_handle_dispatch_and_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("f.txt");
+        let handler = FsHandler::new();
+
+        let result = handler
+            .handle(
+                "fs/write_text_file",
+                &json!({"sessionId": "s", "path": path, "content": "x\ny\n"}),
+            )
+            .unwrap();
+        assert_eq!(result, json!({}));
+        let result = handler
+            .handle(
+                "fs/read_text_file",
+                &json!({"sessionId": "s", "path": path, "line": 2}),
+            )
+            .unwrap();
+        assert_eq!(result, json!({"content": "y\n"}));
+
+        let err = |method, params| handler.handle(method, &params).unwrap_err();
+        assert_eq!(
+            err("fs/read_text_file", json!({"path": "rel.txt"})).code(),
+            -32602
+        );
+        assert_eq!(err("fs/read_text_file", json!({})).code(), -32602);
+        assert_eq!(err("fs/delete", json!({})).code(), -32601);
+        let missing = err(
+            "fs/read_text_file",
+            json!({"path": dir.path().join("nope")}),
+        );
+        assert!(matches!(missing, FsError::Io { .. }));
+    }
+}
@@ -0,0 +1,2008 @@
+//! Optional full-screen mode on the alternate screen.
+//!
+//! Line mode prints events and lets the terminal scroll them away. For long
+//! sessions [`Fullscreen`] instead takes over the alternate screen with three
+//! areas:
+//!
+//! - a scrollable transcript pane (mouse wheel, `PageUp`/`PageDown`),
+//! - a prompt at the bottom, edited with the same keys as [`LineEditor`],
+//! - a tool-call sidebar listing running and finished tools (`F2` toggles it).
+//!
+//! Each tool call is a foldable block in the transcript, collapsed by default
+//! to its one-line `└─` summary. `Alt+Up`/`Alt+Down` select a block, `Ctrl+O`
+//! expands or collapses the selected (or latest) one to show its arguments,
+//! output and diff, and clicking a block toggles it. Fold state lives with the
+//! transcript, so it survives redraws and resizes.
+//!
+//! `Ctrl+F` opens incremental search: type a regex after the `/`, `Enter`
+//! keeps it, then `n`/`N` jump to the next/previous match and `Esc` closes it.
+//! Matches are highlighted across agent text, tool arguments and output, and
+//! folded tool blocks holding a match are expanded. [`FullscreenView::viewer`]
+//! shows a transcript without a prompt (e.g. to replay a recorded session),
+//! where `/` searches directly, arrows and `j`/`k` scroll and `q` quits.
+//!
+//! The transcript is fed by the same event stream as line mode: install
+//! [`FullscreenView::sink`] with [`set_output_sink`] and everything logged
+//! through [`log_event`] lands in the pane. Tool start (`┌─`) and result
+//! (`└─`) lines are picked out of that stream to populate the sidebar.
+//!
+//! The terminal is restored (main screen, cooked mode, mouse capture off) when
+//! the [`Fullscreen`] guard is dropped, and a panic hook restores it if the
+//! process panics while the mode is active. `std::process::exit` skips
+//! destructors, so call [`Fullscreen::leave`] before exiting that way.
+//!
+//! [`LineEditor`]: crate::LineEditor
+//! [`set_output_sink`]: crate::set_output_sink
+//! [`log_event`]: crate::log_event
+
+use std::io::{self, Stdout, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, Once};
+use std::time::Duration;
+
+use colored::Colorize;
+use crossterm::event::{
+    self, Event, KeyCode, KeyEventKind, KeyModifiers, MouseButton, MouseEventKind,
+};
+use crossterm::terminal;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::logging::OutputSink;
+use crate::prompt::{History, PromptAction, PromptEditor, layout};
+use crate::search::{SearchMatch, TranscriptSearch};
+use crate::width::{display_width, segments, strip_ansi, truncate_end};
+
+/// Transcript lines kept before the oldest are dropped, except in viewers.
+const MAX_TRANSCRIPT_LINES: usize = 10_000;
+
+/// Rows scrolled per mouse wheel notch.
+const WHEEL_SCROLL_ROWS: usize = 3;
+
+/// Widest the tool sidebar gets, in columns.
+const SIDEBAR_WIDTH: usize = 30;
+
+/// Narrowest terminal that still shows the sidebar.
+const MIN_SIDEBAR_TERMINAL_WIDTH: usize = 40;
+
+/// Switch to the alternate screen and enable mouse reporting (SGR encoding)
+/// and bracketed paste.
+const ENTER_SEQUENCE: &str = "\x1b[?1049h\x1b[?1000h\x1b[?1006h\x1b[?2004h";
+
+/// Undo [`ENTER_SEQUENCE`] and show the cursor again.
+const LEAVE_SEQUENCE: &str = "\x1b[?2004l\x1b[?1006l\x1b[?1000l\x1b[?1049l\x1b[?25h";
+
+// ============================================================================
+// Transcript
+// ============================================================================
+
+/// State of a tool call shown in the sidebar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToolStatus {
+    /// The tool is executing.
+    Running,
+    /// The tool finished successfully.
+    Done,
+    /// The tool finished with an error.
+    Failed,
+}
+
+/// A tool call shown in the sidebar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolEntry {
+    /// Tool label as shown on its `┌─` line (kind marker included).
+    pub name: String,
+    /// Current state.
+    pub status: ToolStatus,
+}
+
+/// A tool call and the lines it produced, foldable to a single line.
+#[derive(Debug, Clone)]
+struct ToolBlock {
+    tool: ToolEntry,
+    /// Start line, body, output, result line and continuation lines.
+    lines: Vec<String>,
+    /// Index of the `└─` result line in `lines`, once the tool has finished.
+    summary: Option<usize>,
+    expanded: bool,
+}
+
+impl ToolBlock {
+    /// The line shown while collapsed: the result, or the start line while
+    /// the tool is running.
+    fn summary_line(&self) -> &str {
+        &self.lines[self.summary.unwrap_or(0)]
+    }
+}
+
+/// A transcript entry: a line of prose or a tool call block.
+#[derive(Debug, Clone)]
+enum Entry {
+    Line(String),
+    Tool(ToolBlock),
+}
+
+impl Entry {
+    fn line_count(&self) -> usize {
+        match self {
+            Entry::Line(_) => 1,
+            Entry::Tool(block) => block.lines.len(),
+        }
+    }
+}
+
+/// Lines and tool calls received from the event stream.
+#[derive(Debug)]
+struct Transcript {
+    entries: Vec<Entry>,
+    line_count: usize,
+    /// Lines kept before the oldest are dropped; `None` keeps everything.
+    max_lines: Option<usize>,
+    /// Whether new tool blocks start expanded.
+    expand_blocks: bool,
+    /// Tool block that continuation lines attach to.
+    attach: Option<usize>,
+    /// Selected tool block, for folding from the keyboard.
+    selected: Option<usize>,
+    /// Bumped on every change so the terminal guard knows when to redraw.
+    generation: u64,
+}
+
+impl Default for Transcript {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            line_count: 0,
+            max_lines: Some(MAX_TRANSCRIPT_LINES),
+            expand_blocks: false,
+            attach: None,
+            selected: None,
+            generation: 0,
+        }
+    }
+}
+
+impl Transcript {
+    fn push(&mut self, text: &str) {
+        for line in text.strip_suffix('\n').unwrap_or(text).split('\n') {
+            self.push_line(line);
+        }
+        let mut dropped = 0;
+        let max_lines = self.max_lines.unwrap_or(usize::MAX);
+        while self.line_count > max_lines && dropped < self.entries.len() {
+            self.line_count -= self.entries[dropped].line_count();
+            dropped += 1;
+        }
+        if dropped > 0 {
+            self.entries.drain(..dropped);
+            let shift = |index: Option<usize>| index.and_then(|i| i.checked_sub(dropped));
+            self.attach = shift(self.attach);
+            self.selected = shift(self.selected);
+        }
+        self.generation += 1;
+    }
+
+    /// Add one line, grouping tool start, result and continuation lines into
+    /// tool blocks.
+    ///
+    /// Continuation lines (tool bodies, output, diffs, error details) start
+    /// with `│` or are indented; they attach to the tool that most recently
+    /// started or finished. Once other lines come in between, only tool output
+    /// (see [`is_tool_output`]) still reaches the latest running tool, so
+    /// indented agent text such as plans and code blocks stays prose. Error
+    /// details (`  └─ error: ...`) are indented, so only unindented `└─` lines
+    /// finish a tool.
+    fn push_line(&mut self, line: &str) {
+        let plain = strip_ansi(line);
+        if let Some(rest) = plain.strip_prefix("┌─ ") {
+            self.attach = Some(self.entries.len());
+            self.entries.push(Entry::Tool(ToolBlock {
+                tool: ToolEntry {
+                    name: tool_label(rest),
+                    status: ToolStatus::Running,
+                },
+                lines: vec![line.to_string()],
+                summary: None,
+                expanded: self.expand_blocks,
+            }));
+            self.line_count += 1;
+            return;
+        }
+
+        let target = if let Some(rest) = plain.strip_prefix("└─ ") {
+            self.finish_tool(rest)
+        } else if let Some(index) = self.attach.filter(|_| plain.starts_with(['│', ' '])) {
+            Some(index)
+        } else if is_tool_output(&plain) {
+            self.running_blocks().next()
+        } else {
+            None
+        };
+        match target.map(|index| &mut self.entries[index]) {
+            Some(Entry::Tool(block)) => block.lines.push(line.to_string()),
+            _ => {
+                self.attach = None;
+                self.entries.push(Entry::Line(line.to_string()));
+            }
+        }
+        self.line_count += 1;
+    }
+
+    /// Indices of running tool blocks, newest first.
+    fn running_blocks(&self) -> impl Iterator<Item = usize> + '_ {
+        self.entries
+            .iter()
+            .enumerate()
+            .rev()
+            .filter_map(|(i, entry)| match entry {
+                Entry::Tool(block) if block.tool.status == ToolStatus::Running => Some(i),
+                _ => None,
+            })
+    }
+
+    /// Mark the tool a `└─` line belongs to as finished and return its block.
+    ///
+    /// The newest running tool with the same label wins, falling back to the
+    /// newest running tool.
+    fn finish_tool(&mut self, rest: &str) -> Option<usize> {
+        let label = tool_label(rest);
+        let index = self
+            .running_blocks()
+            .find(|&i| matches!(&self.entries[i], Entry::Tool(block) if block.tool.name == label))
+            .or_else(|| self.running_blocks().next())?;
+        if let Entry::Tool(block) = &mut self.entries[index] {
+            block.tool.status = if rest.trim_end().ends_with(" ERROR") {
+                ToolStatus::Failed
+            } else {
+                ToolStatus::Done
+            };
+            block.summary = Some(block.lines.len());
+        }
+        self.attach = Some(index);
+        Some(index)
+    }
+
+    /// Indices of all tool blocks, oldest first.
+    fn blocks(&self) -> impl DoubleEndedIterator<Item = usize> + '_ {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, entry)| matches!(entry, Entry::Tool(_)).then_some(i))
+    }
+
+    fn tools(&self) -> Vec<ToolEntry> {
+        self.entries
+            .iter()
+            .filter_map(|entry| match entry {
+                Entry::Tool(block) => Some(block.tool.clone()),
+                Entry::Line(_) => None,
+            })
+            .collect()
+    }
+
+    /// Expand a collapsed tool block or collapse an expanded one.
+    fn toggle(&mut self, index: usize) {
+        if let Some(Entry::Tool(block)) = self.entries.get_mut(index) {
+            block.expanded = !block.expanded;
+            self.generation += 1;
+        }
+    }
+}
+
+/// Whether `plain` is a tool body line (`│ ...`) or a line of tool output,
+/// which is indented by exactly three spaces.
+fn is_tool_output(plain: &str) -> bool {
+    plain.starts_with('│')
+        || plain
+            .strip_prefix("   ")
+            .is_some_and(|rest| !rest.is_empty() && !rest.starts_with(' '))
+}
+
+/// Tool label at the start of a `┌─`/`└─` line: the tool name, preceded by
+/// its kind marker if it has one.
+fn tool_label(rest: &str) -> String {
+    let mut words = rest.split_whitespace();
+    let first = words.next().unwrap_or_default();
+    let is_marker = !first.chars().any(|c| c.is_alphanumeric() || c == '_');
+    match words.next() {
+        Some(name) if is_marker => format!("{} {}", first, name),
+        _ => first.to_string(),
+    }
+}
+
+/// [`OutputSink`] that appends to a [`FullscreenView`]'s transcript.
+///
+/// Created by [`FullscreenView::sink`]. Blocks get a trailing blank line, like
+/// line mode.
+#[derive(Debug, Clone)]
+pub struct FullscreenSink {
+    transcript: Arc<Mutex<Transcript>>,
+}
+
+impl OutputSink for FullscreenSink {
+    fn emit(&self, message: &str) {
+        if let Ok(mut transcript) = self.transcript.lock() {
+            transcript.push(&format!("{}\n\n", message));
+        }
+    }
+
+    fn emit_line(&self, message: &str) {
+        if let Ok(mut transcript) = self.transcript.lock() {
+            transcript.push(message);
+        }
+    }
+}
+
+// ============================================================================
+// Layout Helpers
+// ============================================================================
+
+/// Hard-wrap `line` into rows of at most `width` columns.
+///
+/// Colors active at a break are closed at the end of the row and reopened at
+/// the start of the next, so every row can be drawn on its own.
+fn wrap_ansi(line: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let line = line.replace('\t', "    ");
+    let mut rows = Vec::new();
+    let mut current = String::new();
+    let mut current_width = 0;
+    let mut active = String::new();
+    for (segment, is_escape) in segments(&line) {
+        if is_escape {
+            if segment == "\x1b[0m" || segment == "\x1b[m" {
+                active.clear();
+            } else if segment.starts_with("\x1b[") && segment.ends_with('m') {
+                active.push_str(segment);
+            }
+            current.push_str(segment);
+            continue;
+        }
+        for grapheme in segment.graphemes(true) {
+            let grapheme_width = display_width(grapheme);
+            if current_width + grapheme_width > width && current_width > 0 {
+                if !active.is_empty() {
+                    current.push_str("\x1b[0m");
+                }
+                rows.push(std::mem::replace(&mut current, active.clone()));
+                current_width = 0;
+            }
+            current.push_str(grapheme);
+            current_width += grapheme_width;
+        }
+    }
+    rows.push(current);
+    rows
+}
+
+/// Suffix on a tool block's first visible line: `[+N]` when collapsed with N
+/// hidden lines, `[−]` when expanded, and a pointer when selected.
+fn fold_marker(block: &ToolBlock, selected: bool) -> String {
+    let hidden = block.lines.len() - 1;
+    let mut marker = if block.expanded && hidden > 0 {
+        format!(" {}", "[−]".dimmed())
+    } else if hidden > 0 {
+        format!(" {}", format!("[+{}]", hidden).dimmed())
+    } else {
+        String::new()
+    };
+    if selected {
+        marker.push_str(&format!(" {}", "◀".cyan().bold()));
+    }
+    marker
+}
+
+/// A transcript row: wrapped text, the entry it belongs to and the index of
+/// its line among [`FullscreenView::lines`].
+#[derive(Debug)]
+struct Row {
+    entry: usize,
+    line: usize,
+    text: String,
+}
+
+/// The transcript wrapped to the pane width, kept until the transcript, fold
+/// state, selection, width or search changes.
+#[derive(Debug)]
+struct WrappedTranscript {
+    key: WrapKey,
+    rows: Vec<Row>,
+    /// Position of the current search match, see [`TranscriptSearch::position`].
+    position: Option<(usize, usize)>,
+}
+
+/// Everything [`WrappedTranscript`] depends on.
+#[derive(Debug, PartialEq)]
+struct WrapKey {
+    generation: u64,
+    width: usize,
+    selected: Option<usize>,
+    search: Option<(String, Option<SearchMatch>)>,
+    colored: bool,
+}
+
+/// Search bar state: the pattern being typed or kept, and its matches.
+#[derive(Debug, Default)]
+struct SearchBar {
+    input: String,
+    /// Whether keys go to `input` (after `/`) or to match navigation.
+    editing: bool,
+    search: Option<TranscriptSearch>,
+    error: Option<String>,
+    /// Last visible line when the search was opened; matches are looked for
+    /// at or above it while typing.
+    origin: usize,
+}
+
+/// Escape sequence moving the cursor to zero-based `(row, col)`.
+fn goto(row: usize, col: usize) -> String {
+    format!("\x1b[{};{}H", row + 1, col + 1)
+}
+
+// ============================================================================
+// Full-Screen View
+// ============================================================================
+
+/// Full-screen layout state: transcript, scroll position, sidebar and prompt.
+///
+/// The view is pure state with no terminal access: feed it events with
+/// [`Self::handle_event`] and draw [`Self::render`]. [`Fullscreen`] does both
+/// on a real terminal.
+///
+/// # Example
+///
+/// ```
+/// use clemitui::{FullscreenView, OutputSink, PromptAction};
+/// use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+///
+/// colored::control::set_override(false);
+/// let mut view = FullscreenView::new("> ").with_size(60, 10);
+/// view.sink().emit("assistant: hello");
+///
+/// view.handle_event(&Event::Paste("hi".to_string()));
+/// let enter = Event::Key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+/// assert_eq!(view.handle_event(&enter), PromptAction::Submit("hi".to_string()));
+///
+/// assert!(view.render().contains("assistant: hello"));
+/// colored::control::unset_override();
+/// ```
+#[derive(Debug)]
+pub struct FullscreenView {
+    transcript: Arc<Mutex<Transcript>>,
+    editor: PromptEditor,
+    prompt: String,
+    cols: usize,
+    rows: usize,
+    /// Rows scrolled up from the bottom; 0 follows new output.
+    scroll: usize,
+    sidebar_visible: bool,
+    /// No prompt: keys browse the transcript.
+    read_only: bool,
+    search_bar: Option<SearchBar>,
+    /// Rows from the last layout; drawing and scrolling reuse them.
+    wrapped: Mutex<Option<Arc<WrappedTranscript>>>,
+}
+
+impl FullscreenView {
+    /// Create an 80x24 view with the given prompt and in-memory history.
+    pub fn new(prompt: impl Into<String>) -> Self {
+        Self {
+            transcript: Arc::default(),
+            editor: PromptEditor::new(),
+            prompt: prompt.into(),
+            cols: 80,
+            rows: 24,
+            scroll: 0,
+            sidebar_visible: true,
+            read_only: false,
+            search_bar: None,
+            wrapped: Mutex::default(),
+        }
+    }
+
+    /// Create a read-only 80x24 view with no prompt, for browsing a
+    /// transcript (e.g. a recorded session fed through [`Self::sink`]).
+    ///
+    /// Unlike [`Self::new`], the transcript is never trimmed and tool blocks
+    /// start expanded, so the whole text is shown.
+    ///
+    /// Arrows, `j`/`k`, `PageUp`/`PageDown`, `Space`/`b` and `g`/`G` scroll,
+    /// `/` searches and `q` ends the session with [`PromptAction::Eof`].
+    pub fn viewer() -> Self {
+        Self {
+            transcript: Arc::new(Mutex::new(Transcript {
+                max_lines: None,
+                expand_blocks: true,
+                ..Transcript::default()
+            })),
+            read_only: true,
+            ..Self::new("")
+        }
+    }
+
+    /// Use `history` for the prompt.
+    pub fn with_history(mut self, history: History) -> Self {
+        self.editor = PromptEditor::with_history(history);
+        self
+    }
+
+    /// Set the terminal size the view is laid out for.
+    pub fn with_size(mut self, cols: u16, rows: u16) -> Self {
+        self.resize(cols, rows);
+        self
+    }
+
+    /// Show or hide the tool sidebar initially.
+    pub fn with_sidebar(mut self, visible: bool) -> Self {
+        self.sidebar_visible = visible;
+        self
+    }
+
+    /// A sink that appends to this view's transcript.
+    pub fn sink(&self) -> FullscreenSink {
+        FullscreenSink {
+            transcript: Arc::clone(&self.transcript),
+        }
+    }
+
+    fn transcript(&self) -> MutexGuard<'_, Transcript> {
+        self.transcript
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Transcript lines received so far, with tool blocks expanded.
+    pub fn lines(&self) -> Vec<String> {
+        let transcript = self.transcript();
+        let mut lines = Vec::with_capacity(transcript.line_count);
+        for entry in &transcript.entries {
+            match entry {
+                Entry::Line(line) => lines.push(line.clone()),
+                Entry::Tool(block) => lines.extend(block.lines.iter().cloned()),
+            }
+        }
+        lines
+    }
+
+    /// Tool calls seen so far, oldest first.
+    pub fn tools(&self) -> Vec<ToolEntry> {
+        self.transcript().tools()
+    }
+
+    /// Position of the selected tool block among [`Self::tools`].
+    pub fn selected_tool(&self) -> Option<usize> {
+        let transcript = self.transcript();
+        let selected = transcript.selected?;
+        transcript.blocks().position(|i| i == selected)
+    }
+
+    /// Whether the tool block at `index` (among [`Self::tools`]) is expanded.
+    pub fn is_expanded(&self, index: usize) -> bool {
+        let transcript = self.transcript();
+        let block = transcript.blocks().nth(index);
+        matches!(block.map(|i| &transcript.entries[i]), Some(Entry::Tool(block)) if block.expanded)
+    }
+
+    /// Select the tool block before the selected one (the latest if none is
+    /// selected) and scroll it into view.
+    pub fn select_previous_tool(&mut self) {
+        let mut transcript = self.transcript();
+        let selected = match transcript.selected {
+            Some(selected) => transcript
+                .blocks()
+                .rev()
+                .find(|&i| i < selected)
+                .or(Some(selected)),
+            None => transcript.blocks().next_back(),
+        };
+        transcript.selected = selected;
+        drop(transcript);
+        self.scroll_to_selected();
+    }
+
+    /// Select the tool block after the selected one; past the latest block
+    /// the selection is cleared.
+    pub fn select_next_tool(&mut self) {
+        let mut transcript = self.transcript();
+        if let Some(selected) = transcript.selected {
+            let next = transcript.blocks().find(|&i| i > selected);
+            transcript.selected = next;
+        }
+        drop(transcript);
+        self.scroll_to_selected();
+    }
+
+    /// Expand or collapse the selected tool block, or the latest one if none
+    /// is selected.
+    pub fn toggle_tool(&mut self) {
+        let mut transcript = self.transcript();
+        if let Some(index) = transcript
+            .selected
+            .or_else(|| transcript.blocks().next_back())
+        {
+            transcript.toggle(index);
+        }
+        drop(transcript);
+        self.scroll_to_selected();
+    }
+
+    /// Expand or collapse every tool block.
+    pub fn set_all_expanded(&mut self, expanded: bool) {
+        let mut transcript = self.transcript();
+        for entry in &mut transcript.entries {
+            if let Entry::Tool(block) = entry {
+                block.expanded = expanded;
+            }
+        }
+        transcript.generation += 1;
+    }
+
+    /// The active search, once a valid pattern has been typed.
+    pub fn search(&self) -> Option<&TranscriptSearch> {
+        self.search_bar.as_ref()?.search.as_ref()
+    }
+
+    /// Search for `pattern` and jump to the last match in or above the
+    /// visible part of the transcript, as if it had been typed after `/`.
+    pub fn start_search(&mut self, pattern: &str) {
+        self.open_search_bar();
+        if let Some(bar) = &mut self.search_bar {
+            bar.input = pattern.to_string();
+            bar.editing = false;
+        }
+        self.update_search();
+    }
+
+    /// Jump to the next match of the active search.
+    pub fn next_match(&mut self) {
+        self.step_search(true);
+    }
+
+    /// Jump to the previous match of the active search.
+    pub fn previous_match(&mut self) {
+        self.step_search(false);
+    }
+
+    /// Close the search bar and remove the highlights.
+    pub fn close_search(&mut self) {
+        self.search_bar = None;
+    }
+
+    /// Open an empty search bar taking input.
+    fn open_search_bar(&mut self) {
+        let origin = self.bottom_line();
+        self.search_bar = Some(SearchBar {
+            editing: true,
+            origin,
+            ..SearchBar::default()
+        });
+    }
+
+    /// Recompile the typed pattern and jump to its first match.
+    fn update_search(&mut self) {
+        let lines = self.lines();
+        let Some(bar) = &mut self.search_bar else {
+            return;
+        };
+        bar.error = None;
+        bar.search = None;
+        if bar.input.is_empty() {
+            return;
+        }
+        match TranscriptSearch::new(&bar.input) {
+            Ok(mut search) => {
+                let found = search.select_before(&lines, bar.origin);
+                bar.search = Some(search);
+                if let Some(found) = found {
+                    self.reveal_line(found.line);
+                }
+            }
+            Err(err) => bar.error = Some(err.to_string()),
+        }
+    }
+
+    fn step_search(&mut self, forward: bool) {
+        let lines = self.lines();
+        let Some(search) = self.search_bar.as_mut().and_then(|bar| bar.search.as_mut()) else {
+            return;
+        };
+        let found = if forward {
+            search.next_match(&lines)
+        } else {
+            search.previous_match(&lines)
+        };
+        if let Some(found) = found {
+            self.reveal_line(found.line);
+        }
+    }
+
+    /// Index (among [`Self::lines`]) of the last visible transcript line.
+    fn bottom_line(&self) -> usize {
+        let rows = &self.transcript_rows().rows;
+        let end = rows.len() - self.scroll_offset();
+        end.checked_sub(1).map_or(0, |last| rows[last].line)
+    }
+
+    /// Scroll line `line` (among [`Self::lines`]) into view, expanding the
+    /// folded tool block that hides it.
+    fn reveal_line(&mut self, line: usize) {
+        {
+            let mut transcript = self.transcript();
+            let mut first = 0;
+            for entry in &mut transcript.entries {
+                let count = entry.line_count();
+                if line < first + count {
+                    if let Entry::Tool(block) = entry
+                        && !block.expanded
+                        && block.summary.unwrap_or(0) != line - first
+                    {
+                        block.expanded = true;
+                        transcript.generation += 1;
+                    }
+                    break;
+                }
+                first += count;
+            }
+        }
+        let rows = &self.transcript_rows().rows;
+        if let Some(row) = rows.iter().position(|row| row.line == line) {
+            self.scroll_to_row(row, rows.len());
+        }
+    }
+
+    /// The prompt editor.
+    pub fn editor(&self) -> &PromptEditor {
+        &self.editor
+    }
+
+    /// Rows scrolled up from the bottom of the transcript (0 when following).
+    pub fn scroll_offset(&self) -> usize {
+        self.scroll.min(self.max_scroll())
+    }
+
+    /// Whether the tool sidebar is shown.
+    pub fn sidebar_visible(&self) -> bool {
+        self.sidebar_visible
+    }
+
+    /// Show or hide the tool sidebar.
+    pub fn set_sidebar_visible(&mut self, visible: bool) {
+        self.sidebar_visible = visible;
+    }
+
+    /// Adapt the layout to a new terminal size.
+    pub fn resize(&mut self, cols: u16, rows: u16) {
+        self.cols = cols.max(1) as usize;
+        self.rows = rows.max(1) as usize;
+    }
+
+    /// Scroll the transcript up by `rows`.
+    pub fn scroll_up(&mut self, rows: usize) {
+        let max_scroll = self.max_scroll();
+        self.scroll = (self.scroll.min(max_scroll) + rows).min(max_scroll);
+    }
+
+    /// Scroll the transcript down by `rows`, back to following at the bottom.
+    pub fn scroll_down(&mut self, rows: usize) {
+        self.scroll = self.scroll_offset().saturating_sub(rows);
+    }
+
+    /// Keep the selected tool block's first row within the transcript pane.
+    fn scroll_to_selected(&mut self) {
+        let Some(selected) = self.transcript().selected else {
+            return;
+        };
+        let rows = &self.transcript_rows().rows;
+        if let Some(row) = rows.iter().position(|row| row.entry == selected) {
+            self.scroll_to_row(row, rows.len());
+        }
+    }
+
+    /// Scroll as little as needed to show transcript row `row` of `total`.
+    fn scroll_to_row(&mut self, row: usize, total: usize) {
+        let height = self.pane_height().max(1);
+        let end = total - self.scroll_offset();
+        if row < end.saturating_sub(height) {
+            self.scroll = total - row - height;
+        } else if row >= end {
+            self.scroll = total - row - 1;
+        }
+        self.scroll = self.scroll.min(self.max_scroll());
+    }
+
+    /// Toggle the tool block drawn on screen row `row`, selecting it.
+    fn click(&mut self, row: usize) {
+        let rows = &self.transcript_rows().rows;
+        let end = rows.len() - self.scroll_offset();
+        let start = end.saturating_sub(self.pane_height());
+        let Some(&Row { entry, .. }) = rows[start..end].get(row) else {
+            return;
+        };
+        let mut transcript = self.transcript();
+        if matches!(transcript.entries[entry], Entry::Tool(_)) {
+            transcript.selected = Some(entry);
+            transcript.toggle(entry);
+        }
+    }
+
+    /// Handle a terminal event.
+    ///
+    /// Scroll, sidebar, tool folding and search keys are handled here;
+    /// everything else goes to the prompt editor, whose action is returned.
+    /// Submitting a prompt clears the tool selection and scrolls back to the
+    /// bottom.
+    pub fn handle_event(&mut self, event: &Event) -> PromptAction {
+        if let Some(action) = self.handle_search_event(event) {
+            return action;
+        }
+        match event {
+            Event::Mouse(mouse) => match mouse.kind {
+                MouseEventKind::ScrollUp => self.scroll_up(WHEEL_SCROLL_ROWS),
+                MouseEventKind::ScrollDown => self.scroll_down(WHEEL_SCROLL_ROWS),
+                MouseEventKind::Down(MouseButton::Left) => self.click(mouse.row as usize),
+                _ => {}
+            },
+            Event::Resize(cols, rows) => self.resize(*cols, *rows),
+            Event::Key(key)
+                if key.kind != KeyEventKind::Release && key.modifiers == KeyModifiers::ALT =>
+            {
+                match key.code {
+                    KeyCode::Up => self.select_previous_tool(),
+                    KeyCode::Down => self.select_next_tool(),
+                    _ => return self.handle_prompt_event(event),
+                }
+            }
+            Event::Key(key)
+                if key.kind != KeyEventKind::Release && key.modifiers == KeyModifiers::CONTROL =>
+            {
+                match key.code {
+                    KeyCode::Char('o') => self.toggle_tool(),
+                    KeyCode::Char('f') => self.open_search_bar(),
+                    _ => return self.handle_prompt_event(event),
+                }
+            }
+            Event::Key(key) if key.kind != KeyEventKind::Release && key.modifiers.is_empty() => {
+                let page = self.pane_height().saturating_sub(1).max(1);
+                match key.code {
+                    KeyCode::PageUp => self.scroll_up(page),
+                    KeyCode::PageDown => self.scroll_down(page),
+                    KeyCode::F(2) => self.sidebar_visible = !self.sidebar_visible,
+                    _ => return self.handle_prompt_event(event),
+                }
+            }
+            _ => return self.handle_prompt_event(event),
+        }
+        PromptAction::Continue
+    }
+
+    /// Keys for the search bar and, in a viewer, for browsing. Returns `None`
+    /// for events left to the regular handling.
+    fn handle_search_event(&mut self, event: &Event) -> Option<PromptAction> {
+        if let Some(bar) = self.search_bar.as_mut().filter(|bar| bar.editing) {
+            match event {
+                Event::Paste(text) => bar.input.push_str(&text.replace('\n', " ")),
+                Event::Key(key) if key.kind != KeyEventKind::Release => {
+                    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+                    match key.code {
+                        KeyCode::Esc => self.search_bar = None,
+                        KeyCode::Char('c') if ctrl => self.search_bar = None,
+                        KeyCode::Enter if bar.input.is_empty() => self.search_bar = None,
+                        KeyCode::Enter => bar.editing = false,
+                        KeyCode::Backspace if bar.input.is_empty() => self.search_bar = None,
+                        KeyCode::Backspace => {
+                            bar.input.pop();
+                        }
+                        KeyCode::Char(c) if !ctrl && !key.modifiers.contains(KeyModifiers::ALT) => {
+                            bar.input.push(c)
+                        }
+                        _ => return Some(PromptAction::Continue),
+                    }
+                }
+                _ => return None,
+            }
+            self.update_search();
+            return Some(PromptAction::Continue);
+        }
+
+        let Event::Key(key) = event else {
+            return None;
+        };
+        if key.kind == KeyEventKind::Release
+            || key
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+        {
+            return None;
+        }
+        let searching = self.search_bar.is_some();
+        let page = self.pane_height().saturating_sub(1).max(1);
+        match key.code {
+            KeyCode::Char('n') if searching => self.next_match(),
+            KeyCode::Char('N') if searching => self.previous_match(),
+            KeyCode::Char('/') if searching || self.read_only => self.open_search_bar(),
+            KeyCode::Esc if searching => self.close_search(),
+            _ if !self.read_only => {
+                // Typing returns to the prompt
+                if matches!(
+                    key.code,
+                    KeyCode::Char(_) | KeyCode::Enter | KeyCode::Backspace
+                ) {
+                    self.search_bar = None;
+                }
+                return None;
+            }
+            KeyCode::Char('q') | KeyCode::Esc => return Some(PromptAction::Eof),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll_down(1),
+            KeyCode::Up | KeyCode::Char('k') => self.scroll_up(1),
+            KeyCode::Char(' ') => self.scroll_down(page),
+            KeyCode::Char('b') => self.scroll_up(page),
+            KeyCode::Home | KeyCode::Char('g') => self.scroll_up(usize::MAX / 2),
+            KeyCode::End | KeyCode::Char('G') => self.scroll = 0,
+            _ => return None,
+        }
+        Some(PromptAction::Continue)
+    }
+
+    fn handle_prompt_event(&mut self, event: &Event) -> PromptAction {
+        if self.read_only {
+            return match event {
+                Event::Key(key)
+                    if key.code == KeyCode::Char('c')
+                        && key.modifiers.contains(KeyModifiers::CONTROL) =>
+                {
+                    PromptAction::Cancel
+                }
+                _ => PromptAction::Continue,
+            };
+        }
+        let action = self.editor.handle_event(event);
+        if matches!(action, PromptAction::Submit(_)) {
+            self.transcript().selected = None;
+            self.scroll = 0;
+        }
+        action
+    }
+
+    /// Columns taken by the sidebar (0 when hidden), excluding its border.
+    fn sidebar_width(&self) -> usize {
+        if self.sidebar_visible && self.cols >= MIN_SIDEBAR_TERMINAL_WIDTH {
+            (self.cols / 3).min(SIDEBAR_WIDTH)
+        } else {
+            0
+        }
+    }
+
+    /// Columns available to the transcript.
+    fn main_width(&self) -> usize {
+        match self.sidebar_width() {
+            0 => self.cols,
+            sidebar => self.cols - sidebar - 1,
+        }
+    }
+
+    /// Prompt rows to show and the cursor's (row, column) within them.
+    ///
+    /// The prompt takes at most half the screen; taller prompts are cut to
+    /// the rows around the cursor.
+    fn prompt_rows(&self) -> (Vec<String>, (usize, usize)) {
+        let (lines, cursor) = layout(&self.editor, &self.prompt, "… ", None, self.cols);
+        let mut rows: Vec<String> = lines
+            .iter()
+            .flat_map(|line| wrap_ansi(line, self.cols))
+            .collect();
+        while rows.len() <= cursor.0 {
+            rows.push(String::new());
+        }
+        let limit = (self.rows / 2).max(1);
+        let start = (cursor.0 + 1).saturating_sub(limit);
+        let end = (start + limit).min(rows.len());
+        (rows[start..end].to_vec(), (cursor.0 - start, cursor.1))
+    }
+
+    /// The search bar row and the cursor column while typing a pattern.
+    fn search_row(&self, bar: &SearchBar) -> (String, Option<usize>) {
+        let status = match (&bar.error, &bar.search) {
+            (Some(error), _) => error.red().to_string(),
+            (None, Some(_)) => match self.transcript_rows().position {
+                Some((_, 0)) | None => "no matches".yellow().to_string(),
+                Some((index, total)) => format!("{}/{}", index, total).dimmed().to_string(),
+            },
+            (None, None) => String::new(),
+        };
+        let input = format!("/{}", bar.input);
+        let hint = if bar.editing {
+            ""
+        } else {
+            "  n next · N previous · Esc close"
+        };
+        let row = format!("{}  {}{}", input, status, hint.dimmed());
+        let cursor = bar
+            .editing
+            .then(|| display_width(&input).min(self.cols.saturating_sub(1)));
+        (truncate_end(&row, self.cols, "…").into_owned(), cursor)
+    }
+
+    /// Rows below the rule (prompt, search bar or viewer help) and the
+    /// cursor's (row, column) within them, if it is shown.
+    fn footer_rows(&self) -> (Vec<String>, Option<(usize, usize)>) {
+        if let Some(bar) = &self.search_bar {
+            let (row, cursor) = self.search_row(bar);
+            return (vec![row], cursor.map(|col| (0, col)));
+        }
+        if self.read_only {
+            let help = "q quit · / search · ↑↓ scroll · Ctrl+O fold";
+            return (
+                vec![truncate_end(help, self.cols, "…").dimmed().to_string()],
+                None,
+            );
+        }
+        let (rows, cursor) = self.prompt_rows();
+        (rows, Some(cursor))
+    }
+
+    /// Rows available to the transcript pane.
+    fn pane_height(&self) -> usize {
+        let (footer, _) = self.footer_rows();
+        self.rows.saturating_sub(footer.len() + 1)
+    }
+
+    /// Transcript wrapped to the pane width, laid out again only when
+    /// something it depends on changed.
+    fn transcript_rows(&self) -> Arc<WrappedTranscript> {
+        let key = {
+            let transcript = self.transcript();
+            WrapKey {
+                generation: transcript.generation,
+                width: self.main_width(),
+                selected: transcript.selected,
+                search: self
+                    .search()
+                    .map(|search| (search.pattern().to_string(), search.current())),
+                colored: colored::control::SHOULD_COLORIZE.should_colorize(),
+            }
+        };
+        let mut cache = self.wrapped.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(wrapped) = cache.as_ref().filter(|wrapped| wrapped.key == key) {
+            return Arc::clone(wrapped);
+        }
+        let wrapped = Arc::new(WrappedTranscript {
+            rows: self.wrap_transcript(key.width),
+            position: self.search().map(|search| search.position(&self.lines())),
+            key,
+        });
+        *cache = Some(Arc::clone(&wrapped));
+        wrapped
+    }
+
+    /// Transcript wrapped to `width`.
+    ///
+    /// Collapsed tool blocks show only their summary line; a fold marker says
+    /// how many lines are hidden, and the selected block is marked. Matches of
+    /// the active search are highlighted.
+    fn wrap_transcript(&self, width: usize) -> Vec<Row> {
+        let search = self.search();
+        let styled = |line: usize, text: &str| match search {
+            Some(search) => search.highlight(line, text),
+            None => text.to_string(),
+        };
+        let transcript = self.transcript();
+        let mut rows = Vec::new();
+        let mut first = 0;
+        for (entry, item) in transcript.entries.iter().enumerate() {
+            let lines: Vec<(usize, String)> = match item {
+                Entry::Line(text) => vec![(first, styled(first, text))],
+                Entry::Tool(block) => {
+                    let marker = fold_marker(block, transcript.selected == Some(entry));
+                    let mut lines: Vec<(usize, String)> = if block.expanded {
+                        (first..)
+                            .zip(&block.lines)
+                            .map(|(line, text)| (line, styled(line, text)))
+                            .collect()
+                    } else {
+                        let line = first + block.summary.unwrap_or(0);
+                        vec![(line, styled(line, block.summary_line()))]
+                    };
+                    lines[0].1.push_str(&marker);
+                    lines
+                }
+            };
+            for (line, text) in lines {
+                rows.extend(wrap_ansi(&text, width).into_iter().map(|text| Row {
+                    entry,
+                    line,
+                    text,
+                }));
+            }
+            first += item.line_count();
+        }
+        rows
+    }
+
+    fn max_scroll(&self) -> usize {
+        self.transcript_rows()
+            .rows
+            .len()
+            .saturating_sub(self.pane_height())
+    }
+
+    /// Sidebar rows: a header, then the most recent tools that fit.
+    fn sidebar_rows(&self, height: usize, width: usize) -> Vec<String> {
+        let tools = self.tools();
+        let running = tools
+            .iter()
+            .filter(|tool| tool.status == ToolStatus::Running)
+            .count();
+        let header = format!("Tools {}/{}", running, tools.len());
+        let mut rows = vec![truncate_end(&header, width, "…").bold().to_string()];
+        let shown = height.saturating_sub(1);
+        for tool in &tools[tools.len().saturating_sub(shown)..] {
+            let marker = match tool.status {
+                ToolStatus::Running => "●".yellow(),
+                ToolStatus::Done => "✓".green(),
+                ToolStatus::Failed => "✗".red(),
+            };
+            let name = truncate_end(&tool.name, width.saturating_sub(2), "…");
+            rows.push(format!("{} {}", marker, name));
+        }
+        rows
+    }
+
+    /// Escape sequence drawing the whole screen, leaving the cursor in the
+    /// prompt or search bar (hidden in a viewer).
+    pub fn render(&self) -> String {
+        let (footer, cursor) = self.footer_rows();
+        let pane_height = self.rows.saturating_sub(footer.len() + 1);
+        let main_width = self.main_width();
+        let sidebar_width = self.sidebar_width();
+
+        let wrapped = self.transcript_rows();
+        let transcript = &wrapped.rows;
+        let scroll = self
+            .scroll
+            .min(transcript.len().saturating_sub(pane_height));
+        let end = transcript.len() - scroll;
+        let visible: Vec<&str> = transcript[end.saturating_sub(pane_height)..end]
+            .iter()
+            .map(|row| row.text.as_str())
+            .collect();
+        let sidebar = if sidebar_width > 0 {
+            self.sidebar_rows(pane_height, sidebar_width - 1)
+        } else {
+            Vec::new()
+        };
+
+        let mut out = String::from("\x1b[?25l");
+        for row in 0..pane_height {
+            out.push_str(&goto(row, 0));
+            out.push_str("\x1b[2K");
+            if let Some(line) = visible.get(row) {
+                out.push_str(line);
+                out.push_str("\x1b[0m");
+            }
+            if sidebar_width > 0 {
+                out.push_str(&goto(row, main_width));
+                out.push_str(&"│".dimmed().to_string());
+                if let Some(entry) = sidebar.get(row) {
+                    out.push(' ');
+                    out.push_str(entry);
+                }
+            }
+        }
+
+        let label = if scroll > 0 {
+            format!("── ↓ {} more ", scroll)
+        } else {
+            String::new()
+        };
+        let rule = "─".repeat(self.cols.saturating_sub(display_width(&label)));
+        out.push_str(&goto(pane_height, 0));
+        out.push_str("\x1b[2K");
+        out.push_str(
+            &truncate_end(&format!("{}{}", label, rule), self.cols, "")
+                .dimmed()
+                .to_string(),
+        );
+
+        for (i, line) in footer.iter().enumerate() {
+            out.push_str(&goto(pane_height + 1 + i, 0));
+            out.push_str("\x1b[2K");
+            out.push_str(line);
+        }
+        if let Some((row, col)) = cursor {
+            out.push_str(&goto(pane_height + 1 + row, col));
+            out.push_str("\x1b[?25h");
+        }
+        out
+    }
+}
+
+// ============================================================================
+// Panic Safety
+// ============================================================================
+
+/// Whether a [`Fullscreen`] owning the real terminal is active.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+static PANIC_HOOK: Once = Once::new();
+
+/// Install (once) a panic hook that restores the terminal from an active
+/// full-screen session before the default hook prints the panic message.
+fn install_panic_hook() {
+    PANIC_HOOK.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |panic_info| {
+            if ACTIVE.swap(false, Ordering::SeqCst) {
+                let mut stdout = io::stdout();
+                let _ = stdout.write_all(LEAVE_SEQUENCE.as_bytes());
+                let _ = stdout.flush();
+                let _ = terminal::disable_raw_mode();
+            }
+            previous(panic_info);
+        }));
+    });
+}
+
+// ============================================================================
+// Terminal Guard
+// ============================================================================
+
+/// A [`FullscreenView`] drawn on the alternate screen.
+///
+/// # Example
+///
+/// ```no_run
+/// use clemitui::{Fullscreen, FullscreenView, PromptAction, log_event, set_output_sink};
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// let mut screen = Fullscreen::enter(FullscreenView::new("> "))?;
+/// set_output_sink(Arc::new(screen.view().sink()));
+/// log_event("events logged here appear in the transcript pane");
+///
+/// loop {
+///     match screen.read_event(Duration::from_millis(100))? {
+///         Some(PromptAction::Submit(prompt)) => log_event(&format!("> {prompt}")),
+///         Some(PromptAction::Eof) => break,
+///         _ => {}
+///     }
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct Fullscreen<W: Write = Stdout> {
+    writer: W,
+    view: FullscreenView,
+    owns_terminal: bool,
+    active: bool,
+    drawn_generation: u64,
+}
+
+impl Fullscreen<Stdout> {
+    /// Enter full-screen mode on stdout, sized to the current terminal.
+    ///
+    /// Switches to the alternate screen, enables raw mode and mouse capture,
+    /// and draws `view`.
+    pub fn enter(view: FullscreenView) -> io::Result<Self> {
+        let (cols, rows) = terminal::size()?;
+        let mut screen = Self::with_writer(io::stdout(), view.with_size(cols, rows));
+        screen.owns_terminal = true;
+        install_panic_hook();
+        terminal::enable_raw_mode()?;
+        screen.setup()?;
+        Ok(screen)
+    }
+
+    /// Wait up to `timeout` for a terminal event and handle it.
+    ///
+    /// Returns the prompt action for the event, or `None` on timeout. The
+    /// screen is redrawn after every event and whenever the transcript has
+    /// changed, so call this in a loop to keep the display current.
+    pub fn read_event(&mut self, timeout: Duration) -> io::Result<Option<PromptAction>> {
+        if event::poll(timeout)? {
+            let event = event::read()?;
+            return self.handle_event(&event).map(Some);
+        }
+        if self.view.transcript().generation != self.drawn_generation {
+            self.draw()?;
+        }
+        Ok(None)
+    }
+}
+
+impl<W: Write> Fullscreen<W> {
+    /// Create a full-screen session writing to `writer`.
+    ///
+    /// Nothing is written until [`Self::setup`] is called. Sessions created
+    /// this way leave raw mode alone and are not restored by the panic hook.
+    pub fn with_writer(writer: W, view: FullscreenView) -> Self {
+        Self {
+            writer,
+            view,
+            owns_terminal: false,
+            active: false,
+            drawn_generation: 0,
+        }
+    }
+
+    /// Switch to the alternate screen and draw the view.
+    pub fn setup(&mut self) -> io::Result<()> {
+        self.writer.write_all(ENTER_SEQUENCE.as_bytes())?;
+        self.active = true;
+        if self.owns_terminal {
+            ACTIVE.store(true, Ordering::SeqCst);
+        }
+        self.draw()
+    }
+
+    /// The view being drawn.
+    pub fn view(&self) -> &FullscreenView {
+        &self.view
+    }
+
+    /// Mutable access to the view (call [`Self::draw`] afterwards).
+    pub fn view_mut(&mut self) -> &mut FullscreenView {
+        &mut self.view
+    }
+
+    /// Redraw the whole screen.
+    pub fn draw(&mut self) -> io::Result<()> {
+        if !self.active {
+            return Ok(());
+        }
+        self.drawn_generation = self.view.transcript().generation;
+        self.writer.write_all(self.view.render().as_bytes())?;
+        self.writer.flush()
+    }
+
+    /// Feed `event` to the view and redraw.
+    pub fn handle_event(&mut self, event: &Event) -> io::Result<PromptAction> {
+        let action = self.view.handle_event(event);
+        self.draw()?;
+        Ok(action)
+    }
+
+    /// Return to the main screen and restore the terminal modes.
+    pub fn leave(&mut self) -> io::Result<()> {
+        if !self.active {
+            return Ok(());
+        }
+        self.active = false;
+        self.writer.write_all(LEAVE_SEQUENCE.as_bytes())?;
+        self.writer.flush()?;
+        if self.owns_terminal {
+            ACTIVE.store(false, Ordering::SeqCst);
+            terminal::disable_raw_mode()?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Drop for Fullscreen<W> {
+    fn drop(&mut self) {
+        let _ = self.leave();
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plan::{Plan, PlanEntry, PlanPriority, PlanStatus, format_plan};
+    use crossterm::event::{KeyEvent, MouseEvent};
+
+    fn key(code: KeyCode) -> Event {
+        Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn wheel(kind: MouseEventKind) -> Event {
+        Event::Mouse(MouseEvent {
+            kind,
+            column: 0,
+            row: 0,
+            modifiers: KeyModifiers::NONE,
+        })
+    }
+
+    /// Plain text of each screen row in a rendered frame.
+    fn screen(frame: &str, rows: usize) -> Vec<String> {
+        let goto = regex::Regex::new(r"\x1b\[(\d+);(\d+)H").unwrap();
+        let mut screen = vec![String::new(); rows];
+        let positions: Vec<_> = goto.captures_iter(frame).collect();
+        for (i, caps) in positions.iter().enumerate() {
+            let row: usize = caps[1].parse().unwrap();
+            let text_start = caps.get(0).unwrap().end();
+            let text_end = positions
+                .get(i + 1)
+                .map_or(frame.len(), |next| next.get(0).unwrap().start());
+            screen[row - 1].push_str(&strip_ansi(&frame[text_start..text_end]));
+        }
+        screen
+    }
+
+    fn view_with_lines(count: usize) -> FullscreenView {
+        let view = FullscreenView::new("> ")
+            .with_size(40, 8)
+            .with_sidebar(false);
+        let sink = view.sink();
+        for i in 0..count {
+            sink.emit_line(&format!("line {}", i));
+        }
+        view
+    }
+
+    #[test]
+    fn test_wrap_ansi_plain() {
+        assert_eq!(wrap_ansi("abcdef", 4), vec!["abcd", "ef"]);
+        assert_eq!(wrap_ansi("", 4), vec![""]);
+        assert_eq!(wrap_ansi("日本語", 4), vec!["日本", "語"]);
+    }
+
+    #[test]
+    fn test_wrap_ansi_reopens_colors() {
+        let rows = wrap_ansi("\x1b[31mabcdef\x1b[0m", 4);
+        assert_eq!(rows, vec!["\x1b[31mabcd\x1b[0m", "\x1b[31mef\x1b[0m"]);
+    }
+
+    #[test]
+    fn test_tool_label() {
+        assert_eq!(tool_label("bash {\"cmd\": \"ls\"}"), "bash");
+        assert_eq!(tool_label("▶ run_tests 0.25s"), "▶ run_tests");
+    }
+
+    #[test]
+    fn test_sink_tracks_tools() {
+        let view = FullscreenView::new("> ");
+        let sink = view.sink();
+        sink.emit_line("┌─ read_file src/main.rs\n│ body");
+        sink.emit_line("┌─ bash ls");
+        sink.emit("└─ bash 0.10s ~5 tok\n  └─ error: ignored detail");
+        sink.emit("└─ read_file 0.02s ~90 tok ERROR");
+
+        let tools = view.tools();
+        assert_eq!(
+            tools,
+            vec![
+                ToolEntry {
+                    name: "read_file".to_string(),
+                    status: ToolStatus::Failed,
+                },
+                ToolEntry {
+                    name: "bash".to_string(),
+                    status: ToolStatus::Done,
+                },
+            ]
+        );
+    }
+
+    /// A view with prose around one finished tool call with body and output.
+    fn view_with_tool() -> FullscreenView {
+        let view = FullscreenView::new("> ")
+            .with_size(50, 10)
+            .with_sidebar(false);
+        let sink = view.sink();
+        sink.emit("Checking the build.");
+        sink.emit_line("┌─ bash cargo build\n│ set -e");
+        sink.emit_line("└─ bash 1.20s ~40 tok ERROR");
+        sink.emit("  └─ error: exit 101\n   error[E0308]: mismatched types");
+        sink.emit("Found a type error.");
+        view
+    }
+
+    fn alt(code: KeyCode) -> Event {
+        Event::Key(KeyEvent::new(code, KeyModifiers::ALT))
+    }
+
+    fn ctrl_o() -> Event {
+        Event::Key(KeyEvent::new(KeyCode::Char('o'), KeyModifiers::CONTROL))
+    }
+
+    #[test]
+    fn test_tool_lines_grouped_into_block() {
+        let view = view_with_tool();
+        let transcript = view.transcript();
+        let kinds: Vec<_> = transcript
+            .entries
+            .iter()
+            .map(|entry| match entry {
+                Entry::Line(line) => line.clone(),
+                Entry::Tool(block) => format!("<{} lines>", block.lines.len()),
+            })
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                "Checking the build.",
+                "",
+                "<5 lines>",
+                "",
+                "Found a type error.",
+                ""
+            ]
+        );
+        assert_eq!(transcript.line_count, 10);
+    }
+
+    #[test]
+    fn test_prose_after_tool_is_not_attached() {
+        let view = FullscreenView::new("> ");
+        let sink = view.sink();
+        sink.emit_line("┌─ bash ls");
+        sink.emit_line("└─ bash 0.01s ~1 tok");
+        sink.emit_line("Done.");
+        sink.emit_line("    indented prose");
+        assert_eq!(view.transcript().entries.len(), 3);
+    }
+
+    #[test]
+    fn test_output_attaches_to_running_tool() {
+        let view = FullscreenView::new("> ");
+        let sink = view.sink();
+        sink.emit_line("┌─ bash make");
+        sink.emit_line("Meanwhile, some prose.");
+        sink.emit_line("│ building...");
+        sink.emit_line("   [1/3] compiling");
+        let transcript = view.transcript();
+        assert!(matches!(&transcript.entries[0], Entry::Tool(block) if block.lines.len() == 3));
+    }
+
+    #[test]
+    fn test_plan_during_running_tool_is_not_attached() {
+        let view = FullscreenView::new("> ");
+        let sink = view.sink();
+        sink.emit_line("┌─ bash make");
+        sink.emit_line("Updated the plan:");
+        sink.emit_line(&format_plan(&Plan::new(vec![
+            PlanEntry::new("Build", PlanPriority::Medium, PlanStatus::InProgress),
+            PlanEntry::new("Test", PlanPriority::Medium, PlanStatus::Pending),
+        ])));
+        sink.emit_line("  - nested bullet");
+        sink.emit_line("    fn main() {}");
+        sink.emit_line("│ building...");
+        let transcript = view.transcript();
+        assert!(matches!(&transcript.entries[0], Entry::Tool(block) if block.lines.len() == 2));
+        assert_eq!(transcript.entries.len(), 7);
+    }
+
+    #[test]
+    fn test_blocks_collapsed_by_default() {
+        colored::control::set_override(false);
+        let view = view_with_tool();
+        let rows = screen(&view.render(), 10);
+        colored::control::unset_override();
+
+        assert_eq!(rows[2], "└─ bash 1.20s ~40 tok ERROR [+4]");
+        assert!(!rows.iter().any(|row| row.contains("┌─")));
+        assert_eq!(view.lines().len(), 10, "lines() includes folded lines");
+    }
+
+    #[test]
+    fn test_toggle_latest_block() {
+        colored::control::set_override(false);
+        let mut view = view_with_tool();
+        view.handle_event(&ctrl_o());
+        assert!(view.is_expanded(0));
+        let rows = screen(&view.render(), 10);
+        assert_eq!(rows[0], "┌─ bash cargo build [−]");
+        assert_eq!(rows[3], "  └─ error: exit 101");
+
+        view.handle_event(&ctrl_o());
+        assert!(!view.is_expanded(0));
+        colored::control::unset_override();
+    }
+
+    #[test]
+    fn test_fold_state_persists_across_redraws() {
+        let mut view = view_with_tool();
+        view.toggle_tool();
+        view.sink().emit_line("┌─ grep TODO");
+        view.resize(30, 12);
+        let _ = view.render();
+        assert!(view.is_expanded(0));
+        assert!(!view.is_expanded(1));
+    }
+
+    #[test]
+    fn test_select_and_toggle_with_keys() {
+        colored::control::set_override(false);
+        let mut view = view_with_tool();
+        view.sink().emit_line("┌─ grep TODO");
+        view.sink().emit_line("└─ grep 0.01s ~2 tok");
+
+        view.handle_event(&alt(KeyCode::Up));
+        assert_eq!(view.selected_tool(), Some(1));
+        assert!(view.render().contains("└─ grep 0.01s ~2 tok [+1] ◀"));
+        view.handle_event(&alt(KeyCode::Up));
+        view.handle_event(&alt(KeyCode::Up));
+        assert_eq!(view.selected_tool(), Some(0), "stays on the oldest block");
+
+        view.handle_event(&ctrl_o());
+        assert!(view.is_expanded(0));
+        assert!(!view.is_expanded(1));
+
+        view.handle_event(&alt(KeyCode::Down));
+        view.handle_event(&alt(KeyCode::Down));
+        assert_eq!(view.selected_tool(), None);
+        colored::control::unset_override();
+    }
+
+    #[test]
+    fn test_selection_scrolls_block_into_view() {
+        let mut view = view_with_tool();
+        let sink = view.sink();
+        for i in 0..30 {
+            sink.emit_line(&format!("line {}", i));
+        }
+        view.select_previous_tool();
+        let rows = screen(&view.render(), 10);
+        assert!(rows.iter().any(|row| row.contains("└─ bash")));
+        assert!(view.scroll_offset() > 0);
+    }
+
+    #[test]
+    fn test_click_toggles_block() {
+        let mut view = view_with_tool();
+        let click = |row| {
+            Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Down(MouseButton::Left),
+                column: 5,
+                row,
+                modifiers: KeyModifiers::NONE,
+            })
+        };
+        view.handle_event(&click(0));
+        assert_eq!(view.selected_tool(), None, "prose rows are not selectable");
+        view.handle_event(&click(2));
+        assert_eq!(view.selected_tool(), Some(0));
+        assert!(view.is_expanded(0));
+    }
+
+    #[test]
+    fn test_submit_clears_selection() {
+        let mut view = view_with_tool();
+        view.select_previous_tool();
+        view.handle_event(&key(KeyCode::Enter));
+        assert_eq!(view.selected_tool(), None);
+    }
+
+    #[test]
+    fn test_set_all_expanded() {
+        let mut view = view_with_tool();
+        view.sink().emit_line("┌─ grep TODO");
+        view.set_all_expanded(true);
+        assert!(view.is_expanded(0) && view.is_expanded(1));
+        view.set_all_expanded(false);
+        assert!(!view.is_expanded(0) && !view.is_expanded(1));
+    }
+
+    #[test]
+    fn test_cap_drops_selected_block() {
+        let mut view = view_with_tool();
+        view.select_previous_tool();
+        let text = vec!["x"; MAX_TRANSCRIPT_LINES].join("\n");
+        view.sink().emit_line(&text);
+        assert_eq!(view.selected_tool(), None);
+        assert!(view.tools().is_empty());
+    }
+
+    fn ctrl(c: char) -> Event {
+        Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL))
+    }
+
+    fn type_text(view: &mut FullscreenView, text: &str) {
+        for c in text.chars() {
+            view.handle_event(&key(KeyCode::Char(c)));
+        }
+    }
+
+    #[test]
+    fn test_search_expands_folded_match() {
+        colored::control::set_override(false);
+        let mut view = view_with_tool();
+        view.handle_event(&ctrl('f'));
+        type_text(&mut view, "mismatch");
+
+        let search = view.search().expect("pattern compiles");
+        assert_eq!(search.current().map(|found| found.line), Some(6));
+        assert!(view.is_expanded(0), "block holding the match is expanded");
+        let rows = screen(&view.render(), 10);
+        assert_eq!(rows[9], "/mismatch  1/1");
+        assert!(
+            rows.iter()
+                .any(|row| row.contains("error[E0308]: mismatched types"))
+        );
+        colored::control::unset_override();
+    }
+
+    #[test]
+    fn test_search_summary_match_keeps_block_folded() {
+        let mut view = view_with_tool();
+        view.start_search("1.20s");
+        assert!(!view.is_expanded(0));
+    }
+
+    #[test]
+    fn test_search_navigation() {
+        let mut view = view_with_lines(20);
+        view.start_search("line 1");
+        // Starts from the bottom of the screen: line 19 is the last match
+        let current = |view: &FullscreenView| view.search().unwrap().current().unwrap().line;
+        assert_eq!(current(&view), 19);
+
+        view.handle_event(&key(KeyCode::Char('N')));
+        assert_eq!(current(&view), 18);
+        view.handle_event(&key(KeyCode::Char('n')));
+        view.handle_event(&key(KeyCode::Char('n')));
+        assert_eq!(current(&view), 1, "wraps to the first match");
+        assert_eq!(view.scroll_offset(), 13, "line 1 scrolled to the top row");
+    }
+
+    #[test]
+    fn test_search_invalid_pattern() {
+        colored::control::set_override(false);
+        let mut view = view_with_lines(3);
+        view.handle_event(&ctrl('f'));
+        type_text(&mut view, "line (");
+        assert!(view.search().is_none());
+        assert!(view.render().contains("invalid search pattern"));
+
+        type_text(&mut view, "1)");
+        assert!(view.search().is_some());
+        colored::control::unset_override();
+    }
+
+    #[test]
+    fn test_search_no_matches() {
+        colored::control::set_override(false);
+        let mut view = view_with_lines(3);
+        view.start_search("zzz");
+        assert!(view.render().contains("/zzz  no matches"));
+        colored::control::unset_override();
+    }
+
+    #[test]
+    fn test_search_bar_closing() {
+        let mut view = view_with_lines(3);
+        view.handle_event(&ctrl('f'));
+        view.handle_event(&key(KeyCode::Backspace));
+        assert!(
+            view.search_bar.is_none(),
+            "backspace on an empty pattern closes"
+        );
+
+        view.start_search("line");
+        view.handle_event(&key(KeyCode::Esc));
+        assert!(view.search().is_none());
+
+        // Typing after a search goes back to the prompt
+        view.start_search("line");
+        view.handle_event(&key(KeyCode::Char('x')));
+        assert!(view.search().is_none());
+        assert_eq!(view.editor().buffer(), "x");
+    }
+
+    #[test]
+    fn test_search_keeps_prompt_contents() {
+        let mut view = view_with_lines(3);
+        type_text(&mut view, "draft");
+        view.handle_event(&ctrl('f'));
+        type_text(&mut view, "line");
+        view.handle_event(&key(KeyCode::Enter));
+        view.handle_event(&key(KeyCode::Char('n')));
+        assert_eq!(view.editor().buffer(), "draft");
+    }
+
+    #[test]
+    fn test_viewer_keys() {
+        colored::control::set_override(false);
+        let mut view = FullscreenView::viewer().with_size(40, 8);
+        let sink = view.sink();
+        for i in 0..20 {
+            sink.emit_line(&format!("line {}", i));
+        }
+
+        let frame = view.render();
+        assert!(!frame.ends_with("\x1b[?25h"), "cursor stays hidden");
+        assert!(screen(&frame, 8)[7].starts_with("q quit"));
+
+        view.handle_event(&key(KeyCode::Char('k')));
+        view.handle_event(&key(KeyCode::Char('k')));
+        view.handle_event(&key(KeyCode::Char('j')));
+        assert_eq!(view.scroll_offset(), 1);
+        view.handle_event(&key(KeyCode::Char('g')));
+        assert_eq!(view.scroll_offset(), 14);
+        view.handle_event(&key(KeyCode::Char('G')));
+        assert_eq!(view.scroll_offset(), 0);
+
+        view.handle_event(&key(KeyCode::Char('/')));
+        type_text(&mut view, "line 3");
+        view.handle_event(&key(KeyCode::Enter));
+        assert_eq!(view.search().unwrap().current().unwrap().line, 3);
+        assert_eq!(
+            view.handle_event(&key(KeyCode::Esc)),
+            PromptAction::Continue
+        );
+        assert_eq!(
+            view.handle_event(&key(KeyCode::Char('q'))),
+            PromptAction::Eof
+        );
+        assert_eq!(view.handle_event(&ctrl('c')), PromptAction::Cancel);
+        colored::control::unset_override();
+    }
+
+    #[test]
+    fn test_emit_adds_blank_line() {
+        let view = FullscreenView::new("> ");
+        view.sink().emit("block");
+        view.sink().emit_line("line");
+        assert_eq!(view.lines(), vec!["block", "", "line"]);
+    }
+
+    #[test]
+    fn test_transcript_is_capped() {
+        let view = FullscreenView::new("> ");
+        let text = vec!["x"; MAX_TRANSCRIPT_LINES + 5].join("\n");
+        view.sink().emit_line(&text);
+        assert_eq!(view.lines().len(), MAX_TRANSCRIPT_LINES);
+    }
+
+    #[test]
+    fn test_wrapped_rows_are_reused() {
+        let mut view = view_with_tool();
+        let first = view.transcript_rows();
+        view.scroll_up(1);
+        view.render();
+        assert!(Arc::ptr_eq(&first, &view.transcript_rows()));
+
+        view.toggle_tool();
+        let toggled = view.transcript_rows();
+        assert!(!Arc::ptr_eq(&first, &toggled));
+        view.sink().emit_line("more");
+        assert!(!Arc::ptr_eq(&toggled, &view.transcript_rows()));
+        view.resize(30, 10);
+        assert_eq!(view.transcript_rows().key.width, 30);
+    }
+
+    #[test]
+    fn test_viewer_keeps_whole_transcript() {
+        colored::control::set_override(false);
+        let view = FullscreenView::viewer()
+            .with_size(50, 10)
+            .with_sidebar(false);
+        let text = vec!["x"; MAX_TRANSCRIPT_LINES + 5].join("\n");
+        view.sink().emit_line("first");
+        view.sink().emit_line(&text);
+        view.sink().emit_line("┌─ bash make\n│ building...");
+        let lines = view.lines();
+        assert_eq!(lines.len(), MAX_TRANSCRIPT_LINES + 8);
+        assert_eq!(lines[0], "first");
+        assert!(view.is_expanded(0));
+        let rows = screen(&view.render(), 10);
+        assert!(rows.iter().any(|row| row == "│ building..."));
+        colored::control::unset_override();
+    }
+
+    #[test]
+    fn test_render_follows_bottom() {
+        colored::control::set_override(false);
+        let view = view_with_lines(20);
+        let rows = screen(&view.render(), 8);
+        colored::control::unset_override();
+
+        // 8 rows: 6 transcript, 1 rule, 1 prompt
+        assert_eq!(rows[0], "line 14");
+        assert_eq!(rows[5], "line 19");
+        assert!(rows[6].starts_with("────"));
+        assert_eq!(rows[7], "> ");
+    }
+
+    #[test]
+    fn test_scroll_with_wheel_and_page_keys() {
+        colored::control::set_override(false);
+        let mut view = view_with_lines(20);
+
+        view.handle_event(&wheel(MouseEventKind::ScrollUp));
+        assert_eq!(view.scroll_offset(), 3);
+        let rows = screen(&view.render(), 8);
+        assert_eq!(rows[5], "line 16");
+        assert!(rows[6].starts_with("── ↓ 3 more"));
+
+        view.handle_event(&key(KeyCode::PageUp));
+        assert_eq!(view.scroll_offset(), 8);
+        view.handle_event(&key(KeyCode::PageUp));
+        view.handle_event(&key(KeyCode::PageUp));
+        assert_eq!(view.scroll_offset(), 14, "clamped to the top");
+        assert_eq!(screen(&view.render(), 8)[0], "line 0");
+
+        view.handle_event(&key(KeyCode::PageDown));
+        view.handle_event(&wheel(MouseEventKind::ScrollDown));
+        assert_eq!(view.scroll_offset(), 6);
+        colored::control::unset_override();
+    }
+
+    #[test]
+    fn test_submit_scrolls_to_bottom() {
+        let mut view = view_with_lines(20);
+        view.scroll_up(5);
+        view.handle_event(&Event::Paste("hi".to_string()));
+        let action = view.handle_event(&key(KeyCode::Enter));
+        assert_eq!(action, PromptAction::Submit("hi".to_string()));
+        assert_eq!(view.scroll_offset(), 0);
+    }
+
+    #[test]
+    fn test_short_transcript_does_not_scroll() {
+        let mut view = view_with_lines(2);
+        view.scroll_up(10);
+        assert_eq!(view.scroll_offset(), 0);
+    }
+
+    #[test]
+    fn test_sidebar_toggle_and_render() {
+        colored::control::set_override(false);
+        let mut view = FullscreenView::new("> ").with_size(60, 6);
+        view.sink().emit_line("┌─ bash ls");
+        view.sink().emit_line("┌─ read_file a.rs");
+        view.sink().emit_line("└─ read_file 0.01s ~3 tok");
+
+        let frame = view.render();
+        assert!(frame.contains("Tools 1/2"));
+        assert!(frame.contains("● bash"));
+        assert!(frame.contains("✓ read_file"));
+        // Sidebar border sits right of the 39-column transcript pane
+        assert!(frame.contains("\x1b[1;40H│"));
+
+        view.handle_event(&key(KeyCode::F(2)));
+        assert!(!view.sidebar_visible());
+        assert!(!view.render().contains("Tools"));
+        colored::control::unset_override();
+    }
+
+    #[test]
+    fn test_sidebar_hidden_on_narrow_terminal() {
+        let view = FullscreenView::new("> ").with_size(30, 6);
+        assert_eq!(view.sidebar_width(), 0);
+        assert_eq!(view.main_width(), 30);
+    }
+
+    #[test]
+    fn test_prompt_cursor_position() {
+        let mut view = FullscreenView::new("> ").with_size(40, 8);
+        view.handle_event(&Event::Paste("abc".to_string()));
+        assert!(view.render().ends_with("\x1b[8;6H\x1b[?25h"));
+    }
+
+    #[test]
+    fn test_tall_prompt_is_limited_to_half_the_screen() {
+        let mut view = FullscreenView::new("> ").with_size(40, 8);
+        view.handle_event(&Event::Paste("1\n2\n3\n4\n5\n6".to_string()));
+        let (rows, cursor) = view.prompt_rows();
+        assert_eq!(rows.len(), 4);
+        assert_eq!(cursor, (3, 3));
+        assert_eq!(view.pane_height(), 3);
+    }
+
+    #[test]
+    fn test_setup_draw_and_leave() {
+        let mut output = Vec::new();
+        {
+            let mut screen = Fullscreen::with_writer(&mut output, FullscreenView::new("> "));
+            screen.setup().unwrap();
+            screen.view().sink().emit_line("hello");
+            screen.handle_event(&key(KeyCode::Char('x'))).unwrap();
+        }
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with(ENTER_SEQUENCE));
+        assert!(output.contains("hello"));
+        assert!(output.ends_with(LEAVE_SEQUENCE));
+    }
+
+    #[test]
+    fn test_leave_without_setup_writes_nothing() {
+        let mut output = Vec::new();
+        Fullscreen::with_writer(&mut output, FullscreenView::new("> "))
+            .leave()
+            .unwrap();
+        assert!(output.is_empty());
+    }
+}
@@ -0,0 +1,415 @@
+//! Terminal hyperlinks (OSC 8) and tool call locations.
+//!
+//! Many terminals render `ESC ] 8 ;; URI ESC \ text ESC ] 8 ;; ESC \` as a
+//! clickable link. clemitui uses this for the file locations reported in ACP
+//! `tool_call` updates, linking either to a `file://` URI or to an editor
+//! scheme such as `vscode://file/...` so a click opens the file at the line.
+//!
+//! # Usage
+//!
+//! ```
+//! use clemitui::{EditorScheme, LinkConfig, set_link_config};
+//! use std::sync::Arc;
+//!
+//! // Open links in VS Code; hyperlink support is still auto-detected
+//! set_link_config(Arc::new(LinkConfig::default().with_scheme(EditorScheme::VsCode)));
+//! ```
+
+use std::env;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, OnceLock, RwLock};
+
+use serde::Deserialize;
+
+// ============================================================================
+// OSC 8
+// ============================================================================
+
+/// Wrap `text` in an OSC 8 hyperlink to `uri`.
+///
+/// # Example
+///
+/// ```
+/// use clemitui::hyperlink::osc8;
+///
+/// assert_eq!(
+///     osc8("https://example.com", "site"),
+///     "\x1b]8;;https://example.com\x1b\\site\x1b]8;;\x1b\\"
+/// );
+/// ```
+pub fn osc8(uri: &str, text: &str) -> String {
+    format!("\x1b]8;;{uri}\x1b\\{text}\x1b]8;;\x1b\\")
+}
+
+/// Whether the environment described by `var` supports OSC 8 hyperlinks.
+///
+/// `FORCE_HYPERLINK=1`/`0` overrides detection. Otherwise known terminals are
+/// recognised from `TERM_PROGRAM`, `TERM`, `VTE_VERSION`, `WT_SESSION` and
+/// `KONSOLE_VERSION`. Multiplexers and unknown terminals are assumed not to
+/// support links.
+pub fn supports_hyperlinks_with(var: impl Fn(&str) -> Option<String>) -> bool {
+    if let Some(force) = var("FORCE_HYPERLINK") {
+        return force != "0";
+    }
+    if let Some(program) = var("TERM_PROGRAM")
+        && matches!(
+            program.as_str(),
+            "iTerm.app" | "WezTerm" | "vscode" | "Hyper" | "ghostty" | "rio"
+        )
+    {
+        return true;
+    }
+    if let Some(term) = var("TERM")
+        && ["kitty", "alacritty", "foot", "wezterm", "ghostty"]
+            .iter()
+            .any(|name| term.contains(name))
+    {
+        return true;
+    }
+    if var("VTE_VERSION")
+        .and_then(|v| v.parse::<u32>().ok())
+        .is_some_and(|v| v >= 5000)
+    {
+        return true;
+    }
+    var("WT_SESSION").is_some() || var("KONSOLE_VERSION").is_some()
+}
+
+/// Whether stdout is a terminal that supports OSC 8 hyperlinks.
+///
+/// Detected once from the process environment.
+pub fn supports_hyperlinks() -> bool {
+    static SUPPORTED: OnceLock<bool> = OnceLock::new();
+    *SUPPORTED.get_or_init(|| {
+        std::io::stdout().is_terminal() && supports_hyperlinks_with(|name| env::var(name).ok())
+    })
+}
+
+// ============================================================================
+// URIs
+// ============================================================================
+
+/// Percent-encode a path for use in a URI, keeping `/` separators.
+fn encode_path(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-._~".contains(&byte) {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    out
+}
+
+/// Resolve `path` against the current directory if it is relative.
+fn absolute(path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        env::current_dir()
+            .map(|cwd| cwd.join(path))
+            .unwrap_or_else(|_| path.to_path_buf())
+    }
+}
+
+/// Where a location link points.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum EditorScheme {
+    /// `file:///abs/path` (default). Line numbers are not part of the URI.
+    #[default]
+    File,
+    /// `vscode://file/abs/path:line`
+    VsCode,
+    /// `cursor://file/abs/path:line`
+    Cursor,
+    /// `zed://file/abs/path:line`
+    Zed,
+    /// `idea://open?file=/abs/path&line=line` (JetBrains IDEs)
+    Idea,
+    /// A template with `{path}` (absolute, percent-encoded) and `{line}`
+    /// placeholders, e.g. `subl://open?url=file://{path}&line={line}`.
+    Custom(String),
+}
+
+impl EditorScheme {
+    /// Parse a scheme name (`file`, `vscode`, `cursor`, `zed`, `idea`); any
+    /// other value containing `{path}` is used as a custom template.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "file" => Some(Self::File),
+            "vscode" => Some(Self::VsCode),
+            "cursor" => Some(Self::Cursor),
+            "zed" => Some(Self::Zed),
+            "idea" => Some(Self::Idea),
+            template if template.contains("{path}") => Some(Self::Custom(template.to_string())),
+            _ => None,
+        }
+    }
+
+    fn template(&self) -> &str {
+        match self {
+            Self::File => "file://{path}",
+            Self::VsCode => "vscode://file{path}:{line}",
+            Self::Cursor => "cursor://file{path}:{line}",
+            Self::Zed => "zed://file{path}:{line}",
+            Self::Idea => "idea://open?file={path}&line={line}",
+            Self::Custom(template) => template,
+        }
+    }
+
+    /// URI opening `path` at `line` (line 1 when unknown).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clemitui::EditorScheme;
+    ///
+    /// assert_eq!(
+    ///     EditorScheme::VsCode.uri("/src/my file.rs", Some(42)),
+    ///     "vscode://file/src/my%20file.rs:42"
+    /// );
+    /// assert_eq!(EditorScheme::File.uri("/src/main.rs", Some(42)), "file:///src/main.rs");
+    /// ```
+    pub fn uri(&self, path: impl AsRef<Path>, line: Option<u32>) -> String {
+        let path = absolute(path.as_ref());
+        let encoded = encode_path(&path.to_string_lossy());
+        self.template()
+            .replace("{path}", &encoded)
+            .replace("{line}", &line.unwrap_or(1).to_string())
+    }
+}
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// How locations are linked.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct LinkConfig {
+    /// Force hyperlinks on or off; `None` auto-detects terminal support.
+    pub enabled: Option<bool>,
+    /// Link target scheme.
+    pub scheme: EditorScheme,
+}
+
+impl LinkConfig {
+    /// Set the link target scheme.
+    pub fn with_scheme(mut self, scheme: EditorScheme) -> Self {
+        self.scheme = scheme;
+        self
+    }
+
+    /// Force hyperlinks on or off instead of auto-detecting.
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = Some(enabled);
+        self
+    }
+
+    /// Whether hyperlinks should be emitted.
+    pub fn links_enabled(&self) -> bool {
+        self.enabled.unwrap_or_else(supports_hyperlinks)
+    }
+
+    /// `text` linked to `path`/`line`, or plain `text` when links are off.
+    pub fn link(&self, path: impl AsRef<Path>, line: Option<u32>, text: &str) -> String {
+        if self.links_enabled() {
+            osc8(&self.scheme.uri(path, line), text)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+static DEFAULT_LINK_CONFIG: LazyLock<Arc<LinkConfig>> =
+    LazyLock::new(|| Arc::new(LinkConfig::default()));
+
+static LINK_CONFIG: RwLock<Option<Arc<LinkConfig>>> = RwLock::new(None);
+
+/// Set the global link configuration used by formatters.
+pub fn set_link_config(config: Arc<LinkConfig>) {
+    if let Ok(mut guard) = LINK_CONFIG.write() {
+        *guard = Some(config);
+    }
+}
+
+/// Reset the global link configuration to auto-detected `file://` links.
+pub fn reset_link_config() {
+    if let Ok(mut guard) = LINK_CONFIG.write() {
+        *guard = None;
+    }
+}
+
+/// Get the current global link configuration.
+pub fn link_config() -> Arc<LinkConfig> {
+    LINK_CONFIG
+        .read()
+        .ok()
+        .and_then(|guard| guard.clone())
+        .unwrap_or_else(|| Arc::clone(&DEFAULT_LINK_CONFIG))
+}
+
+// ============================================================================
+// Tool Locations
+// ============================================================================
+
+/// A file location affected by a tool call, as in ACP `tool_call.locations`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ToolLocation {
+    /// File path (absolute in ACP).
+    pub path: String,
+    /// 1-based line number, if known.
+    #[serde(default)]
+    pub line: Option<u32>,
+}
+
+impl ToolLocation {
+    /// Create a location.
+    pub fn new(path: impl Into<String>, line: Option<u32>) -> Self {
+        Self {
+            path: path.into(),
+            line,
+        }
+    }
+
+    /// Parse the `locations` array of an ACP `tool_call` update, skipping
+    /// malformed entries.
+    pub fn from_acp(locations: &serde_json::Value) -> Vec<Self> {
+        locations
+            .as_array()
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|item| Self::deserialize(item).ok())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn env_of(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn test_detects_supporting_terminals() {
+        assert!(supports_hyperlinks_with(env_of(&[(
+            "TERM_PROGRAM",
+            "iTerm.app"
+        )])));
+        assert!(supports_hyperlinks_with(env_of(&[("TERM", "xterm-kitty")])));
+        assert!(supports_hyperlinks_with(env_of(&[("VTE_VERSION", "7200")])));
+        assert!(supports_hyperlinks_with(env_of(&[("WT_SESSION", "1")])));
+        assert!(!supports_hyperlinks_with(env_of(&[(
+            "VTE_VERSION",
+            "4000"
+        )])));
+        assert!(!supports_hyperlinks_with(env_of(&[(
+            "TERM",
+            "xterm-256color"
+        )])));
+        assert!(!supports_hyperlinks_with(env_of(&[])));
+    }
+
+    #[test]
+    fn test_force_hyperlink_overrides_detection() {
+        assert!(supports_hyperlinks_with(env_of(&[(
+            "FORCE_HYPERLINK",
+            "1"
+        )])));
+        assert!(!supports_hyperlinks_with(env_of(&[
+            ("FORCE_HYPERLINK", "0"),
+            ("TERM_PROGRAM", "WezTerm"),
+        ])));
+    }
+
+    #[test]
+    fn test_scheme_uris() {
+        let path = "/home/dev/src/main.rs";
+        assert_eq!(
+            EditorScheme::File.uri(path, Some(3)),
+            "file:///home/dev/src/main.rs"
+        );
+        assert_eq!(
+            EditorScheme::Cursor.uri(path, None),
+            "cursor://file/home/dev/src/main.rs:1"
+        );
+        assert_eq!(
+            EditorScheme::Idea.uri(path, Some(7)),
+            "idea://open?file=/home/dev/src/main.rs&line=7"
+        );
+        let custom = EditorScheme::Custom("edit://{path}#L{line}".to_string());
+        assert_eq!(custom.uri(path, Some(9)), "edit:///home/dev/src/main.rs#L9");
+    }
+
+    #[test]
+    fn test_relative_paths_resolve_against_cwd() {
+        let uri = EditorScheme::File.uri("src/lib.rs", None);
+        let cwd = env::current_dir().unwrap();
+        assert_eq!(
+            uri,
+            format!(
+                "file://{}",
+                encode_path(&cwd.join("src/lib.rs").to_string_lossy())
+            )
+        );
+    }
+
+    #[test]
+    fn test_encode_path() {
+        assert_eq!(encode_path("/a b/ü#1.rs"), "/a%20b/%C3%BC%231.rs");
+    }
+
+    #[test]
+    fn test_parse_scheme() {
+        assert_eq!(EditorScheme::parse("vscode"), Some(EditorScheme::VsCode));
+        assert_eq!(
+            EditorScheme::parse("x://{path}"),
+            Some(EditorScheme::Custom("x://{path}".to_string()))
+        );
+        assert_eq!(EditorScheme::parse("emacs"), None);
+    }
+
+    #[test]
+    fn test_link_respects_enabled() {
+        let on = LinkConfig::default().with_enabled(true);
+        assert_eq!(
+            on.link("/a.rs", Some(2), "a.rs:2"),
+            "\x1b]8;;file:///a.rs\x1b\\a.rs:2\x1b]8;;\x1b\\"
+        );
+        let off = LinkConfig::default().with_enabled(false);
+        assert_eq!(off.link("/a.rs", Some(2), "a.rs:2"), "a.rs:2");
+    }
+
+    #[test]
+    fn test_locations_from_acp() {
+        let locations = ToolLocation::from_acp(&json!([
+            {"path": "/src/main.rs", "line": 10},
+            {"path": "/src/lib.rs"},
+            {"line": 3}
+        ]));
+        assert_eq!(
+            locations,
+            vec![
+                ToolLocation::new("/src/main.rs", Some(10)),
+                ToolLocation::new("/src/lib.rs", None)
+            ]
+        );
+        assert!(ToolLocation::from_acp(&json!(null)).is_empty());
+    }
+}
@@ -4,8 +4,24 @@
 //! that speak the Agent Client Protocol (ACP). It handles:
 //!
 //! - Streaming text rendering with markdown support
+//! - Markdown tables fitted to the terminal width
+//! - Images, resources and resource links from agent messages
+//! - Agent thoughts rendered apart from the answer
+//! - Agent plans with status, priority and progress, updated in place
 //! - Tool execution display (start/result formatting)
+//! - Live output of embedded terminals, interpreted like a VT100
+//! - Running agent commands for the ACP `terminal/*` methods
+//! - Reading and writing files for the ACP `fs/*` methods, with a diff preview
 //! - Session logging infrastructure
+//! - A persistent status line pinned to the bottom terminal row
+//! - An optional full-screen mode with a scrollable transcript and tool sidebar
+//! - Bell and desktop notifications when long turns end, permission is needed or errors occur
+//! - Paging of oversized output through `$PAGER` or a built-in pager
+//! - Prompt input with emacs keybindings, multiline editing and history
+//! - Slash commands merged from the client and the agent, with completion
+//! - Cost estimation from token usage
+//! - Regex search over the transcript with highlighted matches
+//! - Redaction of secrets before they reach the terminal or a log sink
 //!
 //! This crate is intentionally minimal, providing only primitive formatting
 //! functions that take simple types (strings, durations, token counts). This
@@ -14,22 +30,113 @@
 //!
 //! # Modules
 //!
+//! - [`commands`] - Slash command registry with tab completion and `/help`
+//! - [`content`] - ACP content blocks: inline images, audio, resources and resource links
+//! - [`cost`] - Pricing tables and per-turn/session cost tracking
+//! - [`diff`] - Line diffs of file contents, rendered with line numbers
 //! - [`mod@format`] - Pure formatting functions for tool output, warnings, etc.
+//! - [`fs`] - Client-side ACP `fs/*` methods with unsaved buffers and write approval
+//! - [`fullscreen`] - Alternate-screen mode with transcript pane, prompt and tool sidebar
+//! - [`hyperlink`] - OSC 8 hyperlinks, editor URI schemes and tool call locations
+//! - [`live_region`] - Output blocks that are re-rendered in place
 //! - [`logging`] - OutputSink trait and global logging infrastructure
-//! - [`text_buffer`] - Streaming text accumulation with markdown rendering
+//! - [`mod@notify`] - Terminal bell, OSC 9/777 and tmux passthrough notifications
+//! - [`pager`] - Paging of long rendered blocks through `$PAGER` or a built-in pager
+//! - [`plan`] - ACP agent plans rendered as a checklist with progress
+//! - [`process`] - Client-side ACP `terminal/*` methods running local processes
+//! - [`prompt`] - Line editor for user prompts with history and reverse search
+//! - [`redact`] - Secret redaction for tool arguments and output
+//! - [`search`] - Smart-case regex search over transcript lines with match highlighting
+//! - [`status_line`] - Bottom-row status bar using a terminal scroll region
+//! - [`mod@theme`] - ACP tool kinds with icons, and the colors used by formatters
+//! - [`table`] - Markdown tables with box borders, column alignment and width fitting
+//! - [`terminal_pane`] - Virtual VT100 screen and live window for embedded ACP terminals
+//! - [`text_buffer`] - Streaming text accumulation with markdown rendering and hyperlinks
+//! - [`thought_buffer`] - Agent thought rendering, collapsible to a one-line summary
+//! - [`tool_formatter`] - Per-tool argument and body formatters, matched by name or glob
+//! - [`width`] - Display-width measurement and grapheme-aware truncation
 
+pub mod commands;
+pub mod content;
+pub mod cost;
+pub mod diff;
 pub mod format;
+pub mod fs;
+pub mod fullscreen;
+pub mod hyperlink;
+pub mod live_region;
 pub mod logging;
+pub mod notify;
+pub mod pager;
+pub mod plan;
+pub mod process;
+pub mod prompt;
+pub mod redact;
+pub mod search;
+pub mod status_line;
+pub mod table;
+pub mod terminal_pane;
 pub mod text_buffer;
+pub mod theme;
+pub mod thought_buffer;
+pub mod tool_formatter;
+pub mod width;
 
 // Re-export commonly used types
+pub use commands::{CommandRegistry, CommandSource, SlashCommand, parse_slash_command};
+pub use content::{
+    ContentBlock, EmbeddedResource, ImageProtocol, ResourceLink, format_content_block,
+    format_content_block_with,
+};
+pub use cost::{CostTracker, ModelPricing, PricingError, PricingTable, TokenUsage};
+pub use diff::{DiffHunk, DiffLine, DiffLineKind, FileDiff, format_diff};
 pub use format::{
-    estimate_tokens, format_cancelled, format_context_warning, format_ctrl_c, format_error_detail,
-    format_error_message, format_retry, format_tool_args, format_tool_executing,
-    format_tool_result,
+    ContextGaugeConfig, ContextHint, ContextLevel, OutputPreviewConfig, estimate_tokens,
+    format_arg_pairs, format_cancelled, format_context_gauge, format_context_gauge_warning,
+    format_context_warning, format_cost, format_ctrl_c, format_error_detail, format_error_message,
+    format_json_block, format_json_preview, format_retry, format_session_summary, format_tool_args,
+    format_tool_executing, format_tool_executing_with_kind, format_tool_locations,
+    format_tool_output, format_tool_result, format_tool_result_with_kind,
+};
+pub use fs::{FsError, FsHandler, ReadTextFileRequest, WriteApprover, WriteTextFileRequest};
+pub use fullscreen::{Fullscreen, FullscreenSink, FullscreenView, ToolEntry, ToolStatus};
+pub use hyperlink::{
+    EditorScheme, LinkConfig, ToolLocation, link_config, reset_link_config, set_link_config,
 };
+pub use live_region::LiveRegion;
 pub use logging::{
     OutputSink, disable_logging, enable_logging, is_logging_enabled, log_event, log_event_line,
     set_output_sink,
 };
+pub use notify::{
+    DEFAULT_NOTIFY_MIN_DURATION, NotifyConfig, NotifyEvent, NotifyMethod, format_notification,
+    format_notification_with, notify, notify_config, reset_notify_config, set_notify_config,
+};
+pub use pager::{
+    DEFAULT_PAGER_COMMAND, DEFAULT_PAGER_THRESHOLD, PagerConfig, PagerMode, pager_config,
+    print_paged, reset_pager_config, set_pager_config,
+};
+pub use plan::{Plan, PlanEntry, PlanPriority, PlanStatus, format_plan};
+pub use process::{
+    CreateTerminalRequest, EnvVariable, TerminalError, TerminalExitStatus, TerminalManager,
+    TerminalOutput, format_terminal_exit,
+};
+pub use prompt::{
+    Completer, Completion, History, LineEditor, PromptAction, PromptEditor, ReadResult,
+};
+pub use redact::{
+    DEFAULT_ENTROPY_THRESHOLD, REDACTED, RedactError, Redactor, redactor, reset_redactor,
+    set_redactor,
+};
+pub use search::{SearchError, SearchMatch, TranscriptSearch, highlight_matches};
+pub use status_line::{StatusBar, StatusInfo, format_status_line};
+pub use table::{ColumnAlign, Table, format_table};
+pub use terminal_pane::{TerminalPane, VirtualScreen};
 pub use text_buffer::TextBuffer;
+pub use theme::{BoxGlyphs, KindStyle, Theme, ToolKind, reset_theme, set_theme, theme};
+pub use thought_buffer::{ThoughtBuffer, ThoughtDisplay, format_thought_summary};
+pub use tool_formatter::{
+    HiddenArgs, ToolFormatter, ToolFormatterRegistry, reset_tool_formatters, set_tool_formatters,
+    tool_formatters,
+};
+pub use width::{display_width, strip_ansi, truncate_end, truncate_middle, truncate_path};
@@ -0,0 +1,134 @@
+//! Re-rendering a block of output in place.
+//!
+//! A [`LiveRegion`] remembers how many terminal rows its last content took, so
+//! an updated version (a plan whose entries changed status, for example) can
+//! replace it instead of being printed again below. Once other output is
+//! printed after the region, [`detach`](LiveRegion::detach) it so the next
+//! update starts fresh underneath.
+//!
+//! # Usage
+//!
+//! ```
+//! use clemitui::LiveRegion;
+//!
+//! let mut region = LiveRegion::new().with_terminal_size(80, 24);
+//! print!("{}", region.update("step 1 of 3\n"));
+//! // Moves up one row, clears, and prints the new content
+//! assert_eq!(region.update("step 2 of 3\n"), "\x1b[1F\x1b[Jstep 2 of 3\n");
+//! ```
+
+use crate::width::wrapped_rows;
+
+/// Move the cursor to the start of the line `rows` up and clear to the end of
+/// the screen.
+pub(crate) fn erase_rows(rows: usize) -> String {
+    format!("\x1b[{}F\x1b[J", rows)
+}
+
+/// A block of output that can be replaced in place.
+#[derive(Debug, Clone, Default)]
+pub struct LiveRegion {
+    rows: usize,
+    terminal_size: Option<(u16, u16)>,
+}
+
+impl LiveRegion {
+    /// Create a region with nothing rendered yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use a fixed terminal size (columns, rows) instead of querying the
+    /// terminal.
+    pub fn with_terminal_size(mut self, columns: u16, rows: u16) -> Self {
+        self.terminal_size = Some((columns, rows));
+        self
+    }
+
+    /// Output that replaces the previous content with `content`.
+    ///
+    /// The previous content is only erased while it still fits on screen;
+    /// otherwise part of it has scrolled out of reach, and `content` is
+    /// printed below it instead. A trailing newline is added if missing.
+    pub fn update(&mut self, content: &str) -> String {
+        let mut out = self.clear();
+        out.push_str(content);
+        if !content.ends_with('\n') {
+            out.push('\n');
+        }
+        let columns = self
+            .terminal_size()
+            .map_or(usize::MAX, |(columns, _)| columns as usize);
+        self.rows = wrapped_rows(content, columns);
+        out
+    }
+
+    /// Output that erases the current content, if it is still on screen.
+    pub fn clear(&mut self) -> String {
+        let rows = std::mem::take(&mut self.rows);
+        let screen_rows = self.terminal_size().map_or(0, |(_, rows)| rows as usize);
+        if rows > 0 && rows < screen_rows {
+            erase_rows(rows)
+        } else {
+            String::new()
+        }
+    }
+
+    /// Forget the current content, so the next update prints below it. Call
+    /// this when other output has been printed after the region.
+    pub fn detach(&mut self) {
+        self.rows = 0;
+    }
+
+    /// Terminal rows taken by the current content.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    fn terminal_size(&self) -> Option<(u16, u16)> {
+        self.terminal_size
+            .or_else(|| crossterm::terminal::size().ok())
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_update_prints_content() {
+        let mut region = LiveRegion::new().with_terminal_size(80, 24);
+        assert_eq!(region.update("a\nb"), "a\nb\n");
+        assert_eq!(region.rows(), 2);
+    }
+
+    #[test]
+    fn test_update_replaces_wrapped_content() {
+        let mut region = LiveRegion::new().with_terminal_size(10, 24);
+        region.update(&format!("{}\nshort\n", "x".repeat(15)));
+        assert_eq!(region.rows(), 3);
+        assert_eq!(region.update("new\n"), "\x1b[3F\x1b[Jnew\n");
+        assert_eq!(region.rows(), 1);
+    }
+
+    #[test]
+    fn test_content_taller_than_screen_is_not_erased() {
+        let mut region = LiveRegion::new().with_terminal_size(80, 3);
+        region.update("1\n2\n3\n");
+        assert_eq!(region.update("4\n"), "4\n");
+        assert_eq!(region.clear(), "\x1b[1F\x1b[J");
+        assert_eq!(region.clear(), "");
+    }
+
+    #[test]
+    fn test_detach_prints_below() {
+        let mut region = LiveRegion::new().with_terminal_size(80, 24);
+        region.update("plan v1\n");
+        region.detach();
+        assert_eq!(region.update("plan v2\n"), "plan v2\n");
+    }
+}
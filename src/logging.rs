@@ -83,6 +83,9 @@ pub fn get_output_sink() -> Option<Arc<dyn OutputSink>> {
 }
 
 /// Log a complete block with trailing blank line for visual separation.
+///
+/// Messages are passed through as is; tool arguments and output are redacted
+/// when they are formatted (see [`mod@crate::redact`]).
 pub fn log_event(message: &str) {
     if !is_logging_enabled() {
         return;
@@ -226,6 +229,28 @@ mod tests {
         reset_output_sink();
     }
 
+    #[test]
+    fn test_log_event_leaves_agent_text_alone() {
+        enable_logging(); // Ensure logging is enabled (may have been disabled by other tests)
+        reset_output_sink();
+
+        let sink = Arc::new(MockSink::new());
+        set_output_sink(sink.clone());
+
+        colored::control::set_override(false);
+        let mut buffer = crate::TextBuffer::new();
+        buffer.push("The secret: rotate it.\n\n```rust\nstruct Login { password: String }\n```\n");
+        let rendered = buffer.flush().unwrap();
+        colored::control::unset_override();
+        assert!(rendered.contains("struct Login { password: String }"));
+        log_event(&rendered);
+        log_event_line("let api_key = env::var(\"API_KEY\")?;");
+        assert_eq!(sink.emits(), vec![rendered]);
+        assert_eq!(sink.lines(), vec!["let api_key = env::var(\"API_KEY\")?;"]);
+
+        reset_output_sink();
+    }
+
     #[test]
     fn test_log_event_noop_when_no_sink() {
         reset_output_sink();
@@ -0,0 +1,406 @@
+//! Terminal and desktop notifications for long turns, permission prompts and
+//! errors.
+//!
+//! When a turn finishes after minutes of work the user has usually switched
+//! windows. [`notify`] tells them with the terminal bell or a desktop
+//! notification escape (OSC 9 or OSC 777), wrapped in a tmux passthrough
+//! when running inside tmux (which needs `set -g allow-passthrough on`).
+//!
+//! Notifications are off by default. Enable them with [`set_notify_config`];
+//! turns shorter than [`NotifyConfig::min_duration`] are then skipped, while
+//! permission prompts and errors always notify. Nothing is written when stdout
+//! is not a terminal.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use clemitui::{NotifyConfig, NotifyEvent, NotifyMethod, notify, set_notify_config};
+//! use std::sync::Arc;
+//! use std::time::{Duration, Instant};
+//!
+//! set_notify_config(Arc::new(
+//!     NotifyConfig::default()
+//!         .with_method(NotifyMethod::Auto)
+//!         .with_min_duration(Duration::from_secs(30)),
+//! ));
+//!
+//! let started = Instant::now();
+//! // ... run the turn ...
+//! notify(&NotifyEvent::TurnComplete(started.elapsed()))?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::env;
+use std::io::{self, IsTerminal, Write};
+use std::sync::{Arc, LazyLock, RwLock};
+use std::time::Duration;
+
+use crate::format::format_elapsed;
+use crate::width::strip_ansi;
+
+/// Default shortest turn that notifies on completion.
+pub const DEFAULT_NOTIFY_MIN_DURATION: Duration = Duration::from_secs(10);
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// How notifications reach the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NotifyMethod {
+    /// Never notify.
+    #[default]
+    Off,
+    /// Detect the best method from the environment.
+    Auto,
+    /// The terminal bell (`BEL`), which most terminals and tmux turn into an
+    /// urgency hint or a sound.
+    Bell,
+    /// OSC 9 desktop notification (iTerm2, WezTerm, Ghostty, kitty).
+    Osc9,
+    /// OSC 777 desktop notification with a title (foot, urxvt, VTE-based
+    /// terminals, WezTerm, Ghostty).
+    Osc777,
+}
+
+impl NotifyMethod {
+    /// The method supported by the environment described by `var`.
+    ///
+    /// Recognises OSC 9 terminals from `TERM_PROGRAM` (or `LC_TERMINAL`, which
+    /// survives ssh and tmux) and `TERM`, and OSC 777 terminals from `TERM`
+    /// and `VTE_VERSION`. Anything else gets the bell.
+    pub fn detect_with(var: impl Fn(&str) -> Option<String>) -> Self {
+        let program = var("TERM_PROGRAM")
+            .filter(|program| program != "tmux")
+            .or_else(|| var("LC_TERMINAL"))
+            .unwrap_or_default();
+        let term = var("TERM").unwrap_or_default();
+        if matches!(
+            program.as_str(),
+            "iTerm.app" | "iTerm2" | "WezTerm" | "ghostty"
+        ) || term.contains("kitty")
+            || term.contains("ghostty")
+        {
+            Self::Osc9
+        } else if term.contains("foot") || term.contains("rxvt") || var("VTE_VERSION").is_some() {
+            Self::Osc777
+        } else {
+            Self::Bell
+        }
+    }
+}
+
+/// When and how to notify.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotifyConfig {
+    /// Notification method; [`NotifyMethod::Off`] disables notifications.
+    pub method: NotifyMethod,
+    /// Turns shorter than this do not notify on completion.
+    pub min_duration: Duration,
+    /// Title of OSC 777 notifications.
+    pub title: String,
+}
+
+impl Default for NotifyConfig {
+    fn default() -> Self {
+        Self {
+            method: NotifyMethod::Off,
+            min_duration: DEFAULT_NOTIFY_MIN_DURATION,
+            title: "Agent".to_string(),
+        }
+    }
+}
+
+impl NotifyConfig {
+    /// Set the notification method.
+    pub fn with_method(mut self, method: NotifyMethod) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// Only notify on completion of turns at least this long.
+    pub fn with_min_duration(mut self, min_duration: Duration) -> Self {
+        self.min_duration = min_duration;
+        self
+    }
+
+    /// Set the title of OSC 777 notifications.
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+}
+
+static DEFAULT_NOTIFY_CONFIG: LazyLock<Arc<NotifyConfig>> =
+    LazyLock::new(|| Arc::new(NotifyConfig::default()));
+
+static NOTIFY_CONFIG: RwLock<Option<Arc<NotifyConfig>>> = RwLock::new(None);
+
+/// Set the global notification configuration used by [`notify`].
+pub fn set_notify_config(config: Arc<NotifyConfig>) {
+    if let Ok(mut guard) = NOTIFY_CONFIG.write() {
+        *guard = Some(config);
+    }
+}
+
+/// Reset the global notification configuration (notifications off).
+pub fn reset_notify_config() {
+    if let Ok(mut guard) = NOTIFY_CONFIG.write() {
+        *guard = None;
+    }
+}
+
+/// Get the current global notification configuration.
+pub fn notify_config() -> Arc<NotifyConfig> {
+    NOTIFY_CONFIG
+        .read()
+        .ok()
+        .and_then(|guard| guard.clone())
+        .unwrap_or_else(|| Arc::clone(&DEFAULT_NOTIFY_CONFIG))
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+/// Something the user should hear about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotifyEvent {
+    /// A turn ended after the given time.
+    TurnComplete(Duration),
+    /// The agent asked permission to run the named tool.
+    PermissionRequested(String),
+    /// An error ended or interrupted the turn.
+    Error(String),
+}
+
+impl NotifyEvent {
+    /// The notification text, without colors or control characters.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clemitui::NotifyEvent;
+    /// use std::time::Duration;
+    ///
+    /// let event = NotifyEvent::TurnComplete(Duration::from_secs(125));
+    /// assert_eq!(event.message(), "Turn finished after 2m 05s");
+    /// ```
+    pub fn message(&self) -> String {
+        let message = match self {
+            Self::TurnComplete(elapsed) => {
+                format!("Turn finished after {}", format_elapsed(*elapsed))
+            }
+            Self::PermissionRequested(tool) => format!("Permission needed for {tool}"),
+            Self::Error(message) => format!("Error: {message}"),
+        };
+        strip_ansi(&message)
+            .chars()
+            .map(|c| if c.is_control() { ' ' } else { c })
+            .collect()
+    }
+}
+
+// ============================================================================
+// Formatting
+// ============================================================================
+
+/// The escape sequence announcing `event`, or None if `config` disables it.
+///
+/// Turn completions shorter than [`NotifyConfig::min_duration`] are skipped.
+/// [`NotifyMethod::Auto`] is resolved with [`NotifyMethod::detect_with`], and
+/// OSC sequences are wrapped in a tmux passthrough when `TMUX` is set.
+///
+/// # Example
+///
+/// ```
+/// use clemitui::{NotifyConfig, NotifyEvent, NotifyMethod, format_notification_with};
+/// use std::time::Duration;
+///
+/// let config = NotifyConfig::default().with_method(NotifyMethod::Osc9);
+/// let no_env = |_: &str| None;
+/// let long = NotifyEvent::TurnComplete(Duration::from_secs(90));
+/// assert_eq!(
+///     format_notification_with(&long, &config, no_env).as_deref(),
+///     Some("\x1b]9;Turn finished after 1m 30s\x07")
+/// );
+/// let quick = NotifyEvent::TurnComplete(Duration::from_secs(2));
+/// assert_eq!(format_notification_with(&quick, &config, no_env), None);
+/// ```
+pub fn format_notification_with(
+    event: &NotifyEvent,
+    config: &NotifyConfig,
+    var: impl Fn(&str) -> Option<String>,
+) -> Option<String> {
+    if let NotifyEvent::TurnComplete(elapsed) = event
+        && *elapsed < config.min_duration
+    {
+        return None;
+    }
+    let method = match config.method {
+        NotifyMethod::Auto => NotifyMethod::detect_with(&var),
+        method => method,
+    };
+    let sequence = match method {
+        NotifyMethod::Off => return None,
+        // tmux handles the bell itself, so it needs no passthrough
+        NotifyMethod::Bell => return Some("\x07".to_string()),
+        NotifyMethod::Osc9 => format!("\x1b]9;{}\x07", event.message()),
+        NotifyMethod::Osc777 => format!(
+            "\x1b]777;notify;{};{}\x07",
+            config.title.replace(';', ","),
+            event.message()
+        ),
+        NotifyMethod::Auto => unreachable!("Auto is resolved above"),
+    };
+    if var("TMUX").is_some() {
+        Some(tmux_passthrough(&sequence))
+    } else {
+        Some(sequence)
+    }
+}
+
+/// The escape sequence announcing `event` in the process environment.
+pub fn format_notification(event: &NotifyEvent, config: &NotifyConfig) -> Option<String> {
+    format_notification_with(event, config, |name| env::var(name).ok())
+}
+
+/// Wrap `sequence` so tmux forwards it to the outer terminal.
+fn tmux_passthrough(sequence: &str) -> String {
+    format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+}
+
+/// Notify the user of `event` per the global [`NotifyConfig`].
+///
+/// Does nothing when notifications are off, the turn was too short or stdout
+/// is not a terminal.
+pub fn notify(event: &NotifyEvent) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    if !stdout.is_terminal() {
+        return Ok(());
+    }
+    match format_notification(event, &notify_config()) {
+        Some(sequence) => {
+            stdout.write_all(sequence.as_bytes())?;
+            stdout.flush()
+        }
+        None => Ok(()),
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(vars: &'static [(&'static str, &'static str)]) -> impl Fn(&str) -> Option<String> {
+        move |name| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        }
+    }
+
+    fn long_turn() -> NotifyEvent {
+        NotifyEvent::TurnComplete(Duration::from_secs(300))
+    }
+
+    #[test]
+    fn test_off_by_default() {
+        let config = NotifyConfig::default();
+        assert_eq!(
+            format_notification_with(&long_turn(), &config, env(&[])),
+            None
+        );
+    }
+
+    #[test]
+    fn test_min_duration_applies_to_turns_only() {
+        let config = NotifyConfig::default()
+            .with_method(NotifyMethod::Bell)
+            .with_min_duration(Duration::from_secs(60));
+        let quick = NotifyEvent::TurnComplete(Duration::from_secs(59));
+        assert_eq!(format_notification_with(&quick, &config, env(&[])), None);
+        let exact = NotifyEvent::TurnComplete(Duration::from_secs(60));
+        assert_eq!(
+            format_notification_with(&exact, &config, env(&[])).as_deref(),
+            Some("\x07")
+        );
+        let permission = NotifyEvent::PermissionRequested("bash".into());
+        assert!(format_notification_with(&permission, &config, env(&[])).is_some());
+        let error = NotifyEvent::Error("rate limited".into());
+        assert!(format_notification_with(&error, &config, env(&[])).is_some());
+    }
+
+    #[test]
+    fn test_osc777_includes_title() {
+        let config = NotifyConfig::default()
+            .with_method(NotifyMethod::Osc777)
+            .with_title("my;agent");
+        let event = NotifyEvent::PermissionRequested("write_file".into());
+        assert_eq!(
+            format_notification_with(&event, &config, env(&[])).as_deref(),
+            Some("\x1b]777;notify;my,agent;Permission needed for write_file\x07")
+        );
+    }
+
+    #[test]
+    fn test_tmux_passthrough() {
+        let config = NotifyConfig::default().with_method(NotifyMethod::Osc9);
+        let tmux = env(&[("TMUX", "/tmp/tmux-1000/default,1,0")]);
+        assert_eq!(
+            format_notification_with(&long_turn(), &config, tmux).as_deref(),
+            Some("\x1bPtmux;\x1b\x1b]9;Turn finished after 5m 00s\x07\x1b\\")
+        );
+        let bell = NotifyConfig::default().with_method(NotifyMethod::Bell);
+        let tmux = env(&[("TMUX", "/tmp/tmux-1000/default,1,0")]);
+        assert_eq!(
+            format_notification_with(&long_turn(), &bell, tmux).as_deref(),
+            Some("\x07")
+        );
+    }
+
+    #[test]
+    fn test_message_strips_escapes() {
+        let event = NotifyEvent::Error("\x1b[31mboom\x1b[0m\x07\nagain".into());
+        assert_eq!(event.message(), "Error: boom  again");
+    }
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(NotifyMethod::detect_with(env(&[])), NotifyMethod::Bell);
+        assert_eq!(
+            NotifyMethod::detect_with(env(&[("TERM_PROGRAM", "iTerm.app")])),
+            NotifyMethod::Osc9
+        );
+        assert_eq!(
+            NotifyMethod::detect_with(env(&[("TERM", "xterm-kitty")])),
+            NotifyMethod::Osc9
+        );
+        assert_eq!(
+            NotifyMethod::detect_with(env(&[("TERM", "foot")])),
+            NotifyMethod::Osc777
+        );
+        assert_eq!(
+            NotifyMethod::detect_with(env(&[("VTE_VERSION", "7600")])),
+            NotifyMethod::Osc777
+        );
+        assert_eq!(
+            NotifyMethod::detect_with(env(&[("TERM_PROGRAM", "tmux"), ("LC_TERMINAL", "iTerm2")])),
+            NotifyMethod::Osc9
+        );
+    }
+
+    #[test]
+    fn test_global_config() {
+        set_notify_config(Arc::new(
+            NotifyConfig::default().with_min_duration(Duration::from_secs(1)),
+        ));
+        assert_eq!(notify_config().min_duration, Duration::from_secs(1));
+        reset_notify_config();
+        assert_eq!(notify_config().min_duration, DEFAULT_NOTIFY_MIN_DURATION);
+    }
+}
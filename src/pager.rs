@@ -0,0 +1,375 @@
+//! Paging of oversized output blocks.
+//!
+//! A big file dump or a long plan rendered by [`TextBuffer::flush`] can run to
+//! thousands of lines that scroll past before anyone reads them.
+//! [`print_paged`] prints such blocks through a pager instead: `$PAGER`
+//! (default `less -R`, which keeps colors) or a built-in pager on the
+//! alternate screen with scrolling and `/` search.
+//!
+//! Paging is off by default. Enable it with [`set_pager_config`]; blocks with
+//! more lines than the configured threshold are then paged, and everything
+//! else is printed as usual. Paging is skipped automatically when stdout is not
+//! a terminal, so piped or captured output is never held up by a pager.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use clemitui::{PagerConfig, PagerMode, TextBuffer, print_paged, set_pager_config};
+//! use std::sync::Arc;
+//!
+//! set_pager_config(Arc::new(
+//!     PagerConfig::default()
+//!         .with_mode(PagerMode::External)
+//!         .with_threshold(500),
+//! ));
+//!
+//! let mut buffer = TextBuffer::new();
+//! buffer.push("...thousands of lines...");
+//! if let Some(rendered) = buffer.flush() {
+//!     print_paged(&rendered)?;
+//! }
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! [`TextBuffer::flush`]: crate::TextBuffer::flush
+
+use std::env;
+use std::io::{self, IsTerminal, Write};
+use std::process::{Command, Stdio};
+#[cfg(unix)]
+use std::sync::Mutex;
+use std::sync::{Arc, LazyLock, RwLock};
+use std::time::Duration;
+
+use crate::fullscreen::{Fullscreen, FullscreenView};
+use crate::logging::OutputSink;
+use crate::prompt::PromptAction;
+
+/// Pager used when `$PAGER` is unset or empty.
+pub const DEFAULT_PAGER_COMMAND: &str = "less -R";
+
+/// Default number of lines a block may have before it is paged.
+pub const DEFAULT_PAGER_THRESHOLD: usize = 1000;
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Which pager oversized blocks go through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PagerMode {
+    /// Never page.
+    #[default]
+    Off,
+    /// Pipe through an external pager (`$PAGER`, default `less -R`).
+    External,
+    /// Show in the built-in full-screen pager.
+    Builtin,
+}
+
+/// When and how to page output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PagerConfig {
+    /// Pager to use; [`PagerMode::Off`] disables paging.
+    pub mode: PagerMode,
+    /// Blocks with more lines than this are paged.
+    pub threshold: usize,
+    /// External pager command, split on whitespace (no shell quoting).
+    /// `None` uses `$PAGER`, then [`DEFAULT_PAGER_COMMAND`].
+    pub command: Option<String>,
+}
+
+impl Default for PagerConfig {
+    fn default() -> Self {
+        Self {
+            mode: PagerMode::Off,
+            threshold: DEFAULT_PAGER_THRESHOLD,
+            command: None,
+        }
+    }
+}
+
+impl PagerConfig {
+    /// Set the pager mode.
+    pub fn with_mode(mut self, mode: PagerMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Page blocks with more than `lines` lines.
+    pub fn with_threshold(mut self, lines: usize) -> Self {
+        self.threshold = lines;
+        self
+    }
+
+    /// Use `command` as the external pager instead of `$PAGER`.
+    pub fn with_command(mut self, command: impl Into<String>) -> Self {
+        self.command = Some(command.into());
+        self
+    }
+
+    /// Whether `text` should be paged when printed to a terminal.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clemitui::{PagerConfig, PagerMode};
+    ///
+    /// let config = PagerConfig::default()
+    ///     .with_mode(PagerMode::Builtin)
+    ///     .with_threshold(2);
+    /// assert!(!config.should_page("one\ntwo\n"));
+    /// assert!(config.should_page("one\ntwo\nthree\n"));
+    /// assert!(!PagerConfig::default().should_page(&"x\n".repeat(5000)));
+    /// ```
+    pub fn should_page(&self, text: &str) -> bool {
+        self.mode != PagerMode::Off && text.lines().count() > self.threshold
+    }
+
+    /// The external pager command: the configured one, `$PAGER`, or
+    /// [`DEFAULT_PAGER_COMMAND`].
+    pub fn pager_command(&self) -> String {
+        self.pager_command_with(|name| env::var(name).ok())
+    }
+
+    /// [`Self::pager_command`] with an injectable environment lookup.
+    fn pager_command_with(&self, env_var: impl Fn(&str) -> Option<String>) -> String {
+        self.command
+            .clone()
+            .or_else(|| env_var("PAGER"))
+            .filter(|command| !command.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_PAGER_COMMAND.to_string())
+    }
+}
+
+static DEFAULT_PAGER_CONFIG: LazyLock<Arc<PagerConfig>> =
+    LazyLock::new(|| Arc::new(PagerConfig::default()));
+
+static PAGER_CONFIG: RwLock<Option<Arc<PagerConfig>>> = RwLock::new(None);
+
+/// Set the global pager configuration used by [`print_paged`].
+pub fn set_pager_config(config: Arc<PagerConfig>) {
+    if let Ok(mut guard) = PAGER_CONFIG.write() {
+        *guard = Some(config);
+    }
+}
+
+/// Reset the global pager configuration (paging off).
+pub fn reset_pager_config() {
+    if let Ok(mut guard) = PAGER_CONFIG.write() {
+        *guard = None;
+    }
+}
+
+/// Get the current global pager configuration.
+pub fn pager_config() -> Arc<PagerConfig> {
+    PAGER_CONFIG
+        .read()
+        .ok()
+        .and_then(|guard| guard.clone())
+        .unwrap_or_else(|| Arc::clone(&DEFAULT_PAGER_CONFIG))
+}
+
+// ============================================================================
+// Paging
+// ============================================================================
+
+/// Print `text` to stdout, through a pager if it is oversized.
+///
+/// The text is paged when the global [`PagerConfig`] enables paging, `text`
+/// has more lines than its threshold and stdout is a terminal; otherwise it
+/// is printed as is. An external pager that cannot be started falls back to
+/// the built-in one.
+pub fn print_paged(text: &str) -> io::Result<()> {
+    let config = pager_config();
+    if !config.should_page(text) || !io::stdout().is_terminal() {
+        let mut stdout = io::stdout();
+        stdout.write_all(text.as_bytes())?;
+        return stdout.flush();
+    }
+    match config.mode {
+        PagerMode::External => {
+            let command = config.pager_command();
+            let argv: Vec<&str> = command.split_whitespace().collect();
+            match run_external(&argv, text, Stdio::inherit()) {
+                Err(err) if err.kind() == io::ErrorKind::NotFound => run_builtin(text),
+                result => result,
+            }
+        }
+        PagerMode::Builtin => run_builtin(text),
+        PagerMode::Off => unreachable!("should_page is false when paging is off"),
+    }
+}
+
+/// Pipe `text` into the pager `argv` and wait for it to exit.
+///
+/// Ctrl+C is ignored until the pager exits, so it only reaches the pager
+/// instead of killing this process and leaving the pager on the terminal.
+fn run_external(argv: &[&str], text: &str, stdout: Stdio) -> io::Result<()> {
+    let (program, args) = argv
+        .split_first()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "empty pager command"))?;
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(stdout)
+        .spawn()?;
+    // After spawning, so the pager does not inherit the ignored disposition
+    let _interrupts = IgnoreInterrupts::new();
+    if let Some(mut stdin) = child.stdin.take() {
+        // The pager closes its input when the user quits before the end
+        match stdin.write_all(text.as_bytes()) {
+            Err(err) if err.kind() == io::ErrorKind::BrokenPipe => {}
+            result => result?,
+        }
+    }
+    child.wait()?;
+    Ok(())
+}
+
+/// Live [`IgnoreInterrupts`] guards and the SIGINT disposition the first
+/// one replaced.
+#[cfg(unix)]
+static IGNORED_INTERRUPTS: Mutex<(usize, libc::sighandler_t)> = Mutex::new((0, libc::SIG_DFL));
+
+/// Ignores SIGINT until the last guard is dropped, which restores the
+/// previous disposition.
+#[cfg(unix)]
+struct IgnoreInterrupts;
+
+#[cfg(unix)]
+impl IgnoreInterrupts {
+    fn new() -> Self {
+        let mut ignored = IGNORED_INTERRUPTS.lock().unwrap_or_else(|e| e.into_inner());
+        if ignored.0 == 0 {
+            // SAFETY: SIG_IGN runs no code in the signal context
+            ignored.1 = unsafe { libc::signal(libc::SIGINT, libc::SIG_IGN) };
+        }
+        ignored.0 += 1;
+        Self
+    }
+}
+
+#[cfg(unix)]
+impl Drop for IgnoreInterrupts {
+    fn drop(&mut self) {
+        let mut ignored = IGNORED_INTERRUPTS.lock().unwrap_or_else(|e| e.into_inner());
+        ignored.0 -= 1;
+        if ignored.0 == 0 && ignored.1 != libc::SIG_ERR {
+            // SAFETY: reinstalls the disposition `signal` returned before
+            unsafe { libc::signal(libc::SIGINT, ignored.1) };
+        }
+    }
+}
+
+#[cfg(not(unix))]
+struct IgnoreInterrupts;
+
+#[cfg(not(unix))]
+impl IgnoreInterrupts {
+    fn new() -> Self {
+        Self
+    }
+}
+
+/// Show `text` in the built-in pager until the user quits.
+fn run_builtin(text: &str) -> io::Result<()> {
+    let view = FullscreenView::viewer().with_sidebar(false);
+    view.sink().emit_line(text.trim_end_matches('\n'));
+    let mut screen = Fullscreen::enter(view)?;
+    loop {
+        if let Some(PromptAction::Eof | PromptAction::Cancel) =
+            screen.read_event(Duration::from_millis(100))?
+        {
+            return screen.leave();
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_is_off() {
+        let config = PagerConfig::default();
+        assert_eq!(config.mode, PagerMode::Off);
+        assert_eq!(config.threshold, DEFAULT_PAGER_THRESHOLD);
+        assert!(!config.should_page(&"x\n".repeat(10_000)));
+    }
+
+    #[test]
+    fn test_threshold_is_exclusive() {
+        let config = PagerConfig::default()
+            .with_mode(PagerMode::External)
+            .with_threshold(3);
+        assert!(!config.should_page("a\nb\nc"));
+        assert!(config.should_page("a\nb\nc\nd"));
+    }
+
+    #[test]
+    fn test_pager_command_precedence() {
+        fn env(value: Option<&'static str>) -> impl Fn(&str) -> Option<String> {
+            move |_| value.map(String::from)
+        }
+        let config = PagerConfig::default();
+        assert_eq!(config.pager_command_with(env(None)), "less -R");
+        assert_eq!(config.pager_command_with(env(Some("  "))), "less -R");
+        assert_eq!(config.pager_command_with(env(Some("most"))), "most");
+        let custom = config.with_command("bat --paging=always");
+        assert_eq!(
+            custom.pager_command_with(env(Some("most"))),
+            "bat --paging=always"
+        );
+    }
+
+    #[test]
+    fn test_global_config() {
+        set_pager_config(Arc::new(PagerConfig::default().with_threshold(7)));
+        assert_eq!(pager_config().threshold, 7);
+        reset_pager_config();
+        assert_eq!(pager_config().threshold, DEFAULT_PAGER_THRESHOLD);
+    }
+
+    #[test]
+    fn test_run_external_pipes_text() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("paged");
+        let path_str = path.to_str().unwrap();
+        run_external(&["tee", path_str], "\x1b[31mred\x1b[0m\n", Stdio::null()).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "\x1b[31mred\x1b[0m\n",
+            "colors reach the pager untouched"
+        );
+    }
+
+    #[test]
+    fn test_run_external_pager_quitting_early() {
+        let text = "line\n".repeat(100_000);
+        run_external(&["head", "-n", "1"], &text, Stdio::null()).unwrap();
+    }
+
+    #[test]
+    fn test_run_external_survives_interrupt() {
+        // Ctrl+C at the terminal signals the whole foreground process group
+        run_external(
+            &["sh", "-c", "sleep 0.1; kill -INT $PPID; cat >/dev/null"],
+            "text",
+            Stdio::null(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_run_external_missing_command() {
+        let err = run_external(&["clemitui-no-such-pager"], "text", Stdio::null()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        let err = run_external(&[], "text", Stdio::null()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+}
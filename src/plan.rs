@@ -0,0 +1,258 @@
+//! Agent plans from ACP `plan` updates.
+//!
+//! ACP agents report their execution plan as a list of entries, each with a
+//! content string, a priority and a status, and resend the whole list whenever
+//! it changes. [`format_plan`] renders it as a checklist with a progress line;
+//! pair it with a [`LiveRegion`](crate::LiveRegion) to replace the previous
+//! plan in place when an update arrives.
+//!
+//! # Usage
+//!
+//! ```
+//! use clemitui::{LiveRegion, Plan, format_plan};
+//! use serde_json::json;
+//!
+//! let mut region = LiveRegion::new();
+//! let update = json!({
+//!     "sessionUpdate": "plan",
+//!     "entries": [
+//!         {"content": "Read the config", "priority": "high", "status": "completed"},
+//!         {"content": "Patch the parser", "priority": "medium", "status": "in_progress"}
+//!     ]
+//! });
+//! print!("{}", region.update(&format_plan(&Plan::from_acp(&update))));
+//! ```
+
+use colored::Colorize;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Width of the progress bar in the plan header.
+const PROGRESS_BAR_WIDTH: usize = 10;
+
+// ============================================================================
+// Plan Entries
+// ============================================================================
+
+/// Relative importance of a plan entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanPriority {
+    /// Marked `!`.
+    High,
+    /// Unmarked (default).
+    #[default]
+    Medium,
+    /// Marked `↓`.
+    Low,
+}
+
+/// Execution status of a plan entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanStatus {
+    /// Not started yet (default).
+    #[default]
+    Pending,
+    /// Currently being worked on.
+    InProgress,
+    /// Done.
+    Completed,
+}
+
+/// A single task in an agent's plan.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct PlanEntry {
+    /// Human-readable description of the task.
+    pub content: String,
+    /// Relative importance.
+    #[serde(default)]
+    pub priority: PlanPriority,
+    /// Current status.
+    #[serde(default)]
+    pub status: PlanStatus,
+}
+
+impl PlanEntry {
+    /// Create a plan entry.
+    pub fn new(content: impl Into<String>, priority: PlanPriority, status: PlanStatus) -> Self {
+        Self {
+            content: content.into(),
+            priority,
+            status,
+        }
+    }
+}
+
+/// An agent's plan, as last reported.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Plan {
+    /// Entries in execution order.
+    pub entries: Vec<PlanEntry>,
+}
+
+impl Plan {
+    /// Create a plan from entries.
+    pub fn new(entries: Vec<PlanEntry>) -> Self {
+        Self { entries }
+    }
+
+    /// Parse an ACP `plan` update (an object with `entries`) or a bare array
+    /// of entries. Malformed entries are skipped.
+    pub fn from_acp(update: &Value) -> Self {
+        let entries = update
+            .get("entries")
+            .unwrap_or(update)
+            .as_array()
+            .map(|entries| {
+                entries
+                    .iter()
+                    .filter_map(|entry| PlanEntry::deserialize(entry).ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self { entries }
+    }
+
+    /// Number of completed entries.
+    pub fn completed(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| entry.status == PlanStatus::Completed)
+            .count()
+    }
+
+    /// Whether every entry is completed.
+    pub fn is_complete(&self) -> bool {
+        self.completed() == self.entries.len()
+    }
+}
+
+// ============================================================================
+// Formatting
+// ============================================================================
+
+/// Format a plan as a checklist under a progress line.
+///
+/// Completed entries are marked `✓`, the entry in progress `◐` and pending
+/// ones `○`. When any entry has a non-default priority, a marker column shows
+/// `!` for high and `↓` for low priority. Returns an empty string for an empty
+/// plan.
+///
+/// # Example
+///
+/// ```
+/// use clemitui::{Plan, PlanEntry, PlanPriority, PlanStatus, format_plan};
+///
+/// colored::control::set_override(false);
+/// let plan = Plan::new(vec![
+///     PlanEntry::new("Read the config", PlanPriority::Medium, PlanStatus::Completed),
+///     PlanEntry::new("Patch the parser", PlanPriority::High, PlanStatus::InProgress),
+///     PlanEntry::new("Add tests", PlanPriority::Low, PlanStatus::Pending),
+/// ]);
+/// assert_eq!(
+///     format_plan(&plan),
+///     "Plan [███░░░░░░░] 1/3\n  ✓   Read the config\n  ◐ ! Patch the parser\n  ○ ↓ Add tests\n"
+/// );
+/// colored::control::unset_override();
+/// ```
+pub fn format_plan(plan: &Plan) -> String {
+    if plan.entries.is_empty() {
+        return String::new();
+    }
+
+    let total = plan.entries.len();
+    let completed = plan.completed();
+    let filled = (completed * PROGRESS_BAR_WIDTH + total / 2) / total;
+    let mut out = format!(
+        "{} [{}{}] {}/{}\n",
+        "Plan".bold(),
+        "█".repeat(filled).green(),
+        "░".repeat(PROGRESS_BAR_WIDTH - filled),
+        completed,
+        total
+    );
+
+    let show_priority = plan
+        .entries
+        .iter()
+        .any(|entry| entry.priority != PlanPriority::Medium);
+    for entry in &plan.entries {
+        let content = entry.content.lines().collect::<Vec<_>>().join(" ");
+        let (glyph, content) = match entry.status {
+            PlanStatus::Completed => ("✓".green(), content.dimmed()),
+            PlanStatus::InProgress => ("◐".yellow(), content.bold()),
+            PlanStatus::Pending => ("○".dimmed(), content.normal()),
+        };
+        out.push_str(&format!("  {} ", glyph));
+        if show_priority {
+            let marker = match entry.priority {
+                PlanPriority::High => "!".red().bold(),
+                PlanPriority::Medium => " ".normal(),
+                PlanPriority::Low => "↓".dimmed(),
+            };
+            out.push_str(&format!("{} ", marker));
+        }
+        out.push_str(&format!("{}\n", content));
+    }
+    out
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_from_acp_update() {
+        let plan = Plan::from_acp(&json!({
+            "sessionUpdate": "plan",
+            "entries": [
+                {"content": "a", "priority": "high", "status": "in_progress"},
+                {"content": "b"},
+                {"priority": "low"},
+                {"content": "c", "priority": "urgent", "status": "completed"}
+            ]
+        }));
+        assert_eq!(
+            plan.entries,
+            vec![
+                PlanEntry::new("a", PlanPriority::High, PlanStatus::InProgress),
+                PlanEntry::new("b", PlanPriority::Medium, PlanStatus::Pending),
+            ]
+        );
+        assert_eq!(Plan::from_acp(&json!([{"content": "x"}])).entries.len(), 1);
+        assert!(Plan::from_acp(&json!({"entries": null})).entries.is_empty());
+    }
+
+    #[test]
+    fn test_progress() {
+        let mut plan = Plan::new(vec![
+            PlanEntry::new("a", PlanPriority::Medium, PlanStatus::Completed),
+            PlanEntry::new("b", PlanPriority::Medium, PlanStatus::Pending),
+        ]);
+        assert_eq!(plan.completed(), 1);
+        assert!(!plan.is_complete());
+        plan.entries[1].status = PlanStatus::Completed;
+        assert!(plan.is_complete());
+    }
+
+    #[test]
+    fn test_format_plan_without_priorities() {
+        colored::control::set_override(false);
+        let plan = Plan::new(vec![
+            PlanEntry::new("done", PlanPriority::Medium, PlanStatus::Completed),
+            PlanEntry::new("multi\nline", PlanPriority::Medium, PlanStatus::Completed),
+        ]);
+        assert_eq!(
+            format_plan(&plan),
+            "Plan [██████████] 2/2\n  ✓ done\n  ✓ multi line\n"
+        );
+        assert_eq!(format_plan(&Plan::default()), "");
+        colored::control::unset_override();
+    }
+}
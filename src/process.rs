@@ -0,0 +1,828 @@
+//! Client-side ACP terminals.
+//!
+//! Agents run commands through the client with the `terminal/*` methods:
+//! `terminal/create` starts a process, `terminal/output` returns what it has
+//! printed so far, `terminal/wait_for_exit` blocks until it exits,
+//! `terminal/kill` stops it and `terminal/release` frees it. A
+//! [`TerminalManager`] implements those methods on local processes, keeping
+//! stdout and stderr in one buffer bounded by the requested byte limit.
+//!
+//! The output feeds a [`TerminalPane`](crate::TerminalPane) for display, and
+//! [`format_terminal_exit`] renders how the command ended.
+//!
+//! # Usage
+//!
+//! ```no_run
+//! use clemitui::{CreateTerminalRequest, TerminalManager, TerminalPane};
+//! use serde_json::json;
+//!
+//! let manager = TerminalManager::new();
+//!
+//! // Dispatch JSON-RPC requests from the agent
+//! let created = manager
+//!     .handle("terminal/create", &json!({"sessionId": "s1", "command": "cargo", "args": ["build"]}))
+//!     .unwrap();
+//!
+//! // Or call the typed API directly
+//! let id = manager.create(&CreateTerminalRequest::new("ls").with_args(["-la"])).unwrap();
+//! let mut pane = TerminalPane::new();
+//! print!("{}", pane.set_output(&manager.output(&id).unwrap().output));
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Read};
+use std::path::PathBuf;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+/// How often the exit watcher polls a running process.
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// How long after exit to wait for the output readers to reach end of file.
+///
+/// Background processes started by the command can hold the pipes open long
+/// after it exits, so the exit status is published without them after this.
+const DRAIN_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// How often a reader waiting on a silent pipe checks whether its terminal
+/// was closed.
+#[cfg(unix)]
+const READER_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Errors from the terminal methods.
+#[derive(Debug)]
+pub enum TerminalError {
+    /// No terminal with this ID exists (or it was released).
+    UnknownTerminal(String),
+    /// The command could not be started.
+    Spawn {
+        /// The command that failed to start.
+        command: String,
+        /// The underlying error.
+        source: io::Error,
+    },
+    /// The request parameters are malformed.
+    InvalidParams(serde_json::Error),
+    /// The method is not a `terminal/*` method.
+    UnknownMethod(String),
+}
+
+impl TerminalError {
+    /// JSON-RPC error code for this error.
+    pub fn code(&self) -> i64 {
+        match self {
+            Self::UnknownMethod(_) => -32601,
+            Self::UnknownTerminal(_) | Self::InvalidParams(_) => -32602,
+            Self::Spawn { .. } => -32603,
+        }
+    }
+}
+
+impl fmt::Display for TerminalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownTerminal(id) => write!(f, "unknown terminal: {id}"),
+            Self::Spawn { command, source } => write!(f, "failed to run {command}: {source}"),
+            Self::InvalidParams(e) => write!(f, "invalid terminal request: {e}"),
+            Self::UnknownMethod(method) => write!(f, "unknown method: {method}"),
+        }
+    }
+}
+
+impl std::error::Error for TerminalError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Spawn { source, .. } => Some(source),
+            Self::InvalidParams(e) => Some(e),
+            Self::UnknownTerminal(_) | Self::UnknownMethod(_) => None,
+        }
+    }
+}
+
+// ============================================================================
+// Requests and Responses
+// ============================================================================
+
+/// An environment variable for the command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvVariable {
+    /// Variable name.
+    pub name: String,
+    /// Variable value.
+    pub value: String,
+}
+
+/// Parameters of `terminal/create`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateTerminalRequest {
+    /// Program to run.
+    pub command: String,
+    /// Arguments to the program.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Extra environment variables.
+    #[serde(default)]
+    pub env: Vec<EnvVariable>,
+    /// Working directory; the client's own when unset.
+    #[serde(default)]
+    pub cwd: Option<PathBuf>,
+    /// Maximum bytes of output to keep. Older output is dropped beyond it.
+    #[serde(default)]
+    pub output_byte_limit: Option<u64>,
+}
+
+impl CreateTerminalRequest {
+    /// Request to run `command` without arguments.
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            command: command.into(),
+            args: Vec::new(),
+            env: Vec::new(),
+            cwd: None,
+            output_byte_limit: None,
+        }
+    }
+
+    /// Set the program arguments.
+    pub fn with_args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Add an environment variable.
+    pub fn with_env(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push(EnvVariable {
+            name: name.into(),
+            value: value.into(),
+        });
+        self
+    }
+
+    /// Set the working directory.
+    pub fn with_cwd(mut self, cwd: impl Into<PathBuf>) -> Self {
+        self.cwd = Some(cwd.into());
+        self
+    }
+
+    /// Set the output byte limit.
+    pub fn with_output_byte_limit(mut self, limit: u64) -> Self {
+        self.output_byte_limit = Some(limit);
+        self
+    }
+}
+
+/// How a command ended.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalExitStatus {
+    /// Exit code, if the process exited normally.
+    pub exit_code: Option<i32>,
+    /// Signal name such as `SIGKILL`, if the process was terminated by one.
+    pub signal: Option<String>,
+}
+
+impl TerminalExitStatus {
+    /// Whether the command exited with code 0.
+    pub fn success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+
+    fn from_status(status: ExitStatus) -> Self {
+        #[cfg(unix)]
+        let signal = {
+            use std::os::unix::process::ExitStatusExt;
+            status.signal().map(signal_name)
+        };
+        #[cfg(not(unix))]
+        let signal = None;
+        Self {
+            exit_code: status.code(),
+            signal,
+        }
+    }
+}
+
+/// Name of a Unix signal number.
+#[cfg(unix)]
+fn signal_name(signal: i32) -> String {
+    match signal {
+        1 => "SIGHUP".to_string(),
+        2 => "SIGINT".to_string(),
+        3 => "SIGQUIT".to_string(),
+        6 => "SIGABRT".to_string(),
+        9 => "SIGKILL".to_string(),
+        13 => "SIGPIPE".to_string(),
+        15 => "SIGTERM".to_string(),
+        n => format!("SIG{n}"),
+    }
+}
+
+/// Result of `terminal/output`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalOutput {
+    /// Output so far, stdout and stderr interleaved.
+    pub output: String,
+    /// Whether older output was dropped to stay within the byte limit.
+    pub truncated: bool,
+    /// How the command ended, once it has.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_status: Option<TerminalExitStatus>,
+}
+
+// ============================================================================
+// Terminals
+// ============================================================================
+
+#[derive(Debug, Default)]
+struct TerminalState {
+    output: Vec<u8>,
+    truncated: bool,
+    exit_status: Option<TerminalExitStatus>,
+    /// Output pipes not yet at end of file.
+    open_readers: usize,
+    /// Set when the terminal is killed or released after its command exited;
+    /// readers then close their pipes.
+    closed: bool,
+}
+
+#[derive(Debug)]
+struct Terminal {
+    child: Mutex<Child>,
+    /// Process ID of the command, which also leads its process group.
+    pid: u32,
+    state: Mutex<TerminalState>,
+    exited: Condvar,
+    drained: Condvar,
+    byte_limit: Option<usize>,
+}
+
+impl Terminal {
+    fn state(&self) -> MutexGuard<'_, TerminalState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn append(&self, bytes: &[u8]) {
+        let mut state = self.state();
+        state.output.extend_from_slice(bytes);
+        if let Some(limit) = self.byte_limit
+            && state.output.len() > limit
+        {
+            let mut start = state.output.len() - limit;
+            // Never start in the middle of a UTF-8 sequence
+            while state.output.get(start).is_some_and(|b| b & 0xc0 == 0x80) {
+                start += 1;
+            }
+            state.output.drain(..start);
+            state.truncated = true;
+        }
+    }
+
+    fn output(&self) -> TerminalOutput {
+        let state = self.state();
+        TerminalOutput {
+            output: String::from_utf8_lossy(&state.output).into_owned(),
+            truncated: state.truncated,
+            exit_status: state.exit_status.clone(),
+        }
+    }
+
+    fn wait_for_exit(&self) -> TerminalExitStatus {
+        let mut state = self.state();
+        loop {
+            if let Some(status) = &state.exit_status {
+                return status.clone();
+            }
+            state = self.exited.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+    }
+
+    /// Kill the command and everything it started, or close its output pipes
+    /// if it already exited.
+    ///
+    /// The process group is only signalled while its leader has not been
+    /// reaped: until then the group ID cannot be reused by unrelated
+    /// processes. Background processes that outlive the command may keep the
+    /// pipes open, so those are closed instead.
+    fn kill(&self) {
+        let mut child = self.child.lock().unwrap_or_else(|e| e.into_inner());
+        match child.try_wait() {
+            Ok(None) => self.send_kill(&mut child),
+            _ => {
+                drop(child);
+                self.state().closed = true;
+            }
+        }
+    }
+
+    /// Send SIGKILL to the command's process group.
+    #[cfg(unix)]
+    fn send_kill(&self, child: &mut Child) {
+        use rustix::process::{Pid, Signal, kill_process_group};
+        match i32::try_from(self.pid).ok().and_then(Pid::from_raw) {
+            Some(group) => {
+                // The unreaped leader keeps the group alive, so this succeeds
+                let _ = kill_process_group(group, Signal::KILL);
+            }
+            None => {
+                let _ = child.kill();
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn send_kill(&self, child: &mut Child) {
+        // Fails only if the process already exited
+        let _ = child.kill();
+    }
+
+    /// Wait until `pipe` can be read without blocking; false once the
+    /// terminal is closed.
+    #[cfg(unix)]
+    fn wait_readable(&self, pipe: &impl std::os::fd::AsFd) -> bool {
+        use rustix::event::{PollFd, PollFlags, Timespec, poll};
+        use rustix::io::Errno;
+        let timeout = Timespec::try_from(READER_POLL_INTERVAL).ok();
+        while !self.state().closed {
+            let mut fds = [PollFd::new(pipe, PollFlags::IN)];
+            match poll(&mut fds, timeout.as_ref()) {
+                Ok(0) | Err(Errno::INTR) => {}
+                Ok(_) => return true,
+                Err(_) => return false,
+            }
+        }
+        false
+    }
+
+    #[cfg(not(unix))]
+    fn wait_readable(&self, _pipe: &impl Read) -> bool {
+        !self.state().closed
+    }
+
+    /// A reader reached end of file on one of the output pipes.
+    fn reader_done(&self) {
+        self.state().open_readers -= 1;
+        self.drained.notify_all();
+    }
+
+    /// Poll for exit and publish the status once the output is drained, or
+    /// after [`DRAIN_TIMEOUT`] if other processes keep the pipes open.
+    fn watch(&self) {
+        let status = loop {
+            let result = self
+                .child
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .try_wait();
+            match result {
+                Ok(Some(status)) => break TerminalExitStatus::from_status(status),
+                Ok(None) => thread::sleep(EXIT_POLL_INTERVAL),
+                Err(_) => {
+                    break TerminalExitStatus {
+                        exit_code: None,
+                        signal: None,
+                    };
+                }
+            }
+        };
+        let deadline = Instant::now() + DRAIN_TIMEOUT;
+        let mut state = self.state();
+        while state.open_readers > 0 {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            state = self
+                .drained
+                .wait_timeout(state, remaining)
+                .unwrap_or_else(|e| e.into_inner())
+                .0;
+        }
+        state.exit_status = Some(status);
+        drop(state);
+        self.exited.notify_all();
+    }
+}
+
+/// A command's stdout or stderr pipe.
+#[cfg(unix)]
+trait Pipe: Read + std::os::fd::AsFd + Send + 'static {}
+#[cfg(unix)]
+impl<T: Read + std::os::fd::AsFd + Send + 'static> Pipe for T {}
+
+/// A command's stdout or stderr pipe.
+#[cfg(not(unix))]
+trait Pipe: Read + Send + 'static {}
+#[cfg(not(unix))]
+impl<T: Read + Send + 'static> Pipe for T {}
+
+/// Copy `pipe` into the terminal's output until end of file or until the
+/// terminal is closed, then close the pipe.
+fn spawn_reader(terminal: Arc<Terminal>, mut pipe: impl Pipe) {
+    thread::spawn(move || {
+        let mut buf = [0u8; 8192];
+        while terminal.wait_readable(&pipe) {
+            match pipe.read(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => terminal.append(&buf[..n]),
+            }
+        }
+        terminal.reader_done();
+    });
+}
+
+// ============================================================================
+// Terminal Manager
+// ============================================================================
+
+/// Runs commands for an agent and implements the ACP `terminal/*` methods.
+///
+/// Terminals are identified by IDs like `term-1`. Running processes are
+/// killed when their terminal is released or the manager is dropped.
+#[derive(Debug, Default)]
+pub struct TerminalManager {
+    terminals: Mutex<HashMap<String, Arc<Terminal>>>,
+    next_id: AtomicU64,
+}
+
+impl TerminalManager {
+    /// Create a manager with no terminals.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Dispatch a `terminal/*` JSON-RPC request and return its result.
+    ///
+    /// `terminal/wait_for_exit` blocks until the command exits.
+    pub fn handle(&self, method: &str, params: &Value) -> Result<Value, TerminalError> {
+        let terminal_id = || {
+            params
+                .get("terminalId")
+                .and_then(Value::as_str)
+                .ok_or_else(|| {
+                    TerminalError::InvalidParams(serde::de::Error::missing_field("terminalId"))
+                })
+        };
+        match method {
+            "terminal/create" => {
+                let request = CreateTerminalRequest::deserialize(params)
+                    .map_err(TerminalError::InvalidParams)?;
+                Ok(json!({"terminalId": self.create(&request)?}))
+            }
+            "terminal/output" => Ok(to_value(self.output(terminal_id()?)?)),
+            "terminal/wait_for_exit" => Ok(to_value(self.wait_for_exit(terminal_id()?)?)),
+            "terminal/kill" => self.kill(terminal_id()?).map(|()| json!({})),
+            "terminal/release" => self.release(terminal_id()?).map(|()| json!({})),
+            _ => Err(TerminalError::UnknownMethod(method.to_string())),
+        }
+    }
+
+    /// Start a command and return its terminal ID.
+    pub fn create(&self, request: &CreateTerminalRequest) -> Result<String, TerminalError> {
+        let mut command = Command::new(&request.command);
+        command
+            .args(&request.args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        for var in &request.env {
+            command.env(&var.name, &var.value);
+        }
+        if let Some(cwd) = &request.cwd {
+            command.current_dir(cwd);
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            // Own process group, so kill reaches the command's children too
+            command.process_group(0);
+        }
+        let mut child = command.spawn().map_err(|source| TerminalError::Spawn {
+            command: request.command.clone(),
+            source,
+        })?;
+
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+        let terminal = Arc::new(Terminal {
+            pid: child.id(),
+            child: Mutex::new(child),
+            state: Mutex::new(TerminalState {
+                open_readers: usize::from(stdout.is_some()) + usize::from(stderr.is_some()),
+                ..TerminalState::default()
+            }),
+            exited: Condvar::new(),
+            drained: Condvar::new(),
+            byte_limit: request
+                .output_byte_limit
+                .map(|limit| usize::try_from(limit).unwrap_or(usize::MAX)),
+        });
+        if let Some(stdout) = stdout {
+            spawn_reader(Arc::clone(&terminal), stdout);
+        }
+        if let Some(stderr) = stderr {
+            spawn_reader(Arc::clone(&terminal), stderr);
+        }
+        let watched = Arc::clone(&terminal);
+        thread::spawn(move || watched.watch());
+
+        let id = format!("term-{}", self.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+        self.terminals().insert(id.clone(), terminal);
+        Ok(id)
+    }
+
+    /// Output so far and, once exited, the exit status.
+    pub fn output(&self, terminal_id: &str) -> Result<TerminalOutput, TerminalError> {
+        Ok(self.get(terminal_id)?.output())
+    }
+
+    /// Block until the command exits.
+    pub fn wait_for_exit(&self, terminal_id: &str) -> Result<TerminalExitStatus, TerminalError> {
+        // Don't hold the map lock while waiting
+        let terminal = self.get(terminal_id)?;
+        Ok(terminal.wait_for_exit())
+    }
+
+    /// Kill the command. The terminal stays valid for `output` and
+    /// `wait_for_exit` until released.
+    pub fn kill(&self, terminal_id: &str) -> Result<(), TerminalError> {
+        self.get(terminal_id)?.kill();
+        Ok(())
+    }
+
+    /// Kill the command if it is still running and forget the terminal.
+    pub fn release(&self, terminal_id: &str) -> Result<(), TerminalError> {
+        let terminal = self
+            .terminals()
+            .remove(terminal_id)
+            .ok_or_else(|| TerminalError::UnknownTerminal(terminal_id.to_string()))?;
+        terminal.kill();
+        Ok(())
+    }
+
+    /// IDs of terminals that have not been released, in creation order.
+    pub fn terminal_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.terminals().keys().cloned().collect();
+        ids.sort_by_key(|id| {
+            id.trim_start_matches("term-")
+                .parse::<u64>()
+                .unwrap_or(u64::MAX)
+        });
+        ids
+    }
+
+    fn get(&self, terminal_id: &str) -> Result<Arc<Terminal>, TerminalError> {
+        self.terminals()
+            .get(terminal_id)
+            .cloned()
+            .ok_or_else(|| TerminalError::UnknownTerminal(terminal_id.to_string()))
+    }
+
+    fn terminals(&self) -> MutexGuard<'_, HashMap<String, Arc<Terminal>>> {
+        self.terminals.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for TerminalManager {
+    fn drop(&mut self) {
+        for terminal in self.terminals().values() {
+            terminal.kill();
+        }
+    }
+}
+
+fn to_value(value: impl Serialize) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+// ============================================================================
+// Formatting
+// ============================================================================
+
+/// Format how a terminal's command ended, for display under its tool call.
+///
+/// Produces `  term-1 exited with code 0` (dimmed on success, red otherwise)
+/// or `  term-1 killed by SIGKILL` (yellow).
+///
+/// # Example
+///
+/// ```
+/// use clemitui::{TerminalExitStatus, format_terminal_exit};
+///
+/// colored::control::set_override(false);
+/// let status = TerminalExitStatus { exit_code: None, signal: Some("SIGKILL".into()) };
+/// assert_eq!(format_terminal_exit("term-1", &status), "  term-1 killed by SIGKILL\n");
+/// colored::control::unset_override();
+/// ```
+pub fn format_terminal_exit(terminal_id: &str, status: &TerminalExitStatus) -> String {
+    let line = match (&status.signal, status.exit_code) {
+        (Some(signal), _) => format!("{terminal_id} killed by {signal}").yellow(),
+        (None, Some(0)) => format!("{terminal_id} exited with code 0").dimmed(),
+        (None, Some(code)) => format!("{terminal_id} exited with code {code}").red(),
+        (None, None) => format!("{terminal_id} exited").dimmed(),
+    };
+    format!("  {}\n", line)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn sh(script: &str) -> CreateTerminalRequest {
+        CreateTerminalRequest::new("sh").with_args(["-c", script])
+    }
+
+    #[test]
+    fn test_output_and_exit_status() {
+        let manager = TerminalManager::new();
+        let id = manager
+            .create(&sh("echo out; echo err >&2; exit 3"))
+            .unwrap();
+        let status = manager.wait_for_exit(&id).unwrap();
+        assert_eq!(status.exit_code, Some(3));
+        assert_eq!(status.signal, None);
+
+        let output = manager.output(&id).unwrap();
+        assert!(output.output.contains("out\n"));
+        assert!(output.output.contains("err\n"));
+        assert!(!output.truncated);
+        assert_eq!(output.exit_status, Some(status));
+    }
+
+    #[test]
+    fn test_output_byte_limit_truncates_from_start() {
+        let manager = TerminalManager::new();
+        let id = manager
+            .create(&sh("printf '0123456789é'").with_output_byte_limit(4))
+            .unwrap();
+        manager.wait_for_exit(&id).unwrap();
+        let output = manager.output(&id).unwrap();
+        assert!(output.truncated);
+        assert_eq!(output.output, "89é");
+
+        let id = manager
+            .create(&sh("printf 'aéb'").with_output_byte_limit(2))
+            .unwrap();
+        manager.wait_for_exit(&id).unwrap();
+        // The last 2 bytes start inside "é", which is dropped whole
+        assert_eq!(manager.output(&id).unwrap().output, "b");
+    }
+
+    #[test]
+    fn test_env_and_cwd() {
+        let manager = TerminalManager::new();
+        let dir = std::env::temp_dir();
+        let id = manager
+            .create(
+                &sh("echo $GREETING; pwd")
+                    .with_env("GREETING", "hi")
+                    .with_cwd(&dir),
+            )
+            .unwrap();
+        manager.wait_for_exit(&id).unwrap();
+        let output = manager.output(&id).unwrap().output;
+        assert!(output.starts_with("hi\n"), "got: {output:?}");
+        let pwd = PathBuf::from(output.lines().nth(1).unwrap());
+        assert_eq!(pwd.canonicalize().unwrap(), dir.canonicalize().unwrap());
+    }
+
+    #[test]
+    fn test_kill_and_release() {
+        let manager = TerminalManager::new();
+        let id = manager.create(&sh("exec sleep 30")).unwrap();
+        assert_eq!(manager.output(&id).unwrap().exit_status, None);
+        manager.kill(&id).unwrap();
+        let status = manager.wait_for_exit(&id).unwrap();
+        assert_eq!(status.signal.as_deref(), Some("SIGKILL"));
+        assert!(!status.success());
+
+        assert_eq!(manager.terminal_ids(), vec![id.clone()]);
+        manager.release(&id).unwrap();
+        assert!(manager.terminal_ids().is_empty());
+        assert!(matches!(
+            manager.output(&id),
+            Err(TerminalError::UnknownTerminal(_))
+        ));
+    }
+
+    #[test]
+    fn test_kill_reaches_child_processes() {
+        let manager = TerminalManager::new();
+        // Not `exec`: the shell waits on a child that holds the output pipes
+        let id = manager.create(&sh("sleep 30; echo done")).unwrap();
+        thread::sleep(Duration::from_millis(100));
+        let started = Instant::now();
+        manager.kill(&id).unwrap();
+        let status = manager.wait_for_exit(&id).unwrap();
+        assert_eq!(status.signal.as_deref(), Some("SIGKILL"));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(!manager.output(&id).unwrap().output.contains("done"));
+    }
+
+    #[test]
+    fn test_exit_status_not_held_by_background_processes() {
+        let manager = TerminalManager::new();
+        let id = manager.create(&sh("sleep 30 & echo hi")).unwrap();
+        let started = Instant::now();
+        let status = manager.wait_for_exit(&id).unwrap();
+        assert_eq!(status.exit_code, Some(0));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(manager.output(&id).unwrap().output, "hi\n");
+
+        // The leader is reaped, so release closes the pipes the leftover
+        // `sleep` holds instead of signalling a group ID that may be reused
+        let terminal = manager.get(&id).unwrap();
+        manager.release(&id).unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while terminal.state().open_readers > 0 {
+            assert!(Instant::now() < deadline, "readers still open");
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_handle_json_rpc() {
+        let manager = TerminalManager::new();
+        let created = manager
+            .handle(
+                "terminal/create",
+                &json!({"sessionId": "s", "command": "sh", "args": ["-c", "echo hi"], "outputByteLimit": 100}),
+            )
+            .unwrap();
+        let id = created["terminalId"].as_str().unwrap();
+        let params = json!({"sessionId": "s", "terminalId": id});
+
+        let exit = manager.handle("terminal/wait_for_exit", &params).unwrap();
+        assert_eq!(exit, json!({"exitCode": 0, "signal": null}));
+        let output = manager.handle("terminal/output", &params).unwrap();
+        assert_eq!(
+            output,
+            json!({"output": "hi\n", "truncated": false, "exitStatus": {"exitCode": 0, "signal": null}})
+        );
+        assert_eq!(
+            manager.handle("terminal/release", &params).unwrap(),
+            json!({})
+        );
+
+        let err = manager.handle("terminal/kill", &params).unwrap_err();
+        assert_eq!(err.code(), -32602);
+        let err = manager.handle("terminal/output", &json!({})).unwrap_err();
+        assert!(err.to_string().contains("terminalId"));
+        let err = manager.handle("terminal/resize", &params).unwrap_err();
+        assert_eq!(err.code(), -32601);
+    }
+
+    #[test]
+    fn test_spawn_error() {
+        let manager = TerminalManager::new();
+        let err = manager
+            .create(&CreateTerminalRequest::new("/nonexistent/command"))
+            .unwrap_err();
+        assert!(matches!(err, TerminalError::Spawn { .. }));
+        assert_eq!(err.code(), -32603);
+    }
+
+    #[test]
+    fn test_format_terminal_exit() {
+        colored::control::set_override(false);
+        let exited = |code| TerminalExitStatus {
+            exit_code: Some(code),
+            signal: None,
+        };
+        assert_eq!(
+            format_terminal_exit("term-2", &exited(0)),
+            "  term-2 exited with code 0\n"
+        );
+        assert_eq!(
+            format_terminal_exit("term-2", &exited(101)),
+            "  term-2 exited with code 101\n"
+        );
+        // Windows reports NTSTATUS failures as negative codes
+        assert_eq!(
+            format_terminal_exit("term-2", &exited(-1073741819)),
+            "  term-2 exited with code -1073741819\n"
+        );
+        colored::control::unset_override();
+    }
+}
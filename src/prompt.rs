@@ -0,0 +1,1422 @@
+//! Line editor for user prompts.
+//!
+//! [`LineEditor`] reads a prompt from the terminal in raw mode with emacs
+//! keybindings, multiline editing, bracketed paste and persistent history
+//! with reverse search. Editing logic lives in [`PromptEditor`], a pure state
+//! machine driven by key events, so it can be tested without a terminal.
+//!
+//! # Keybindings
+//!
+//! | Keys | Action |
+//! |------|--------|
+//! | `Enter` | Submit (or continue the line if it ends with `\`) |
+//! | `Shift+Enter`, `Alt+Enter`, `Ctrl+J` | Insert a newline |
+//! | `Ctrl+A` / `Ctrl+E` | Start / end of line |
+//! | `Ctrl+B` / `Ctrl+F`, arrows | Move one character |
+//! | `Alt+B` / `Alt+F`, `Ctrl+←/→` | Move one word |
+//! | `Ctrl+K` / `Ctrl+U` | Kill to end / start of line |
+//! | `Ctrl+W` / `Alt+D` | Kill previous / next word |
+//! | `Ctrl+Y` | Yank last kill |
+//! | `Ctrl+P` / `Ctrl+N`, `↑/↓` | Previous / next history entry |
+//! | `Ctrl+R` | Reverse history search |
+//! | `Ctrl+L` | Clear screen |
+//! | `Ctrl+C` | Cancel the current line |
+//! | `Ctrl+D` | Delete character, or end of input on an empty line |
+//! | `Tab` | Complete (when a [`Completer`] is set), else indent with spaces |
+
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock, RwLock};
+
+use colored::Colorize;
+
+use crossterm::event::{
+    self, DisableBracketedPaste, EnableBracketedPaste, Event, KeyCode, KeyEvent, KeyEventKind,
+    KeyModifiers, KeyboardEnhancementFlags, PopKeyboardEnhancementFlags,
+    PushKeyboardEnhancementFlags,
+};
+use crossterm::{execute, terminal};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::width::{display_width, wrapped_rows};
+
+// ============================================================================
+// History
+// ============================================================================
+
+/// Default number of entries kept in history.
+const DEFAULT_HISTORY_SIZE: usize = 1000;
+
+/// Columns between the tab stops Tab moves to when nothing completes.
+const TAB_WIDTH: usize = 4;
+
+/// Prompt history, optionally persisted to a file.
+///
+/// The file stores one entry per line; newlines and backslashes inside
+/// entries are escaped as `\n` and `\\` so multiline prompts round-trip.
+#[derive(Debug, Clone)]
+pub struct History {
+    entries: Vec<String>,
+    max_entries: usize,
+    path: Option<PathBuf>,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_SIZE)
+    }
+}
+
+impl History {
+    /// Create an in-memory history keeping at most `max_entries` entries.
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            entries: Vec::new(),
+            max_entries,
+            path: None,
+        }
+    }
+
+    /// Load history from `path`, which is also where [`Self::save`] writes.
+    ///
+    /// A missing file yields an empty history.
+    pub fn load(path: impl AsRef<Path>, max_entries: usize) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut history = Self::new(max_entries);
+        match std::fs::read_to_string(&path) {
+            Ok(text) => {
+                for line in text.lines() {
+                    history.push(&unescape_entry(line));
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        history.path = Some(path);
+        Ok(history)
+    }
+
+    /// Write history to the file it was loaded from (no-op for in-memory history).
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut text = String::new();
+        for entry in &self.entries {
+            text.push_str(&escape_entry(entry));
+            text.push('\n');
+        }
+        std::fs::write(path, text)
+    }
+
+    /// Add an entry, skipping blank entries and repeats of the latest entry.
+    pub fn push(&mut self, entry: &str) {
+        if entry.trim().is_empty() || self.entries.last().is_some_and(|last| last == entry) {
+            return;
+        }
+        self.entries.push(entry.to_string());
+        if self.entries.len() > self.max_entries {
+            let excess = self.entries.len() - self.max_entries;
+            self.entries.drain(..excess);
+        }
+    }
+
+    /// All entries, oldest first.
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if history has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Index of the newest entry before `before` that contains `query`.
+    pub fn search_backward(&self, query: &str, before: usize) -> Option<usize> {
+        self.entries[..before.min(self.entries.len())]
+            .iter()
+            .rposition(|entry| entry.contains(query))
+    }
+}
+
+fn escape_entry(entry: &str) -> String {
+    entry.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape_entry(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') => out.push('\n'),
+                Some(other) => out.push(other),
+                None => out.push('\\'),
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+// ============================================================================
+// Completion
+// ============================================================================
+
+/// A completion candidate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    /// Text that replaces the completed span of the line.
+    pub replacement: String,
+    /// Optional description shown in the completion popup.
+    pub description: Option<String>,
+}
+
+/// Source of tab completions and inline hints for the prompt.
+///
+/// See [`CommandRegistry`](crate::CommandRegistry) for slash commands.
+pub trait Completer {
+    /// Candidates for the text before `cursor`, plus the byte offset where the
+    /// replaced span starts. An empty list means nothing to complete.
+    fn complete(&self, line: &str, cursor: usize) -> (usize, Vec<Completion>);
+
+    /// Ghost text shown (dimmed) after the cursor, if any.
+    fn hint(&self, _line: &str, _cursor: usize) -> Option<String> {
+        None
+    }
+}
+
+impl<T: Completer + ?Sized> Completer for Arc<T> {
+    fn complete(&self, line: &str, cursor: usize) -> (usize, Vec<Completion>) {
+        (**self).complete(line, cursor)
+    }
+
+    fn hint(&self, line: &str, cursor: usize) -> Option<String> {
+        (**self).hint(line, cursor)
+    }
+}
+
+/// Lets a completer be updated while a [`LineEditor`] holds it
+/// (e.g. `Arc<RwLock<CommandRegistry>>`).
+impl<T: Completer> Completer for RwLock<T> {
+    fn complete(&self, line: &str, cursor: usize) -> (usize, Vec<Completion>) {
+        self.read()
+            .map_or((cursor, Vec::new()), |inner| inner.complete(line, cursor))
+    }
+
+    fn hint(&self, line: &str, cursor: usize) -> Option<String> {
+        self.read().ok().and_then(|inner| inner.hint(line, cursor))
+    }
+}
+
+/// Longest common prefix of all candidates' replacements.
+fn common_prefix(candidates: &[Completion]) -> &str {
+    let Some(first) = candidates.first() else {
+        return "";
+    };
+    let mut len = first.replacement.len();
+    for candidate in &candidates[1..] {
+        len = first.replacement[..len]
+            .char_indices()
+            .zip(candidate.replacement.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(len.min(candidate.replacement.len()), |((i, _), _)| i);
+    }
+    &first.replacement[..len]
+}
+
+// ============================================================================
+// Editor State Machine
+// ============================================================================
+
+/// Result of feeding an event to a [`PromptEditor`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PromptAction {
+    /// Keep editing.
+    Continue,
+    /// The user submitted this text.
+    Submit(String),
+    /// The user cancelled the current line (`Ctrl+C`).
+    Cancel,
+    /// The user ended input (`Ctrl+D` on an empty line).
+    Eof,
+    /// The user asked to clear the screen (`Ctrl+L`).
+    ClearScreen,
+}
+
+/// Reverse incremental search state.
+#[derive(Debug, Clone)]
+struct SearchState {
+    query: String,
+    match_index: Option<usize>,
+    original: (String, usize),
+}
+
+/// Editing state for a prompt: buffer, cursor, kill buffer, history navigation.
+///
+/// # Example
+///
+/// ```
+/// use clemitui::{PromptAction, PromptEditor};
+/// use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+///
+/// let mut editor = PromptEditor::new();
+/// editor.insert_str("hello");
+/// editor.handle_key(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL));
+/// editor.insert_str("> ");
+/// let action = editor.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+/// assert_eq!(action, PromptAction::Submit("> hello".to_string()));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PromptEditor {
+    buffer: String,
+    /// Byte offset into `buffer`, always on a char boundary.
+    cursor: usize,
+    kill_buffer: String,
+    history: History,
+    history_index: Option<usize>,
+    saved_line: String,
+    search: Option<SearchState>,
+}
+
+impl PromptEditor {
+    /// Create an editor with empty in-memory history.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an editor using the given history.
+    pub fn with_history(history: History) -> Self {
+        Self {
+            history,
+            ..Self::default()
+        }
+    }
+
+    /// Current buffer contents.
+    pub fn buffer(&self) -> &str {
+        &self.buffer
+    }
+
+    /// Cursor position as a byte offset into [`Self::buffer`].
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// The editor's history.
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    /// Mutable access to the editor's history.
+    pub fn history_mut(&mut self) -> &mut History {
+        &mut self.history
+    }
+
+    /// Reverse search query and current match, while searching.
+    pub fn search(&self) -> Option<(&str, Option<&str>)> {
+        self.search.as_ref().map(|search| {
+            let matched = search
+                .match_index
+                .map(|i| self.history.entries()[i].as_str());
+            (search.query.as_str(), matched)
+        })
+    }
+
+    /// Replace the buffer, moving the cursor to the end.
+    pub fn set_buffer(&mut self, text: &str) {
+        self.buffer = text.to_string();
+        self.cursor = self.buffer.len();
+    }
+
+    /// Clear the buffer and reset history navigation.
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+        self.cursor = 0;
+        self.history_index = None;
+        self.search = None;
+    }
+
+    /// Insert text at the cursor (used for typing and bracketed paste).
+    pub fn insert_str(&mut self, text: &str) {
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+        self.buffer.insert_str(self.cursor, &text);
+        self.cursor += text.len();
+    }
+
+    /// Complete the text before the cursor using `completer`.
+    ///
+    /// A single candidate is inserted followed by a space; several candidates
+    /// extend the text to their longest common prefix. Returns the candidates.
+    pub fn complete_with(&mut self, completer: &dyn Completer) -> Vec<Completion> {
+        let (start, candidates) = completer.complete(&self.buffer, self.cursor);
+        if start > self.cursor || !self.buffer.is_char_boundary(start) {
+            return candidates;
+        }
+        let replacement = match candidates.as_slice() {
+            [] => return candidates,
+            [only] => format!("{} ", only.replacement),
+            _ => common_prefix(&candidates).to_string(),
+        };
+        if replacement.len() >= self.cursor - start {
+            self.buffer.replace_range(start..self.cursor, &replacement);
+            self.cursor = start + replacement.len();
+        }
+        candidates
+    }
+
+    /// Handle a terminal event. Non-key, non-paste events are ignored.
+    pub fn handle_event(&mut self, event: &Event) -> PromptAction {
+        match event {
+            Event::Key(key) => self.handle_key(*key),
+            Event::Paste(text) => {
+                if self.search.is_some() {
+                    self.accept_search();
+                }
+                self.insert_str(text);
+                PromptAction::Continue
+            }
+            _ => PromptAction::Continue,
+        }
+    }
+
+    /// Handle a key press.
+    pub fn handle_key(&mut self, key: KeyEvent) -> PromptAction {
+        if key.kind == KeyEventKind::Release {
+            return PromptAction::Continue;
+        }
+        if self.search.is_some()
+            && let Some(action) = self.handle_search_key(key)
+        {
+            return action;
+        }
+
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+
+        match key.code {
+            KeyCode::Enter if shift || alt => self.insert_str("\n"),
+            KeyCode::Enter => return self.submit(),
+            KeyCode::Char('j') if ctrl => self.insert_str("\n"),
+            KeyCode::Char('c') if ctrl => {
+                self.clear();
+                return PromptAction::Cancel;
+            }
+            KeyCode::Char('d') if ctrl => {
+                if self.buffer.is_empty() {
+                    return PromptAction::Eof;
+                }
+                self.delete_forward();
+            }
+            KeyCode::Char('l') if ctrl => return PromptAction::ClearScreen,
+            KeyCode::Char('a') if ctrl => self.cursor = self.line_start(),
+            KeyCode::Char('e') if ctrl => self.cursor = self.line_end(),
+            KeyCode::Char('b') if ctrl => self.move_left(),
+            KeyCode::Char('f') if ctrl => self.move_right(),
+            KeyCode::Char('h') if ctrl => self.delete_backward(),
+            KeyCode::Char('k') if ctrl => {
+                let end = self.line_end();
+                // At end of a line, kill the newline to join with the next
+                let end = if end == self.cursor && end < self.buffer.len() {
+                    end + 1
+                } else {
+                    end
+                };
+                self.kill(self.cursor, end);
+            }
+            KeyCode::Char('u') if ctrl => self.kill(self.line_start(), self.cursor),
+            KeyCode::Char('w') if ctrl => self.kill(self.word_start_before(), self.cursor),
+            KeyCode::Char('y') if ctrl => {
+                let yanked = self.kill_buffer.clone();
+                self.insert_str(&yanked);
+            }
+            KeyCode::Char('p') if ctrl => self.history_prev(),
+            KeyCode::Char('n') if ctrl => self.history_next(),
+            KeyCode::Char('r') if ctrl => self.start_search(),
+            KeyCode::Char('b') if alt => self.cursor = self.word_start_before(),
+            KeyCode::Char('f') if alt => self.cursor = self.word_end_after(),
+            KeyCode::Char('d') if alt => self.kill(self.cursor, self.word_end_after()),
+            KeyCode::Char(c) if !ctrl && !alt => {
+                let mut buf = [0; 4];
+                self.insert_str(c.encode_utf8(&mut buf));
+            }
+            KeyCode::Tab => {
+                // Spaces rather than `\t`, whose width depends on the terminal
+                let column = display_width(&self.buffer[self.line_start()..self.cursor]);
+                self.insert_str(&" ".repeat(TAB_WIDTH - column % TAB_WIDTH));
+            }
+            KeyCode::Backspace if alt => self.kill(self.word_start_before(), self.cursor),
+            KeyCode::Backspace => self.delete_backward(),
+            KeyCode::Delete => self.delete_forward(),
+            KeyCode::Left if ctrl || alt => self.cursor = self.word_start_before(),
+            KeyCode::Right if ctrl || alt => self.cursor = self.word_end_after(),
+            KeyCode::Left => self.move_left(),
+            KeyCode::Right => self.move_right(),
+            KeyCode::Home => self.cursor = self.line_start(),
+            KeyCode::End => self.cursor = self.line_end(),
+            KeyCode::Up => {
+                if self.line_start() > 0 {
+                    self.move_vertical(-1);
+                } else {
+                    self.history_prev();
+                }
+            }
+            KeyCode::Down => {
+                if self.line_end() < self.buffer.len() {
+                    self.move_vertical(1);
+                } else {
+                    self.history_next();
+                }
+            }
+            _ => {}
+        }
+        PromptAction::Continue
+    }
+
+    fn submit(&mut self) -> PromptAction {
+        // A trailing backslash continues the prompt on a new line
+        if self.cursor == self.buffer.len() && self.buffer.ends_with('\\') {
+            self.buffer.pop();
+            self.cursor -= 1;
+            self.insert_str("\n");
+            return PromptAction::Continue;
+        }
+        let text = std::mem::take(&mut self.buffer);
+        self.history.push(&text);
+        self.clear();
+        PromptAction::Submit(text)
+    }
+
+    // ---- cursor movement ----
+
+    fn prev_boundary(&self, pos: usize) -> usize {
+        self.buffer[..pos]
+            .grapheme_indices(true)
+            .next_back()
+            .map_or(0, |(i, _)| i)
+    }
+
+    fn next_boundary(&self, pos: usize) -> usize {
+        self.buffer[pos..]
+            .graphemes(true)
+            .next()
+            .map_or(pos, |g| pos + g.len())
+    }
+
+    fn move_left(&mut self) {
+        self.cursor = self.prev_boundary(self.cursor);
+    }
+
+    fn move_right(&mut self) {
+        self.cursor = self.next_boundary(self.cursor);
+    }
+
+    fn line_start(&self) -> usize {
+        self.buffer[..self.cursor].rfind('\n').map_or(0, |i| i + 1)
+    }
+
+    fn line_end(&self) -> usize {
+        self.buffer[self.cursor..]
+            .find('\n')
+            .map_or(self.buffer.len(), |i| self.cursor + i)
+    }
+
+    /// Move to the same column on the previous (`-1`) or next (`1`) line.
+    fn move_vertical(&mut self, direction: i32) {
+        let column = display_width(&self.buffer[self.line_start()..self.cursor]);
+        let target_start = if direction < 0 {
+            let prev_end = self.line_start() - 1;
+            self.buffer[..prev_end].rfind('\n').map_or(0, |i| i + 1)
+        } else {
+            self.line_end() + 1
+        };
+        let target_end = self.buffer[target_start..]
+            .find('\n')
+            .map_or(self.buffer.len(), |i| target_start + i);
+        let line = &self.buffer[target_start..target_end];
+        let mut width = 0;
+        self.cursor = target_start
+            + line
+                .grapheme_indices(true)
+                .find(|(_, g)| {
+                    width += display_width(g);
+                    width > column
+                })
+                .map_or(line.len(), |(i, _)| i);
+    }
+
+    fn word_start_before(&self) -> usize {
+        let before = &self.buffer[..self.cursor];
+        let trimmed = before.trim_end_matches(|c: char| !c.is_alphanumeric());
+        trimmed
+            .rfind(|c: char| !c.is_alphanumeric())
+            .map_or(0, |i| {
+                i + trimmed[i..].chars().next().map_or(1, char::len_utf8)
+            })
+    }
+
+    fn word_end_after(&self) -> usize {
+        let after = &self.buffer[self.cursor..];
+        let skipped = after.len()
+            - after
+                .trim_start_matches(|c: char| !c.is_alphanumeric())
+                .len();
+        let rest = &after[skipped..];
+        let word_len = rest
+            .find(|c: char| !c.is_alphanumeric())
+            .unwrap_or(rest.len());
+        self.cursor + skipped + word_len
+    }
+
+    // ---- deletion ----
+
+    fn delete_backward(&mut self) {
+        if self.cursor > 0 {
+            let start = self.prev_boundary(self.cursor);
+            self.buffer.drain(start..self.cursor);
+            self.cursor = start;
+        }
+    }
+
+    fn delete_forward(&mut self) {
+        let end = self.next_boundary(self.cursor);
+        self.buffer.drain(self.cursor..end);
+    }
+
+    fn kill(&mut self, start: usize, end: usize) {
+        if start < end {
+            self.kill_buffer = self.buffer.drain(start..end).collect();
+            self.cursor = start;
+        }
+    }
+
+    // ---- history ----
+
+    fn history_prev(&mut self) {
+        let index = match self.history_index {
+            None if self.history.is_empty() => return,
+            None => {
+                self.saved_line = self.buffer.clone();
+                self.history.len() - 1
+            }
+            Some(0) => return,
+            Some(i) => i - 1,
+        };
+        self.history_index = Some(index);
+        self.set_buffer(&self.history.entries()[index].clone());
+    }
+
+    fn history_next(&mut self) {
+        let Some(index) = self.history_index else {
+            return;
+        };
+        if index + 1 < self.history.len() {
+            self.history_index = Some(index + 1);
+            self.set_buffer(&self.history.entries()[index + 1].clone());
+        } else {
+            self.history_index = None;
+            let saved = std::mem::take(&mut self.saved_line);
+            self.set_buffer(&saved);
+        }
+    }
+
+    // ---- reverse search ----
+
+    fn start_search(&mut self) {
+        self.search = Some(SearchState {
+            query: String::new(),
+            match_index: None,
+            original: (self.buffer.clone(), self.cursor),
+        });
+    }
+
+    /// Re-run the search for the current query, looking before `before`.
+    fn update_search(&mut self, before: usize) {
+        if let Some(search) = &mut self.search {
+            search.match_index = if search.query.is_empty() {
+                None
+            } else {
+                self.history
+                    .search_backward(&search.query, before)
+                    .or(search.match_index)
+            };
+        }
+    }
+
+    fn accept_search(&mut self) {
+        if let Some(search) = self.search.take()
+            && let Some(index) = search.match_index
+        {
+            self.set_buffer(&self.history.entries()[index].clone());
+        }
+    }
+
+    /// Handle a key during reverse search. Returns None when the key should
+    /// fall through to normal editing after accepting the match.
+    fn handle_search_key(&mut self, key: KeyEvent) -> Option<PromptAction> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let search = self.search.as_mut()?;
+        match key.code {
+            KeyCode::Char('r') if ctrl => {
+                let before = search.match_index.unwrap_or(self.history.len());
+                self.update_search(before);
+            }
+            KeyCode::Char('g') | KeyCode::Char('c') if ctrl => {
+                let (buffer, cursor) = self.search.take()?.original;
+                self.buffer = buffer;
+                self.cursor = cursor;
+            }
+            KeyCode::Esc => {
+                let (buffer, cursor) = self.search.take()?.original;
+                self.buffer = buffer;
+                self.cursor = cursor;
+            }
+            KeyCode::Backspace => {
+                search.query.pop();
+                self.update_search(self.history.len());
+            }
+            KeyCode::Char(c) if !ctrl && !key.modifiers.contains(KeyModifiers::ALT) => {
+                search.query.push(c);
+                self.update_search(self.history.len());
+            }
+            KeyCode::Enter => {
+                self.accept_search();
+                return Some(self.submit());
+            }
+            _ => {
+                self.accept_search();
+                return None;
+            }
+        }
+        Some(PromptAction::Continue)
+    }
+}
+
+// ============================================================================
+// Rendering
+// ============================================================================
+
+/// Lines to display for the editor, plus the cursor's (row, column).
+///
+/// Rows count wrapped terminal rows from the top of the prompt. With a
+/// completer, its hint is shown as ghost text after the cursor and matching
+/// candidates are listed below the prompt.
+pub(crate) fn layout(
+    editor: &PromptEditor,
+    prompt: &str,
+    continuation: &str,
+    completer: Option<&dyn Completer>,
+    cols: usize,
+) -> (Vec<String>, (usize, usize)) {
+    if let Some((query, matched)) = editor.search() {
+        let line = format!("(reverse-i-search)`{}': {}", query, matched.unwrap_or(""));
+        let line = line.replace('\n', "⏎");
+        let width = display_width(&line);
+        return (vec![line], (width / cols.max(1), width % cols.max(1)));
+    }
+
+    let buffer = editor.buffer();
+    let before_cursor = &buffer[..editor.cursor()];
+    let cursor_line = before_cursor.matches('\n').count();
+    let cursor_col_text = before_cursor.rsplit('\n').next().unwrap_or("");
+
+    let mut lines = Vec::new();
+    let mut row = 0;
+    let mut cursor = (0, 0);
+    for (i, line) in buffer.split('\n').enumerate() {
+        let prefix = if i == 0 { prompt } else { continuation };
+        let rendered = format!("{}{}", prefix, line);
+        if i == cursor_line {
+            let col = display_width(prefix) + display_width(cursor_col_text);
+            cursor = (row + col / cols.max(1), col % cols.max(1));
+        }
+        row += wrapped_rows(&rendered, cols);
+        lines.push(rendered);
+    }
+
+    if let Some(completer) = completer {
+        let at_end = editor.cursor() == buffer.len();
+        if let Some(hint) = completer.hint(buffer, editor.cursor()).filter(|_| at_end)
+            && let Some(last) = lines.last_mut()
+        {
+            last.push_str(&hint.dimmed().to_string());
+        }
+        let (_, candidates) = completer.complete(buffer, editor.cursor());
+        let exact = candidates.len() == 1 && candidates[0].replacement == buffer;
+        if !exact {
+            for candidate in candidates {
+                let description = candidate.description.unwrap_or_default();
+                lines.push(format!(
+                    "  {}  {}",
+                    candidate.replacement.cyan(),
+                    description.dimmed()
+                ));
+            }
+        }
+    }
+    (lines, cursor)
+}
+
+/// Escape sequence that redraws `lines` in place of the previous render.
+///
+/// `prev_cursor_row` is the cursor's row within the previous render, used to
+/// move back to its top. The returned sequence leaves the cursor at `cursor`.
+fn redraw_sequence(
+    prev_cursor_row: usize,
+    lines: &[String],
+    cursor: (usize, usize),
+    cols: usize,
+) -> String {
+    let mut out = String::from("\r");
+    if prev_cursor_row > 0 {
+        out.push_str(&format!("\x1b[{}A", prev_cursor_row));
+    }
+    out.push_str("\x1b[J");
+    out.push_str(&lines.join("\r\n"));
+
+    let last_row: usize = lines
+        .iter()
+        .map(|line| wrapped_rows(line, cols))
+        .sum::<usize>()
+        - 1;
+    if last_row > cursor.0 {
+        out.push_str(&format!("\x1b[{}A", last_row - cursor.0));
+    }
+    out.push('\r');
+    if cursor.1 > 0 {
+        out.push_str(&format!("\x1b[{}C", cursor.1));
+    }
+    out
+}
+
+// ============================================================================
+// Terminal Line Editor
+// ============================================================================
+
+/// Outcome of [`LineEditor::read_line`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReadResult {
+    /// The user submitted a prompt.
+    Line(String),
+    /// The user pressed `Ctrl+C`.
+    Interrupted,
+    /// The user pressed `Ctrl+D` on an empty line.
+    Eof,
+}
+
+/// Whether the terminal supports the kitty keyboard protocol (queried once).
+///
+/// The query waits for a reply, so repeating it for every prompt would add
+/// latency on terminals that never answer.
+static KEYBOARD_ENHANCEMENT: OnceLock<bool> = OnceLock::new();
+
+/// Restores cooked mode and terminal modes when dropped (including on panic).
+struct RawModeGuard {
+    enhanced_keys: bool,
+}
+
+impl RawModeGuard {
+    fn enable() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        let mut stdout = io::stdout();
+        execute!(stdout, EnableBracketedPaste)?;
+        let enhanced_keys = *KEYBOARD_ENHANCEMENT
+            .get_or_init(|| terminal::supports_keyboard_enhancement().unwrap_or(false));
+        if enhanced_keys {
+            execute!(
+                stdout,
+                PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
+            )?;
+        }
+        Ok(Self { enhanced_keys })
+    }
+}
+
+impl Drop for RawModeGuard {
+    fn drop(&mut self) {
+        let mut stdout = io::stdout();
+        if self.enhanced_keys {
+            let _ = execute!(stdout, PopKeyboardEnhancementFlags);
+        }
+        let _ = execute!(stdout, DisableBracketedPaste);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Interactive prompt reader built on [`PromptEditor`].
+///
+/// # Example
+///
+/// ```no_run
+/// use clemitui::{LineEditor, ReadResult};
+///
+/// let mut editor = LineEditor::new("> ").with_history_file("/tmp/history")?;
+/// loop {
+///     match editor.read_line()? {
+///         ReadResult::Line(prompt) => println!("you said: {prompt}"),
+///         ReadResult::Interrupted => continue,
+///         ReadResult::Eof => break,
+///     }
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct LineEditor {
+    editor: PromptEditor,
+    prompt: String,
+    continuation: String,
+    completer: Option<Box<dyn Completer>>,
+}
+
+impl LineEditor {
+    /// Create a line editor with the given prompt and in-memory history.
+    pub fn new(prompt: impl Into<String>) -> Self {
+        Self {
+            editor: PromptEditor::new(),
+            prompt: prompt.into(),
+            continuation: "… ".to_string(),
+            completer: None,
+        }
+    }
+
+    /// Load history from (and save it to) `path`.
+    pub fn with_history_file(mut self, path: impl AsRef<Path>) -> io::Result<Self> {
+        let history = History::load(path, DEFAULT_HISTORY_SIZE)?;
+        self.editor = PromptEditor::with_history(history);
+        Ok(self)
+    }
+
+    /// Set the prefix shown on continuation lines of a multiline prompt.
+    pub fn with_continuation(mut self, continuation: impl Into<String>) -> Self {
+        self.continuation = continuation.into();
+        self
+    }
+
+    /// Use `completer` for tab completion, ghost hints and the candidate popup.
+    pub fn with_completer(mut self, completer: impl Completer + 'static) -> Self {
+        self.completer = Some(Box::new(completer));
+        self
+    }
+
+    /// Set the prompt shown on the first line.
+    pub fn set_prompt(&mut self, prompt: impl Into<String>) {
+        self.prompt = prompt.into();
+    }
+
+    /// The underlying editor state.
+    pub fn editor(&self) -> &PromptEditor {
+        &self.editor
+    }
+
+    /// Read one prompt from the terminal.
+    ///
+    /// Submitted prompts are added to history and saved to the history file.
+    pub fn read_line(&mut self) -> io::Result<ReadResult> {
+        let _guard = RawModeGuard::enable()?;
+        let mut stdout = io::stdout();
+        let mut cursor_row = 0;
+        self.render(&mut stdout, &mut cursor_row)?;
+
+        loop {
+            let event = event::read()?;
+            let action = match (&event, &self.completer) {
+                (Event::Key(key), Some(completer))
+                    if key.code == KeyCode::Tab
+                        && key.modifiers.is_empty()
+                        && key.kind != KeyEventKind::Release
+                        && self.editor.search().is_none() =>
+                {
+                    self.editor.complete_with(completer.as_ref());
+                    PromptAction::Continue
+                }
+                _ => self.editor.handle_event(&event),
+            };
+            match action {
+                PromptAction::Continue => self.render(&mut stdout, &mut cursor_row)?,
+                PromptAction::ClearScreen => {
+                    write!(stdout, "\x1b[2J\x1b[H")?;
+                    cursor_row = 0;
+                    self.render(&mut stdout, &mut cursor_row)?;
+                }
+                PromptAction::Submit(text) => {
+                    // Show the submitted text (not the search UI), cursor at the end
+                    let mut submitted = PromptEditor::new();
+                    submitted.set_buffer(&text);
+                    self.render_editor(&submitted, &mut stdout, &mut cursor_row)?;
+                    write!(stdout, "\r\n")?;
+                    stdout.flush()?;
+                    self.editor.history().save()?;
+                    return Ok(ReadResult::Line(text));
+                }
+                PromptAction::Cancel => {
+                    write!(stdout, "^C\r\n")?;
+                    stdout.flush()?;
+                    return Ok(ReadResult::Interrupted);
+                }
+                PromptAction::Eof => {
+                    write!(stdout, "\r\n")?;
+                    stdout.flush()?;
+                    return Ok(ReadResult::Eof);
+                }
+            }
+        }
+    }
+
+    fn render(&self, stdout: &mut impl Write, cursor_row: &mut usize) -> io::Result<()> {
+        self.render_editor(&self.editor, stdout, cursor_row)
+    }
+
+    fn render_editor(
+        &self,
+        editor: &PromptEditor,
+        stdout: &mut impl Write,
+        cursor_row: &mut usize,
+    ) -> io::Result<()> {
+        let cols = terminal::size().map_or(80, |(cols, _)| cols as usize);
+        // The submitted prompt is shown without hints or popup
+        let completer = self
+            .completer
+            .as_deref()
+            .filter(|_| std::ptr::eq(editor, &self.editor));
+        let (lines, cursor) = layout(editor, &self.prompt, &self.continuation, completer, cols);
+        write!(
+            stdout,
+            "{}",
+            redraw_sequence(*cursor_row, &lines, cursor, cols)
+        )?;
+        *cursor_row = cursor.0;
+        stdout.flush()
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn ctrl(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    fn alt(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::ALT)
+    }
+
+    fn type_str(editor: &mut PromptEditor, text: &str) {
+        for c in text.chars() {
+            editor.handle_key(key(KeyCode::Char(c)));
+        }
+    }
+
+    fn editor_with_history(entries: &[&str]) -> PromptEditor {
+        let mut history = History::new(100);
+        for entry in entries {
+            history.push(entry);
+        }
+        PromptEditor::with_history(history)
+    }
+
+    // =========================================
+    // Basic editing
+    // =========================================
+
+    #[test]
+    fn test_typing_and_submit() {
+        let mut editor = PromptEditor::new();
+        type_str(&mut editor, "hello");
+        assert_eq!(editor.buffer(), "hello");
+        assert_eq!(editor.cursor(), 5);
+        assert_eq!(
+            editor.handle_key(key(KeyCode::Enter)),
+            PromptAction::Submit("hello".to_string())
+        );
+        assert_eq!(editor.buffer(), "");
+        assert_eq!(editor.history().entries(), ["hello"]);
+    }
+
+    #[test]
+    fn test_cursor_movement_and_insert() {
+        let mut editor = PromptEditor::new();
+        type_str(&mut editor, "world");
+        editor.handle_key(ctrl('a'));
+        type_str(&mut editor, "hello ");
+        editor.handle_key(ctrl('e'));
+        type_str(&mut editor, "!");
+        assert_eq!(editor.buffer(), "hello world!");
+
+        editor.handle_key(ctrl('b'));
+        editor.handle_key(ctrl('b'));
+        editor.handle_key(ctrl('f'));
+        assert_eq!(editor.cursor(), 11);
+    }
+
+    #[test]
+    fn test_multibyte_movement_and_deletion() {
+        let mut editor = PromptEditor::new();
+        type_str(&mut editor, "héllo 日本");
+        editor.handle_key(key(KeyCode::Left));
+        editor.handle_key(key(KeyCode::Backspace));
+        assert_eq!(editor.buffer(), "héllo 本");
+        editor.handle_key(ctrl('a'));
+        editor.handle_key(key(KeyCode::Right));
+        editor.handle_key(ctrl('d'));
+        assert_eq!(editor.buffer(), "hllo 本");
+    }
+
+    #[test]
+    fn test_word_movement() {
+        let mut editor = PromptEditor::new();
+        type_str(&mut editor, "fix the  parser bug");
+        editor.handle_key(alt('b'));
+        assert_eq!(editor.cursor(), 16);
+        editor.handle_key(alt('b'));
+        assert_eq!(editor.cursor(), 9);
+        editor.handle_key(alt('f'));
+        assert_eq!(editor.cursor(), 15);
+        editor.handle_key(KeyEvent::new(KeyCode::Left, KeyModifiers::CONTROL));
+        assert_eq!(editor.cursor(), 9);
+    }
+
+    #[test]
+    fn test_kill_and_yank() {
+        let mut editor = PromptEditor::new();
+        type_str(&mut editor, "one two three");
+        editor.handle_key(ctrl('w'));
+        assert_eq!(editor.buffer(), "one two ");
+        editor.handle_key(ctrl('a'));
+        editor.handle_key(ctrl('y'));
+        assert_eq!(editor.buffer(), "threeone two ");
+
+        editor.handle_key(ctrl('k'));
+        assert_eq!(editor.buffer(), "three");
+        editor.handle_key(ctrl('u'));
+        assert_eq!(editor.buffer(), "");
+        editor.handle_key(ctrl('y'));
+        assert_eq!(editor.buffer(), "three");
+
+        editor.handle_key(ctrl('a'));
+        editor.handle_key(alt('d'));
+        assert_eq!(editor.buffer(), "");
+    }
+
+    #[test]
+    fn test_ctrl_c_and_ctrl_d() {
+        let mut editor = PromptEditor::new();
+        type_str(&mut editor, "abc");
+        assert_eq!(editor.handle_key(ctrl('d')), PromptAction::Continue);
+        assert_eq!(editor.handle_key(ctrl('c')), PromptAction::Cancel);
+        assert_eq!(editor.buffer(), "");
+        assert_eq!(editor.handle_key(ctrl('d')), PromptAction::Eof);
+        assert_eq!(editor.handle_key(ctrl('l')), PromptAction::ClearScreen);
+    }
+
+    #[test]
+    fn test_release_events_ignored() {
+        let mut editor = PromptEditor::new();
+        let mut release = key(KeyCode::Char('x'));
+        release.kind = KeyEventKind::Release;
+        editor.handle_key(release);
+        assert_eq!(editor.buffer(), "");
+    }
+
+    // =========================================
+    // Multiline
+    // =========================================
+
+    #[test]
+    fn test_shift_enter_inserts_newline() {
+        let mut editor = PromptEditor::new();
+        type_str(&mut editor, "line one");
+        editor.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::SHIFT));
+        type_str(&mut editor, "line two");
+        editor.handle_key(ctrl('j'));
+        editor.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::ALT));
+        assert_eq!(editor.buffer(), "line one\nline two\n\n");
+    }
+
+    #[test]
+    fn test_trailing_backslash_continues() {
+        let mut editor = PromptEditor::new();
+        type_str(&mut editor, "first \\");
+        assert_eq!(
+            editor.handle_key(key(KeyCode::Enter)),
+            PromptAction::Continue
+        );
+        type_str(&mut editor, "second");
+        assert_eq!(
+            editor.handle_key(key(KeyCode::Enter)),
+            PromptAction::Submit("first \nsecond".to_string())
+        );
+    }
+
+    #[test]
+    fn test_vertical_movement_within_buffer() {
+        let mut editor = PromptEditor::new();
+        editor.insert_str("abcdef\nxy\nlonger line");
+        editor.handle_key(key(KeyCode::Up));
+        // Column 11 clamps to the end of "xy"
+        assert_eq!(editor.cursor(), 9);
+        editor.handle_key(key(KeyCode::Up));
+        assert_eq!(editor.cursor(), 2);
+        editor.handle_key(key(KeyCode::Down));
+        assert_eq!(editor.cursor(), 9);
+        editor.handle_key(ctrl('a'));
+        assert_eq!(editor.cursor(), 7);
+    }
+
+    #[test]
+    fn test_bracketed_paste() {
+        let mut editor = PromptEditor::new();
+        type_str(&mut editor, "> ");
+        let action = editor.handle_event(&Event::Paste("fn main() {\r\n}\r\n".to_string()));
+        assert_eq!(action, PromptAction::Continue);
+        assert_eq!(editor.buffer(), "> fn main() {\n}\n");
+    }
+
+    // =========================================
+    // History
+    // =========================================
+
+    #[test]
+    fn test_history_navigation() {
+        let mut editor = editor_with_history(&["first", "second"]);
+        type_str(&mut editor, "draft");
+        editor.handle_key(key(KeyCode::Up));
+        assert_eq!(editor.buffer(), "second");
+        editor.handle_key(ctrl('p'));
+        assert_eq!(editor.buffer(), "first");
+        editor.handle_key(ctrl('p'));
+        assert_eq!(editor.buffer(), "first");
+        editor.handle_key(ctrl('n'));
+        assert_eq!(editor.buffer(), "second");
+        editor.handle_key(key(KeyCode::Down));
+        assert_eq!(editor.buffer(), "draft");
+    }
+
+    #[test]
+    fn test_history_push_dedupes_and_limits() {
+        let mut history = History::new(2);
+        history.push("a");
+        history.push("a");
+        history.push("   ");
+        history.push("b");
+        history.push("c");
+        assert_eq!(history.entries(), ["b", "c"]);
+    }
+
+    #[test]
+    fn test_history_file_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("history");
+
+        let mut history = History::load(&path, 10).unwrap();
+        assert!(history.is_empty());
+        history.push("multi\nline");
+        history.push("back\\slash");
+        history.save().unwrap();
+
+        let loaded = History::load(&path, 10).unwrap();
+        assert_eq!(loaded.entries(), ["multi\nline", "back\\slash"]);
+    }
+
+    #[test]
+    fn test_reverse_search() {
+        let mut editor = editor_with_history(&["cargo build", "git status", "cargo test"]);
+        type_str(&mut editor, "draft");
+        editor.handle_key(ctrl('r'));
+        type_str(&mut editor, "cargo");
+        assert_eq!(editor.search(), Some(("cargo", Some("cargo test"))));
+
+        editor.handle_key(ctrl('r'));
+        assert_eq!(editor.search(), Some(("cargo", Some("cargo build"))));
+
+        // Searching past the oldest match keeps it
+        editor.handle_key(ctrl('r'));
+        assert_eq!(editor.search(), Some(("cargo", Some("cargo build"))));
+
+        // Movement keys accept the match and keep editing
+        editor.handle_key(ctrl('e'));
+        assert!(editor.search().is_none());
+        assert_eq!(editor.buffer(), "cargo build");
+    }
+
+    #[test]
+    fn test_reverse_search_cancel_and_submit() {
+        let mut editor = editor_with_history(&["git status"]);
+        type_str(&mut editor, "draft");
+        editor.handle_key(ctrl('r'));
+        type_str(&mut editor, "git");
+        editor.handle_key(ctrl('g'));
+        assert_eq!(editor.buffer(), "draft");
+
+        editor.handle_key(ctrl('r'));
+        type_str(&mut editor, "stat");
+        assert_eq!(
+            editor.handle_key(key(KeyCode::Enter)),
+            PromptAction::Submit("git status".to_string())
+        );
+    }
+
+    // =========================================
+    // Rendering
+    // =========================================
+
+    #[test]
+    fn test_layout_multiline_cursor() {
+        let mut editor = PromptEditor::new();
+        editor.insert_str("ab\ncd");
+        editor.handle_key(key(KeyCode::Left));
+        let (lines, cursor) = layout(&editor, "> ", ". ", None, 80);
+        assert_eq!(lines, ["> ab", ". cd"]);
+        assert_eq!(cursor, (1, 3));
+    }
+
+    #[test]
+    fn test_layout_wraps_long_lines() {
+        let mut editor = PromptEditor::new();
+        editor.insert_str("abcdefgh\nxy");
+        editor.handle_key(key(KeyCode::Left));
+        let (lines, cursor) = layout(&editor, "> ", "> ", None, 4);
+        assert_eq!(lines.len(), 2);
+        // "> abcdefgh" takes 3 rows of 4 columns, so line 2 starts on row 3
+        assert_eq!(cursor, (3, 3));
+    }
+
+    #[test]
+    fn test_layout_with_tab() {
+        let mut editor = PromptEditor::new();
+        type_str(&mut editor, "a");
+        editor.handle_key(key(KeyCode::Tab));
+        type_str(&mut editor, "bcdef");
+        editor.handle_key(key(KeyCode::Tab));
+        assert_eq!(editor.buffer(), "a   bcdef   ");
+        let (lines, cursor) = layout(&editor, "> ", "> ", None, 80);
+        assert_eq!(lines, ["> a   bcdef   "]);
+        assert_eq!(cursor, (0, 14));
+    }
+
+    #[test]
+    fn test_layout_search() {
+        let mut editor = editor_with_history(&["cargo test"]);
+        editor.handle_key(ctrl('r'));
+        type_str(&mut editor, "test");
+        let (lines, _) = layout(&editor, "> ", "> ", None, 80);
+        assert_eq!(lines, ["(reverse-i-search)`test': cargo test"]);
+    }
+
+    /// Completes words from a fixed list.
+    struct WordCompleter(Vec<&'static str>);
+
+    impl Completer for WordCompleter {
+        fn complete(&self, line: &str, cursor: usize) -> (usize, Vec<Completion>) {
+            let start = line[..cursor].rfind(' ').map_or(0, |i| i + 1);
+            let word = &line[start..cursor];
+            let candidates = self
+                .0
+                .iter()
+                .filter(|w| !word.is_empty() && w.starts_with(word))
+                .map(|w| Completion {
+                    replacement: w.to_string(),
+                    description: Some(format!("the word {w}")),
+                })
+                .collect();
+            (start, candidates)
+        }
+
+        fn hint(&self, line: &str, cursor: usize) -> Option<String> {
+            let (start, candidates) = self.complete(line, cursor);
+            let first = candidates.first()?;
+            Some(first.replacement[cursor - start..].to_string())
+        }
+    }
+
+    #[test]
+    fn test_common_prefix() {
+        let candidates = |words: &[&str]| -> Vec<Completion> {
+            words
+                .iter()
+                .map(|w| Completion {
+                    replacement: w.to_string(),
+                    description: None,
+                })
+                .collect()
+        };
+        assert_eq!(common_prefix(&candidates(&["/clear", "/compact"])), "/c");
+        assert_eq!(common_prefix(&candidates(&["/help"])), "/help");
+        assert_eq!(common_prefix(&candidates(&["abc", "ab"])), "ab");
+        assert_eq!(common_prefix(&[]), "");
+    }
+
+    #[test]
+    fn test_complete_with() {
+        let completer = WordCompleter(vec!["commit", "compare", "status"]);
+        let mut editor = PromptEditor::new();
+
+        type_str(&mut editor, "git s");
+        let candidates = editor.complete_with(&completer);
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(editor.buffer(), "git status ");
+
+        type_str(&mut editor, "co");
+        assert_eq!(editor.complete_with(&completer).len(), 2);
+        assert_eq!(editor.buffer(), "git status com");
+
+        type_str(&mut editor, "x");
+        assert!(editor.complete_with(&completer).is_empty());
+        assert_eq!(editor.buffer(), "git status comx");
+    }
+
+    #[test]
+    fn test_layout_with_hint_and_popup() {
+        colored::control::set_override(false);
+        let completer = WordCompleter(vec!["commit", "compare"]);
+        let mut editor = PromptEditor::new();
+        type_str(&mut editor, "com");
+        let (lines, cursor) = layout(&editor, "> ", "> ", Some(&completer), 80);
+        assert_eq!(
+            lines,
+            [
+                "> commit",
+                "  commit  the word commit",
+                "  compare  the word compare"
+            ]
+        );
+        // The ghost text doesn't move the cursor
+        assert_eq!(cursor, (0, 5));
+
+        // An exact single match shows no popup
+        editor.set_buffer("commit");
+        let (lines, _) = layout(&editor, "> ", "> ", Some(&completer), 80);
+        assert_eq!(lines, ["> commit"]);
+        colored::control::unset_override();
+    }
+
+    #[test]
+    fn test_redraw_sequence() {
+        let lines = vec!["> ab".to_string(), ". cd".to_string()];
+        assert_eq!(
+            redraw_sequence(0, &lines, (0, 3), 80),
+            "\r\x1b[J> ab\r\n. cd\x1b[1A\r\x1b[3C"
+        );
+        assert_eq!(
+            redraw_sequence(1, &lines, (1, 0), 80),
+            "\r\x1b[1A\x1b[J> ab\r\n. cd\r"
+        );
+    }
+}
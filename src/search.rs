@@ -0,0 +1,438 @@
+//! Regex search over rendered transcript lines, with match highlighting.
+//!
+//! Lines are searched as they appear on screen: ANSI escape sequences are
+//! ignored when matching, so colored agent text, tool arguments and tool
+//! output are all found by their visible text. [`highlight_matches`] then
+//! marks the matched ranges in the original (colored) line without disturbing
+//! its styling.
+//!
+//! [`TranscriptSearch`] keeps the current match for `n`/`N` style navigation.
+//! It stores only a position, not the match list, so the searched lines may
+//! grow between calls. Patterns are smart-case: an all-lowercase pattern
+//! matches case-insensitively, and any uppercase letter makes it exact.
+//!
+//! # Example
+//!
+//! ```
+//! use clemitui::TranscriptSearch;
+//!
+//! let lines = ["┌─ grep TODO", "   src/main.rs:3: // TODO", "done"];
+//! let mut search = TranscriptSearch::new("todo")?;
+//! assert_eq!(search.matches(&lines).len(), 2);
+//!
+//! let first = search.next_match(&lines).unwrap();
+//! assert_eq!((first.line, first.start), (0, 12)); // byte offset
+//! let second = search.next_match(&lines).unwrap();
+//! assert_eq!(second.line, 1);
+//! # Ok::<(), clemitui::SearchError>(())
+//! ```
+
+use std::fmt;
+use std::ops::Range;
+
+use regex::{Regex, RegexBuilder};
+
+use crate::width::{segments, strip_ansi};
+
+/// Starts highlighting a match (reverse video).
+const MATCH_ON: &str = "\x1b[7m";
+/// Ends highlighting a match without resetting other styling.
+const MATCH_OFF: &str = "\x1b[27m";
+/// Starts highlighting the current match (reverse video, underlined).
+const CURRENT_ON: &str = "\x1b[4;7m";
+/// Ends highlighting the current match.
+const CURRENT_OFF: &str = "\x1b[24;27m";
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Error compiling a search pattern.
+#[derive(Debug)]
+pub enum SearchError {
+    /// The pattern is not a valid regular expression.
+    InvalidPattern {
+        /// The pattern as typed.
+        pattern: String,
+        /// Underlying regex error.
+        source: regex::Error,
+    },
+}
+
+impl fmt::Display for SearchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidPattern { pattern, source } => {
+                write!(f, "invalid search pattern '{pattern}': {source}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SearchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InvalidPattern { source, .. } => Some(source),
+        }
+    }
+}
+
+// ============================================================================
+// Matching
+// ============================================================================
+
+/// A match in a transcript line.
+///
+/// `start..end` is a byte range in the line's visible text, i.e. the line with
+/// ANSI escape sequences removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SearchMatch {
+    /// Index of the line.
+    pub line: usize,
+    /// Start of the match in the visible text.
+    pub start: usize,
+    /// End of the match in the visible text.
+    pub end: usize,
+}
+
+/// Compile `pattern` as a smart-case regex.
+fn compile(pattern: &str) -> Result<Regex, SearchError> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(!pattern.chars().any(char::is_uppercase))
+        .build()
+        .map_err(|source| SearchError::InvalidPattern {
+            pattern: pattern.to_string(),
+            source,
+        })
+}
+
+/// Search state: a compiled pattern and the current match.
+#[derive(Debug, Clone)]
+pub struct TranscriptSearch {
+    pattern: String,
+    regex: Regex,
+    current: Option<SearchMatch>,
+}
+
+impl TranscriptSearch {
+    /// Compile `pattern` (a regular expression, smart-case).
+    pub fn new(pattern: &str) -> Result<Self, SearchError> {
+        Ok(Self {
+            pattern: pattern.to_string(),
+            regex: compile(pattern)?,
+            current: None,
+        })
+    }
+
+    /// The pattern as given.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// The current match, if one has been selected.
+    pub fn current(&self) -> Option<SearchMatch> {
+        self.current
+    }
+
+    /// Byte ranges of non-empty matches in the visible text of `line`.
+    pub fn ranges(&self, line: &str) -> Vec<Range<usize>> {
+        self.regex
+            .find_iter(&strip_ansi(line))
+            .filter(|found| !found.is_empty())
+            .map(|found| found.range())
+            .collect()
+    }
+
+    /// All matches in `lines`, in order.
+    pub fn matches<S: AsRef<str>>(&self, lines: &[S]) -> Vec<SearchMatch> {
+        lines
+            .iter()
+            .enumerate()
+            .flat_map(|(line, text)| {
+                self.ranges(text.as_ref())
+                    .into_iter()
+                    .map(move |range| SearchMatch {
+                        line,
+                        start: range.start,
+                        end: range.end,
+                    })
+            })
+            .collect()
+    }
+
+    /// Select the match after the current one (the first if none is
+    /// selected), wrapping around at the end.
+    pub fn next_match<S: AsRef<str>>(&mut self, lines: &[S]) -> Option<SearchMatch> {
+        let matches = self.matches(lines);
+        let next = match self.current {
+            Some(current) => matches.iter().find(|found| **found > current),
+            None => None,
+        };
+        self.current = next.or(matches.first()).copied();
+        self.current
+    }
+
+    /// Select the match before the current one (the last if none is
+    /// selected), wrapping around at the start.
+    pub fn previous_match<S: AsRef<str>>(&mut self, lines: &[S]) -> Option<SearchMatch> {
+        let matches = self.matches(lines);
+        let previous = match self.current {
+            Some(current) => matches.iter().rev().find(|found| **found < current),
+            None => None,
+        };
+        self.current = previous.or(matches.last()).copied();
+        self.current
+    }
+
+    /// Select the last match on or before line `line`, or the first match if
+    /// there is none (used to start searching from the visible part of a
+    /// transcript).
+    pub fn select_before<S: AsRef<str>>(
+        &mut self,
+        lines: &[S],
+        line: usize,
+    ) -> Option<SearchMatch> {
+        let matches = self.matches(lines);
+        self.current = matches
+            .iter()
+            .rev()
+            .find(|found| found.line <= line)
+            .or(matches.first())
+            .copied();
+        self.current
+    }
+
+    /// Position of the current match as `(1-based index, total matches)`.
+    pub fn position<S: AsRef<str>>(&self, lines: &[S]) -> (usize, usize) {
+        let matches = self.matches(lines);
+        let index = self
+            .current
+            .and_then(|current| matches.iter().position(|found| *found == current))
+            .map_or(0, |i| i + 1);
+        (index, matches.len())
+    }
+
+    /// `line` (the `index`-th of the searched lines) with its matches
+    /// highlighted; see [`highlight_matches`].
+    pub fn highlight(&self, index: usize, line: &str) -> String {
+        let ranges = self.ranges(line);
+        highlight_matches(line, &ranges, self.current_range(index, line))
+    }
+
+    /// Which of [`Self::ranges`] of `line` (the `index`-th line) is the
+    /// current match.
+    fn current_range(&self, index: usize, line: &str) -> Option<usize> {
+        let current = self.current.filter(|current| current.line == index)?;
+        self.ranges(line)
+            .iter()
+            .position(|range| range.start == current.start)
+    }
+}
+
+// ============================================================================
+// Highlighting
+// ============================================================================
+
+/// Highlight byte ranges of `line`'s visible text in reverse video.
+///
+/// `ranges` index the text with ANSI escape sequences removed (as returned by
+/// [`TranscriptSearch::ranges`]); they must be sorted and non-overlapping. The
+/// range at index `current` is also underlined. Existing colors are kept, and
+/// the highlight is reapplied after any reset inside a match. Returns `line`
+/// unchanged when colors are disabled.
+///
+/// # Example
+///
+/// ```
+/// use clemitui::highlight_matches;
+///
+/// colored::control::set_override(true);
+/// let line = highlight_matches("find the needle", &[9..15], None);
+/// assert_eq!(line, "find the \x1b[7mneedle\x1b[27m");
+/// colored::control::unset_override();
+/// ```
+pub fn highlight_matches(line: &str, ranges: &[Range<usize>], current: Option<usize>) -> String {
+    if !colored::control::SHOULD_COLORIZE.should_colorize() {
+        return line.to_string();
+    }
+    highlight_ranges(line, ranges, current)
+}
+
+/// [`highlight_matches`] regardless of the color setting.
+fn highlight_ranges(line: &str, ranges: &[Range<usize>], current: Option<usize>) -> String {
+    let codes = |i: usize| {
+        if Some(i) == current {
+            (CURRENT_ON, CURRENT_OFF)
+        } else {
+            (MATCH_ON, MATCH_OFF)
+        }
+    };
+    // (offset, code, inside a match after this point)
+    let mut events = Vec::with_capacity(ranges.len() * 2);
+    for (i, range) in ranges.iter().enumerate() {
+        let (on, off) = codes(i);
+        events.push((range.start, on, Some(i)));
+        events.push((range.end, off, None));
+    }
+
+    let mut out = String::with_capacity(line.len() + events.len() * 8);
+    let mut events = events.into_iter().peekable();
+    let mut inside: Option<usize> = None;
+    let mut pos = 0;
+    for (segment, is_escape) in segments(line) {
+        if is_escape {
+            out.push_str(segment);
+            if let Some(i) = inside {
+                out.push_str(codes(i).0);
+            }
+            continue;
+        }
+        let mut written = 0;
+        while let Some(&(offset, code, state)) = events.peek() {
+            if offset >= pos + segment.len() {
+                break;
+            }
+            let split = offset.saturating_sub(pos);
+            out.push_str(&segment[written..split.max(written)]);
+            written = split.max(written);
+            out.push_str(code);
+            inside = state;
+            events.next();
+        }
+        out.push_str(&segment[written..]);
+        pos += segment.len();
+    }
+    for (_, code, _) in events {
+        out.push_str(code);
+    }
+    out
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_smart_case() {
+        let lower = TranscriptSearch::new("error").unwrap();
+        assert_eq!(lower.ranges("ERROR and error").len(), 2);
+        let upper = TranscriptSearch::new("ERROR").unwrap();
+        assert_eq!(upper.ranges("ERROR and error"), vec![0..5]);
+    }
+
+    #[test]
+    fn test_regex_pattern() {
+        let search = TranscriptSearch::new(r"\d+\.\d+s").unwrap();
+        assert_eq!(search.ranges("└─ bash 0.25s ~10 tok"), vec![12..17]);
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        let err = TranscriptSearch::new("(unclosed").unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("invalid search pattern '(unclosed'")
+        );
+        assert!(std::error::Error::source(&err).is_some());
+    }
+
+    #[test]
+    fn test_matches_ignore_ansi() {
+        let search = TranscriptSearch::new("red text").unwrap();
+        let lines = ["\x1b[31mred\x1b[0m text"];
+        assert_eq!(
+            search.matches(&lines),
+            vec![SearchMatch {
+                line: 0,
+                start: 0,
+                end: 8,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_empty_matches_skipped() {
+        let search = TranscriptSearch::new("x*").unwrap();
+        assert_eq!(search.ranges("abc"), Vec::<Range<usize>>::new());
+    }
+
+    #[test]
+    fn test_next_and_previous_wrap() {
+        let lines = ["a", "b a", "c"];
+        let mut search = TranscriptSearch::new("a").unwrap();
+        assert_eq!(search.next_match(&lines).unwrap().line, 0);
+        assert_eq!(search.next_match(&lines).unwrap().line, 1);
+        assert_eq!(search.next_match(&lines).unwrap().line, 0, "wraps");
+        assert_eq!(search.previous_match(&lines).unwrap().line, 1, "wraps back");
+        assert_eq!(search.position(&lines), (2, 2));
+    }
+
+    #[test]
+    fn test_next_with_no_matches() {
+        let mut search = TranscriptSearch::new("zzz").unwrap();
+        assert_eq!(search.next_match(&["a", "b"]), None);
+        assert_eq!(search.position(&["a", "b"]), (0, 0));
+    }
+
+    #[test]
+    fn test_select_before() {
+        let lines = ["hit", "miss", "hit", "miss", "hit"];
+        let mut search = TranscriptSearch::new("hit").unwrap();
+        assert_eq!(search.select_before(&lines, 3).unwrap().line, 2);
+        assert_eq!(search.select_before(&lines, 0).unwrap().line, 0);
+        let mut later = TranscriptSearch::new("miss").unwrap();
+        assert_eq!(
+            later.select_before(&lines, 0).unwrap().line,
+            1,
+            "falls back to the first"
+        );
+    }
+
+    #[test]
+    fn test_highlight_plain() {
+        let line = highlight_ranges("a needle and a needle", &[2..8, 15..21], Some(1));
+        assert_eq!(
+            line,
+            "a \x1b[7mneedle\x1b[27m and a \x1b[4;7mneedle\x1b[24;27m"
+        );
+    }
+
+    #[test]
+    fn test_highlight_keeps_colors_and_survives_resets() {
+        // Match spans a colored word and the reset after it
+        let line = "\x1b[31mred\x1b[0m text";
+        let out = highlight_ranges(line, std::slice::from_ref(&(0..8)), None);
+        assert_eq!(out, "\x1b[31m\x1b[7mred\x1b[0m\x1b[7m text\x1b[27m");
+        assert_eq!(strip_ansi(&out), "red text");
+    }
+
+    #[test]
+    fn test_highlight_at_line_end() {
+        let out = highlight_ranges("abc", std::slice::from_ref(&(1..3)), None);
+        assert_eq!(out, "a\x1b[7mbc\x1b[27m");
+    }
+
+    #[test]
+    fn test_highlight_without_colors() {
+        colored::control::set_override(false);
+        assert_eq!(
+            highlight_matches("abc", std::slice::from_ref(&(0..1)), None),
+            "abc"
+        );
+        colored::control::unset_override();
+    }
+
+    #[test]
+    fn test_search_current_range() {
+        let lines = ["one two one"];
+        let mut search = TranscriptSearch::new("one").unwrap();
+        search.next_match(&lines);
+        search.next_match(&lines);
+        assert_eq!(search.current_range(0, lines[0]), Some(1));
+        assert_eq!(search.current_range(1, lines[0]), None);
+    }
+}
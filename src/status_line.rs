@@ -0,0 +1,439 @@
+//! Persistent status line pinned to the bottom row of the terminal.
+//!
+//! [`StatusBar`] reserves the last terminal row by shrinking the scroll
+//! region (`DECSTBM`) to the rows above it. Regular [`log_event`] output keeps
+//! scrolling in that region while the status line stays in place, showing the
+//! model, session mode, context gauge, turn time, running tools and cost.
+//!
+//! The scroll region is reset when the bar is dropped, and a panic hook
+//! restores the terminal if the process panics while a bar is installed.
+//! `std::process::exit` skips destructors, so call [`StatusBar::uninstall`]
+//! before exiting that way.
+//!
+//! [`log_event`]: crate::log_event
+
+use std::io::{self, Stdout, Write};
+use std::sync::Once;
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::time::{Duration, Instant};
+
+use colored::Colorize;
+
+use crate::format::{ContextGaugeConfig, format_context_gauge, format_cost, format_elapsed};
+use crate::width::display_width;
+
+// ============================================================================
+// Status Line Formatting
+// ============================================================================
+
+/// Separator between status line segments.
+const SEGMENT_SEPARATOR: &str = " │ ";
+
+/// Contents of the status line. Unset fields are omitted.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StatusInfo {
+    /// Model name (e.g. `claude-sonnet-4`).
+    pub model: Option<String>,
+    /// Session mode (e.g. `plan`, `auto-edit`).
+    pub mode: Option<String>,
+    /// Context window usage as `(used, limit)` tokens.
+    pub context: Option<(u64, u64)>,
+    /// Elapsed time of the current turn.
+    pub turn_elapsed: Option<Duration>,
+    /// Number of tools currently executing.
+    pub running_tools: usize,
+    /// Cost as `(turn, total)` in USD.
+    pub cost: Option<(f64, f64)>,
+}
+
+/// Format the status line contents to fit within `width` columns.
+///
+/// Segments are added in priority order (model, context, turn time, running
+/// tools, cost, mode) and any segment that would overflow `width` is dropped,
+/// so the most important information survives on narrow terminals.
+///
+/// # Example
+///
+/// ```
+/// use clemitui::{ContextGaugeConfig, StatusInfo, format_status_line};
+///
+/// colored::control::set_override(false);
+/// let info = StatusInfo {
+///     model: Some("sonnet".to_string()),
+///     running_tools: 2,
+///     ..Default::default()
+/// };
+/// let line = format_status_line(&info, &ContextGaugeConfig::default(), 80);
+/// assert_eq!(line, " sonnet │ 2 tools running");
+/// colored::control::unset_override();
+/// ```
+pub fn format_status_line(info: &StatusInfo, gauge: &ContextGaugeConfig, width: usize) -> String {
+    let mut segments = Vec::new();
+    if let Some(model) = &info.model {
+        segments.push(model.bold().to_string());
+    }
+    if let Some((used, limit)) = info.context {
+        segments.push(format_context_gauge(used, limit, gauge));
+    }
+    if let Some(elapsed) = info.turn_elapsed {
+        segments.push(format_elapsed(elapsed).yellow().to_string());
+    }
+    if info.running_tools > 0 {
+        let label = if info.running_tools == 1 {
+            "tool"
+        } else {
+            "tools"
+        };
+        segments.push(
+            format!("{} {} running", info.running_tools, label)
+                .cyan()
+                .to_string(),
+        );
+    }
+    if let Some((turn, total)) = info.cost {
+        segments.push(format_cost(turn, total));
+    }
+    if let Some(mode) = &info.mode {
+        segments.push(mode.magenta().to_string());
+    }
+
+    let separator = SEGMENT_SEPARATOR.dimmed().to_string();
+    let mut line = String::from(" ");
+    let mut used = 1;
+    let mut first = true;
+    for segment in segments {
+        let separator_len = if first {
+            0
+        } else {
+            display_width(SEGMENT_SEPARATOR)
+        };
+        let extra = separator_len + display_width(&segment);
+        if used + extra > width {
+            continue;
+        }
+        if !first {
+            line.push_str(&separator);
+        }
+        line.push_str(&segment);
+        used += extra;
+        first = false;
+    }
+    line
+}
+
+// ============================================================================
+// Escape Sequences
+// ============================================================================
+
+/// Reserve the bottom row: make room for it, then limit scrolling to the rows above.
+///
+/// `DECSTBM` moves the cursor home, so the cursor is saved and restored around it.
+fn setup_sequence(rows: u16) -> String {
+    format!(
+        "\n\x1b[1A\x1b7\x1b[1;{}r\x1b8",
+        rows.saturating_sub(1).max(1)
+    )
+}
+
+/// Draw `line` on the bottom row without disturbing the cursor.
+fn draw_sequence(line: &str, rows: u16) -> String {
+    format!("\x1b7\x1b[{};1H\x1b[2K{}\x1b[0m\x1b8", rows, line)
+}
+
+/// Reset the scroll region and clear the status row.
+fn teardown_sequence(rows: u16) -> String {
+    format!("\x1b7\x1b[r\x1b[{};1H\x1b[2K\x1b8", rows)
+}
+
+// ============================================================================
+// Panic Restore
+// ============================================================================
+
+/// Row count of the installed status bar, or 0 when none is installed.
+static ACTIVE_ROWS: AtomicU16 = AtomicU16::new(0);
+
+static PANIC_HOOK: Once = Once::new();
+
+/// Install (once) a panic hook that tears down an active status bar before
+/// the default hook prints the panic message.
+fn install_panic_hook() {
+    PANIC_HOOK.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |panic_info| {
+            let rows = ACTIVE_ROWS.swap(0, Ordering::SeqCst);
+            if rows > 0 {
+                let mut stdout = io::stdout();
+                let _ = stdout.write_all(teardown_sequence(rows).as_bytes());
+                let _ = stdout.flush();
+            }
+            previous(panic_info);
+        }));
+    });
+}
+
+// ============================================================================
+// Status Bar
+// ============================================================================
+
+/// A status line pinned to the bottom terminal row.
+///
+/// # Example
+///
+/// ```no_run
+/// use clemitui::{StatusBar, StatusInfo, log_event};
+///
+/// let mut bar = StatusBar::install(StatusInfo {
+///     model: Some("claude-sonnet-4".to_string()),
+///     ..Default::default()
+/// })?;
+///
+/// bar.start_turn();
+/// log_event("output scrolls above the status line");
+/// bar.update(|info| info.running_tools = 1)?;
+/// bar.end_turn()?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct StatusBar<W: Write = Stdout> {
+    writer: W,
+    info: StatusInfo,
+    gauge: ContextGaugeConfig,
+    turn_started: Option<Instant>,
+    cols: u16,
+    rows: u16,
+    restore_on_panic: bool,
+    installed: bool,
+}
+
+impl StatusBar<Stdout> {
+    /// Install a status bar on stdout, sized to the current terminal.
+    pub fn install(info: StatusInfo) -> io::Result<Self> {
+        let (cols, rows) = crossterm::terminal::size()?;
+        let mut bar = Self::with_writer(io::stdout(), cols, rows, info);
+        bar.restore_on_panic = true;
+        install_panic_hook();
+        bar.setup()?;
+        Ok(bar)
+    }
+
+    /// Re-read the terminal size and redraw (call on terminal resize).
+    pub fn refresh_size(&mut self) -> io::Result<()> {
+        let (cols, rows) = crossterm::terminal::size()?;
+        self.resize(cols, rows)
+    }
+}
+
+impl<W: Write> StatusBar<W> {
+    /// Create a status bar writing to `writer` for a terminal of the given size.
+    ///
+    /// The bar is not drawn until [`Self::setup`] is called. Bars created this
+    /// way are not restored by the panic hook.
+    pub fn with_writer(writer: W, cols: u16, rows: u16, info: StatusInfo) -> Self {
+        Self {
+            writer,
+            info,
+            gauge: ContextGaugeConfig::default(),
+            turn_started: None,
+            cols,
+            rows,
+            restore_on_panic: false,
+            installed: false,
+        }
+    }
+
+    /// Set the context gauge configuration used for the context segment.
+    pub fn with_gauge(mut self, gauge: ContextGaugeConfig) -> Self {
+        self.gauge = gauge;
+        self
+    }
+
+    /// Reserve the bottom row and draw the status line.
+    pub fn setup(&mut self) -> io::Result<()> {
+        self.writer
+            .write_all(setup_sequence(self.rows).as_bytes())?;
+        self.installed = true;
+        if self.restore_on_panic {
+            ACTIVE_ROWS.store(self.rows, Ordering::SeqCst);
+        }
+        self.redraw()
+    }
+
+    /// Current status line contents.
+    pub fn info(&self) -> &StatusInfo {
+        &self.info
+    }
+
+    /// Modify the status line contents and redraw.
+    pub fn update(&mut self, f: impl FnOnce(&mut StatusInfo)) -> io::Result<()> {
+        f(&mut self.info);
+        self.redraw()
+    }
+
+    /// Start timing a new turn.
+    pub fn start_turn(&mut self) {
+        self.turn_started = Some(Instant::now());
+    }
+
+    /// Stop timing the turn, leaving the final elapsed time displayed.
+    pub fn end_turn(&mut self) -> io::Result<()> {
+        if let Some(started) = self.turn_started.take() {
+            self.info.turn_elapsed = Some(started.elapsed());
+        }
+        self.redraw()
+    }
+
+    /// Redraw the status line (call periodically to tick the turn timer).
+    pub fn redraw(&mut self) -> io::Result<()> {
+        if !self.installed {
+            return Ok(());
+        }
+        if let Some(started) = self.turn_started {
+            self.info.turn_elapsed = Some(started.elapsed());
+        }
+        let line = format_status_line(&self.info, &self.gauge, self.cols as usize);
+        self.writer
+            .write_all(draw_sequence(&line, self.rows).as_bytes())?;
+        self.writer.flush()
+    }
+
+    /// Adapt to a new terminal size: clear the old row, re-reserve and redraw.
+    pub fn resize(&mut self, cols: u16, rows: u16) -> io::Result<()> {
+        if self.installed {
+            self.writer
+                .write_all(teardown_sequence(self.rows).as_bytes())?;
+        }
+        self.cols = cols;
+        self.rows = rows;
+        if self.installed {
+            self.setup()?;
+        }
+        Ok(())
+    }
+
+    /// Reset the scroll region and clear the status row.
+    pub fn uninstall(&mut self) -> io::Result<()> {
+        if !self.installed {
+            return Ok(());
+        }
+        self.installed = false;
+        if self.restore_on_panic {
+            ACTIVE_ROWS.store(0, Ordering::SeqCst);
+        }
+        self.writer
+            .write_all(teardown_sequence(self.rows).as_bytes())?;
+        self.writer.flush()
+    }
+}
+
+impl<W: Write> Drop for StatusBar<W> {
+    fn drop(&mut self) {
+        let _ = self.uninstall();
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_info() -> StatusInfo {
+        StatusInfo {
+            model: Some("sonnet".to_string()),
+            mode: Some("plan".to_string()),
+            context: Some((50_000, 200_000)),
+            turn_elapsed: Some(Duration::from_secs(65)),
+            running_tools: 1,
+            cost: Some((0.5, 2.0)),
+        }
+    }
+
+    #[test]
+    fn test_format_status_line_all_segments() {
+        colored::control::set_override(false);
+        let line = format_status_line(&sample_info(), &ContextGaugeConfig::default(), 200);
+        assert_eq!(
+            line,
+            " sonnet │ [███░░░░░░░] 25% 50.0k/200.0k │ 1m 05s │ 1 tool running │ $0.50 turn · $2.00 total │ plan"
+        );
+        colored::control::unset_override();
+    }
+
+    #[test]
+    fn test_format_status_line_drops_segments_to_fit() {
+        colored::control::set_override(false);
+        let line = format_status_line(&sample_info(), &ContextGaugeConfig::default(), 57);
+        assert!(display_width(&line) <= 57, "too wide: {line:?}");
+        assert!(line.starts_with(" sonnet │ [███"));
+        assert!(!line.contains("total"), "cost should be dropped: {line:?}");
+        assert!(line.ends_with("plan"), "short mode still fits: {line:?}");
+        colored::control::unset_override();
+    }
+
+    #[test]
+    fn test_format_status_line_empty() {
+        let line = format_status_line(&StatusInfo::default(), &ContextGaugeConfig::default(), 80);
+        assert_eq!(line, " ");
+    }
+
+    #[test]
+    fn test_escape_sequences() {
+        assert_eq!(setup_sequence(24), "\n\x1b[1A\x1b7\x1b[1;23r\x1b8");
+        assert_eq!(
+            draw_sequence("hi", 24),
+            "\x1b7\x1b[24;1H\x1b[2Khi\x1b[0m\x1b8"
+        );
+        assert_eq!(teardown_sequence(24), "\x1b7\x1b[r\x1b[24;1H\x1b[2K\x1b8");
+        // Degenerate one-row terminal keeps a valid region
+        assert_eq!(setup_sequence(1), "\n\x1b[1A\x1b7\x1b[1;1r\x1b8");
+    }
+
+    #[test]
+    fn test_status_bar_lifecycle() {
+        colored::control::set_override(false);
+        let mut output = Vec::new();
+        {
+            let mut bar = StatusBar::with_writer(&mut output, 80, 24, StatusInfo::default());
+            // Not drawn until setup
+            bar.redraw().unwrap();
+            bar.setup().unwrap();
+            bar.update(|info| info.model = Some("flash".to_string()))
+                .unwrap();
+            bar.resize(100, 30).unwrap();
+            bar.uninstall().unwrap();
+        }
+        let output = String::from_utf8(output).unwrap();
+        colored::control::unset_override();
+
+        assert!(output.starts_with(&setup_sequence(24)));
+        assert!(output.contains(&draw_sequence(" flash", 24)));
+        // Resize clears the old row and reserves the new one
+        assert!(output.contains(&format!("{}{}", teardown_sequence(24), setup_sequence(30))));
+        assert!(output.contains(&draw_sequence(" flash", 30)));
+        // Explicit uninstall restores the terminal; Drop doesn't repeat it
+        assert!(output.ends_with(&teardown_sequence(30)));
+        assert_eq!(output.matches(&teardown_sequence(30)).count(), 1);
+    }
+
+    #[test]
+    fn test_status_bar_turn_timer() {
+        let mut bar = StatusBar::with_writer(io::sink(), 80, 24, StatusInfo::default());
+        bar.setup().unwrap();
+        assert!(bar.info().turn_elapsed.is_none());
+        bar.start_turn();
+        bar.end_turn().unwrap();
+        assert!(bar.info().turn_elapsed.is_some());
+    }
+
+    #[test]
+    fn test_status_bar_drop_restores() {
+        let mut output = Vec::new();
+        {
+            let mut bar = StatusBar::with_writer(&mut output, 80, 10, StatusInfo::default());
+            bar.setup().unwrap();
+        }
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.ends_with(&teardown_sequence(10)));
+    }
+}
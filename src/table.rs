@@ -0,0 +1,657 @@
+//! Markdown tables fitted to the terminal width.
+//!
+//! Markdown is rendered without line wrapping, so a wide table would run off
+//! the right edge of the terminal. Tables are therefore taken out of the
+//! markdown before it is rendered and drawn here instead:
+//!
+//! - borders use the [`BoxGlyphs`] of the active theme;
+//! - columns are aligned according to the `:---`, `:---:` and `---:` markers;
+//! - when the table is wider than the terminal, the widest columns shrink
+//!   and their cells wrap onto several lines;
+//! - when even that does not fit, each row is printed as a vertical record of
+//!   `header │ value` lines.
+//!
+//! # Usage
+//!
+//! ```
+//! use clemitui::{Table, format_table};
+//!
+//! let table = Table::parse("| Crate | Version |\n|---|--:|\n| serde | 1.0 |").unwrap();
+//! print!("{}", format_table(&table, 80));
+//! ```
+
+use colored::Colorize;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::theme::{BoxGlyphs, theme};
+use crate::width::{display_width, segments, truncate_end};
+
+/// Width below which columns are not shrunk further.
+const MIN_COLUMN_WIDTH: usize = 6;
+
+/// Marks where a table was taken out of the markdown (`\u{E002}index\u{E003}`).
+const PLACEHOLDER_START: char = '\u{E002}';
+const PLACEHOLDER_END: char = '\u{E003}';
+
+// ============================================================================
+// Tables
+// ============================================================================
+
+/// Horizontal alignment of a table column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColumnAlign {
+    /// `:---` or `---` (default).
+    #[default]
+    Left,
+    /// `:---:`.
+    Center,
+    /// `---:`.
+    Right,
+}
+
+/// A table with a header row.
+///
+/// Cells may contain ANSI styling; widths are measured in terminal columns.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Table {
+    /// Column headers.
+    pub headers: Vec<String>,
+    /// Alignment of each column.
+    pub alignments: Vec<ColumnAlign>,
+    /// Body rows, each with one cell per column.
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    /// Create a table with left-aligned columns and no rows.
+    pub fn new<S: Into<String>>(headers: impl IntoIterator<Item = S>) -> Self {
+        let headers: Vec<String> = headers.into_iter().map(Into::into).collect();
+        Self {
+            alignments: vec![ColumnAlign::Left; headers.len()],
+            headers,
+            rows: Vec::new(),
+        }
+    }
+
+    /// Set column alignments; missing columns stay left-aligned.
+    pub fn with_alignments(mut self, alignments: impl IntoIterator<Item = ColumnAlign>) -> Self {
+        let mut alignments: Vec<ColumnAlign> = alignments.into_iter().collect();
+        alignments.resize(self.headers.len(), ColumnAlign::Left);
+        self.alignments = alignments;
+        self
+    }
+
+    /// Append a row, padded or cut to the number of columns.
+    pub fn push_row<S: Into<String>>(&mut self, cells: impl IntoIterator<Item = S>) {
+        let mut row: Vec<String> = cells.into_iter().map(Into::into).collect();
+        row.resize(self.headers.len(), String::new());
+        self.rows.push(row);
+    }
+
+    /// Parse a GitHub-flavored markdown table: a header row, a delimiter row
+    /// with one `---` per column, and any number of body rows.
+    ///
+    /// Returns None if the text does not start with a table. Lines after the
+    /// table's last row are ignored.
+    pub fn parse(markdown: &str) -> Option<Self> {
+        let mut lines = markdown.lines();
+        let mut table = parse_head(lines.next()?, lines.next()?)?;
+        for line in lines.take_while(|line| is_row(line)) {
+            table.push_row(split_row(line));
+        }
+        Some(table)
+    }
+
+    /// Apply `f` to every header and body cell.
+    fn map_cells(&self, mut f: impl FnMut(&str) -> String) -> Self {
+        Self {
+            headers: self.headers.iter().map(|cell| f(cell)).collect(),
+            alignments: self.alignments.clone(),
+            rows: self
+                .rows
+                .iter()
+                .map(|row| row.iter().map(|cell| f(cell)).collect())
+                .collect(),
+        }
+    }
+}
+
+/// Header and alignments from a header line and a delimiter line.
+fn parse_head(header: &str, delimiter: &str) -> Option<Table> {
+    if !header.contains('|') {
+        return None;
+    }
+    let headers = split_row(header);
+    let alignments = split_row(delimiter)
+        .iter()
+        .map(|cell| {
+            let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+            if dashes.is_empty() || !dashes.chars().all(|c| c == '-') {
+                return None;
+            }
+            Some(match (cell.starts_with(':'), cell.ends_with(':')) {
+                (true, true) => ColumnAlign::Center,
+                (false, true) => ColumnAlign::Right,
+                _ => ColumnAlign::Left,
+            })
+        })
+        .collect::<Option<Vec<_>>>()?;
+    if alignments.len() != headers.len() {
+        return None;
+    }
+    Some(Table::new(headers).with_alignments(alignments))
+}
+
+/// Whether `line` can continue a table body.
+fn is_row(line: &str) -> bool {
+    !line.trim().is_empty() && line.contains('|')
+}
+
+/// Cells of a table row, without the outer pipes. `\|` and pipes inside
+/// backtick code spans do not split cells.
+fn split_row(line: &str) -> Vec<String> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = match line.strip_suffix('|') {
+        Some(rest) if !rest.ends_with('\\') => rest,
+        _ => line,
+    };
+
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut in_code = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'|') => {
+                cell.push('|');
+                chars.next();
+            }
+            '`' => {
+                in_code = !in_code;
+                cell.push(c);
+            }
+            '|' if !in_code => cells.push(std::mem::take(&mut cell)),
+            _ => cell.push(c),
+        }
+    }
+    cells.push(cell);
+    cells
+        .into_iter()
+        .map(|cell| cell.trim().to_string())
+        .collect()
+}
+
+// ============================================================================
+// Markdown Pipeline
+// ============================================================================
+
+/// Replace each table in `text` (outside fenced code) with a placeholder
+/// line, returning the tables in order.
+pub(crate) fn extract_tables(text: &str) -> (String, Vec<Table>) {
+    let lines: Vec<&str> = text.split('\n').collect();
+    let mut out: Vec<String> = Vec::with_capacity(lines.len());
+    let mut tables = Vec::new();
+    let mut fence: Option<&str> = None;
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim_start();
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+        } else if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fence = Some(&trimmed[..3]);
+        } else if let Some(delimiter) = lines.get(i + 1)
+            && let Some(mut table) = parse_head(line, delimiter)
+        {
+            i += 2;
+            while i < lines.len() && is_row(lines[i]) {
+                table.push_row(split_row(lines[i]));
+                i += 1;
+            }
+            out.push(format!(
+                "{PLACEHOLDER_START}{}{PLACEHOLDER_END}",
+                tables.len()
+            ));
+            tables.push(table);
+            continue;
+        }
+        out.push(line.to_string());
+        i += 1;
+    }
+    (out.join("\n"), tables)
+}
+
+/// Replace placeholder lines in rendered markdown with the formatted tables,
+/// rendering each cell's inline markdown with `render_cell`.
+pub(crate) fn restore_tables(
+    rendered: &str,
+    tables: &[Table],
+    max_width: usize,
+    mut render_cell: impl FnMut(&str) -> String,
+) -> String {
+    if tables.is_empty() {
+        return rendered.to_string();
+    }
+    let lines: Vec<String> = rendered
+        .split('\n')
+        .map(|line| {
+            let index = line
+                .split_once(PLACEHOLDER_START)
+                .and_then(|(_, rest)| rest.split_once(PLACEHOLDER_END))
+                .and_then(|(index, _)| index.parse::<usize>().ok());
+            match index.and_then(|index| tables.get(index)) {
+                Some(table) => {
+                    let table = table.map_cells(&mut render_cell);
+                    format_table(&table, max_width)
+                        .trim_end_matches('\n')
+                        .to_string()
+                }
+                None => line.to_string(),
+            }
+        })
+        .collect();
+    lines.join("\n")
+}
+
+// ============================================================================
+// Formatting
+// ============================================================================
+
+/// Format a table with box borders, fitting it within `max_width` columns.
+///
+/// Columns wider than the terminal allows are shrunk, wrapping their cells:
+/// first down to their longest word, then to `MIN_COLUMN_WIDTH`. If the table
+/// still does not fit, each row is shown as a record of `header │ value`
+/// lines instead.
+///
+/// # Example
+///
+/// ```
+/// use clemitui::{ColumnAlign, Table, format_table};
+///
+/// colored::control::set_override(false);
+/// let mut table = Table::new(["Name", "Score"])
+///     .with_alignments([ColumnAlign::Left, ColumnAlign::Right]);
+/// table.push_row(["alice", "7"]);
+/// assert_eq!(
+///     format_table(&table, 80),
+///     "┌───────┬───────┐\n\
+///      │ Name  │ Score │\n\
+///      ├───────┼───────┤\n\
+///      │ alice │     7 │\n\
+///      └───────┴───────┘\n"
+/// );
+/// colored::control::unset_override();
+/// ```
+pub fn format_table(table: &Table, max_width: usize) -> String {
+    let columns = table.headers.len();
+    if columns == 0 {
+        return String::new();
+    }
+    let glyphs = theme().box_glyphs;
+
+    let mut widths: Vec<usize> = (0..columns)
+        .map(|col| {
+            std::iter::once(&table.headers[col])
+                .chain(table.rows.iter().filter_map(|row| row.get(col)))
+                .map(|cell| display_width(cell))
+                .max()
+                .unwrap_or(0)
+                .max(1)
+        })
+        .collect();
+    let minimums: Vec<usize> = widths.iter().map(|w| (*w).min(MIN_COLUMN_WIDTH)).collect();
+    // Each column adds its padding and one border; plus the closing border
+    let total = |widths: &[usize]| widths.iter().sum::<usize>() + 3 * widths.len() + 1;
+    if total(&minimums) > max_width {
+        return format_records(table, max_width, &glyphs);
+    }
+    // Shrink down to the longest word first, so cells wrap between words,
+    // and only then split words
+    let longest_words: Vec<usize> = (0..columns)
+        .map(|col| {
+            std::iter::once(&table.headers[col])
+                .chain(table.rows.iter().filter_map(|row| row.get(col)))
+                .flat_map(|cell| cell.split_whitespace())
+                .map(display_width)
+                .max()
+                .unwrap_or(0)
+                .max(minimums[col])
+                .min(widths[col])
+        })
+        .collect();
+    for floors in [&longest_words, &minimums] {
+        while total(&widths) > max_width {
+            let Some(widest) = (0..columns)
+                .filter(|&col| widths[col] > floors[col])
+                .max_by_key(|&col| (widths[col] - floors[col], std::cmp::Reverse(col)))
+            else {
+                break;
+            };
+            widths[widest] -= 1;
+        }
+    }
+
+    let border = |left: char, mid: char, right: char| {
+        let segments: Vec<String> = widths
+            .iter()
+            .map(|w| glyphs.horizontal.to_string().repeat(w + 2))
+            .collect();
+        format!(
+            "{}\n",
+            format!("{left}{}{right}", segments.join(&mid.to_string())).dimmed()
+        )
+    };
+    let vertical = glyphs.vertical.to_string().dimmed().to_string();
+    let row_lines = |cells: &[String], header: bool| {
+        let wrapped: Vec<Vec<String>> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, &w)| wrap_cell(cell, w))
+            .collect();
+        let height = wrapped.iter().map(Vec::len).max().unwrap_or(1);
+        let mut out = String::new();
+        for line in 0..height {
+            out.push_str(&vertical);
+            for (col, lines) in wrapped.iter().enumerate() {
+                let text = lines.get(line).map_or("", String::as_str);
+                let text = if header && !text.is_empty() {
+                    text.bold().to_string()
+                } else {
+                    text.to_string()
+                };
+                let align = table.alignments.get(col).copied().unwrap_or_default();
+                out.push_str(&format!(" {} {}", pad(&text, widths[col], align), vertical));
+            }
+            out.push('\n');
+        }
+        out
+    };
+
+    let mut out = border(glyphs.top_left, glyphs.top_tee, glyphs.top_right);
+    out.push_str(&row_lines(&table.headers, true));
+    out.push_str(&border(glyphs.left_tee, glyphs.cross, glyphs.right_tee));
+    for row in &table.rows {
+        out.push_str(&row_lines(row, false));
+    }
+    out.push_str(&border(
+        glyphs.bottom_left,
+        glyphs.bottom_tee,
+        glyphs.bottom_right,
+    ));
+    out
+}
+
+/// Each row as `header │ value` lines, records separated by a rule.
+fn format_records(table: &Table, max_width: usize, glyphs: &BoxGlyphs) -> String {
+    let label_width = table
+        .headers
+        .iter()
+        .map(|header| display_width(header))
+        .max()
+        .unwrap_or(0)
+        .clamp(1, (max_width / 3).max(1));
+    let value_width = max_width.saturating_sub(label_width + 3).max(1);
+    let vertical = glyphs.vertical.to_string().dimmed();
+    let rule = format!(
+        "{}{}{}",
+        glyphs.horizontal.to_string().repeat(label_width + 1),
+        glyphs.cross,
+        glyphs.horizontal.to_string().repeat(value_width + 1)
+    )
+    .dimmed();
+
+    let mut out = String::new();
+    for (i, row) in table.rows.iter().enumerate() {
+        if i > 0 {
+            out.push_str(&format!("{rule}\n"));
+        }
+        for (header, cell) in table.headers.iter().zip(row) {
+            let label = truncate_end(header, label_width, "…").bold().to_string();
+            for (line, value) in wrap_cell(cell, value_width).iter().enumerate() {
+                let label = if line == 0 { label.as_str() } else { "" };
+                let line = format!(
+                    "{} {} {}",
+                    pad(label, label_width, ColumnAlign::Left),
+                    vertical,
+                    value
+                );
+                out.push_str(line.trim_end());
+                out.push('\n');
+            }
+        }
+    }
+    out
+}
+
+/// Pad styled `text` to `width` columns.
+fn pad(text: &str, width: usize, align: ColumnAlign) -> String {
+    let space = width.saturating_sub(display_width(text));
+    let (left, right) = match align {
+        ColumnAlign::Left => (0, space),
+        ColumnAlign::Right => (space, 0),
+        ColumnAlign::Center => (space / 2, space - space / 2),
+    };
+    format!("{}{text}{}", " ".repeat(left), " ".repeat(right))
+}
+
+/// Word-wrap styled text to `width` columns, splitting words that are too
+/// long. Styling and hyperlinks open at a line break are closed at the end
+/// of the line and reopened on the next one.
+fn wrap_cell(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+
+    // Words as (piece, width) runs; escapes are zero-width pieces
+    let mut words: Vec<Vec<(&str, usize)>> = vec![Vec::new()];
+    for (segment, is_escape) in segments(text) {
+        if is_escape {
+            words.last_mut().unwrap().push((segment, 0));
+            continue;
+        }
+        for grapheme in segment.graphemes(true) {
+            if grapheme.chars().all(char::is_whitespace) {
+                words.push(Vec::new());
+            } else {
+                words
+                    .last_mut()
+                    .unwrap()
+                    .push((grapheme, display_width(grapheme)));
+            }
+        }
+    }
+
+    let mut lines: Vec<String> = vec![String::new()];
+    let mut line_width = 0;
+    for word in words {
+        let word_width: usize = word.iter().map(|(_, w)| w).sum();
+        if word_width == 0 {
+            // Keep escapes even when the word has no visible text
+            lines
+                .last_mut()
+                .unwrap()
+                .extend(word.iter().map(|(s, _)| *s));
+            continue;
+        }
+        if line_width > 0 && line_width + 1 + word_width <= width {
+            lines.last_mut().unwrap().push(' ');
+            line_width += 1;
+        } else if line_width > 0 {
+            lines.push(String::new());
+            line_width = 0;
+        }
+        for (piece, piece_width) in word {
+            if line_width + piece_width > width && line_width > 0 {
+                lines.push(String::new());
+                line_width = 0;
+            }
+            lines.last_mut().unwrap().push_str(piece);
+            line_width += piece_width;
+        }
+    }
+    carry_styles(lines)
+}
+
+/// Close styles and hyperlinks at the end of each line and reopen them at
+/// the start of the next.
+fn carry_styles(lines: Vec<String>) -> Vec<String> {
+    if lines.len() < 2 {
+        return lines;
+    }
+    let mut sgr: Vec<String> = Vec::new();
+    let mut link: Option<String> = None;
+    lines
+        .into_iter()
+        .map(|line| {
+            let mut out = sgr.concat();
+            out.extend(link.clone());
+            out.push_str(&line);
+            for (segment, is_escape) in segments(&line) {
+                if !is_escape {
+                    continue;
+                }
+                if segment == "\x1b[0m" || segment == "\x1b[m" {
+                    sgr.clear();
+                } else if segment.starts_with("\x1b[") && segment.ends_with('m') {
+                    sgr.push(segment.to_string());
+                } else if let Some(params) = segment.strip_prefix("\x1b]8;") {
+                    let uri = params.split_once(';').map_or("", |(_, rest)| rest);
+                    let closed = uri
+                        .trim_end_matches(['\x07', '\\'])
+                        .trim_end_matches('\x1b');
+                    link = (!closed.is_empty()).then(|| segment.to_string());
+                }
+            }
+            if link.is_some() {
+                out.push_str("\x1b]8;;\x1b\\");
+            }
+            if !sgr.is_empty() {
+                out.push_str("\x1b[0m");
+            }
+            out
+        })
+        .collect()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Table {
+        Table::parse(
+            "| Crate | Purpose | Stars |\n\
+             |:------|:-------:|------:|\n\
+             | serde | serialization framework for Rust | 9000 |\n\
+             | `a|b` | escaped \\| pipe | 1 |",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_alignments_and_cells() {
+        let table = sample();
+        assert_eq!(table.headers, ["Crate", "Purpose", "Stars"]);
+        assert_eq!(
+            table.alignments,
+            [ColumnAlign::Left, ColumnAlign::Center, ColumnAlign::Right]
+        );
+        assert_eq!(table.rows[1], ["`a|b`", "escaped | pipe", "1"]);
+
+        assert!(
+            Table::parse("| a | b |\n| --- |").is_none(),
+            "column mismatch"
+        );
+        assert!(Table::parse("no pipes\n---").is_none());
+        assert!(Table::parse("| a |\n| x |").is_none(), "no delimiter");
+
+        let mut short = Table::parse("a | b\n--|--\n1 |").unwrap();
+        assert_eq!(short.rows, [["1", ""]]);
+        short.push_row(["x", "y", "z"]);
+        assert_eq!(short.rows[1], ["x", "y"]);
+    }
+
+    #[test]
+    fn test_shrinks_and_wraps_to_width() {
+        colored::control::set_override(false);
+        let out = format_table(&sample(), 40);
+        assert_eq!(
+            out,
+            "┌───────┬──────────────────────┬───────┐\n\
+             │ Crate │       Purpose        │ Stars │\n\
+             ├───────┼──────────────────────┼───────┤\n\
+             │ serde │    serialization     │  9000 │\n\
+             │       │  framework for Rust  │       │\n\
+             │ `a|b` │    escaped | pipe    │     1 │\n\
+             └───────┴──────────────────────┴───────┘\n"
+        );
+        assert!(out.lines().all(|line| display_width(line) <= 40));
+        colored::control::unset_override();
+    }
+
+    #[test]
+    fn test_falls_back_to_records() {
+        colored::control::set_override(false);
+        let out = format_table(&sample(), 20);
+        assert_eq!(
+            out,
+            concat!(
+                "Crate  │ serde\n",
+                "Purpo… │ serializati\n",
+                "       │ on\n",
+                "       │ framework\n",
+                "       │ for Rust\n",
+                "Stars  │ 9000\n",
+                "───────┼────────────\n",
+                "Crate  │ `a|b`\n",
+                "Purpo… │ escaped |\n",
+                "       │ pipe\n",
+                "Stars  │ 1\n",
+            )
+        );
+        assert!(out.lines().all(|line| display_width(line) <= 20));
+        colored::control::unset_override();
+    }
+
+    #[test]
+    fn test_wrap_cell_carries_styles() {
+        assert_eq!(wrap_cell("", 5), [""]);
+        assert_eq!(wrap_cell("abcdefgh", 3), ["abc", "def", "gh"]);
+        assert_eq!(wrap_cell("日本語", 4), ["日本", "語"]);
+        assert_eq!(
+            wrap_cell("\x1b[1mbold words\x1b[0m end", 5),
+            ["\x1b[1mbold\x1b[0m", "\x1b[1mwords\x1b[0m", "end"]
+        );
+        let link = "\x1b]8;;https://a.io\x1b\\two words\x1b]8;;\x1b\\";
+        assert_eq!(
+            wrap_cell(link, 5),
+            [
+                "\x1b]8;;https://a.io\x1b\\two\x1b]8;;\x1b\\",
+                "\x1b]8;;https://a.io\x1b\\words\x1b]8;;\x1b\\"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_and_restore() {
+        let text = "Intro\n| a | b |\n|---|---|\n| 1 | 2 |\nafter\n```\n| x | y |\n|---|---|\n```";
+        let (out, tables) = extract_tables(text);
+        assert_eq!(tables.len(), 1);
+        assert_eq!(
+            out,
+            "Intro\n\u{E002}0\u{E003}\nafter\n```\n| x | y |\n|---|---|\n```"
+        );
+
+        colored::control::set_override(false);
+        let restored = restore_tables(&out, &tables, 80, |cell| format!("<{cell}>"));
+        assert!(restored.contains("│ <a> │ <b> │\n"), "got: {restored}");
+        assert!(restored.starts_with("Intro\n┌─────┬─────┐\n"));
+        assert!(restored.contains("└─────┴─────┘\nafter\n"));
+        colored::control::unset_override();
+    }
+}
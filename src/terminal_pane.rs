@@ -0,0 +1,696 @@
+//! Live output of embedded ACP terminals.
+//!
+//! ACP tool calls can embed `terminal` content, whose output the client reads
+//! with `terminal/output`. Commands write to that terminal as if it were a
+//! real one: progress bars redraw with `\r`, status lines move the cursor and
+//! clear lines. A [`VirtualScreen`] interprets that stream, and a
+//! [`TerminalPane`] shows its last rows in a bounded live window under the
+//! tool call, replaced by a folded snapshot when the command exits.
+//!
+//! # Usage
+//!
+//! ```
+//! use clemitui::TerminalPane;
+//!
+//! let mut pane = TerminalPane::new().with_window_rows(6);
+//! // For each chunk of output, redraw the live window
+//! print!("{}", pane.push("Downloading  40%\rDownloading 100%\n"));
+//! // When the command exits, replace the window with the final snapshot
+//! print!("{}", pane.finish());
+//! ```
+
+use colored::Colorize;
+use unicode_width::UnicodeWidthChar;
+
+use crate::format::{OutputPreviewConfig, format_tool_output};
+use crate::live_region::LiveRegion;
+
+/// Tab stop interval.
+const TAB_WIDTH: usize = 8;
+
+/// Lines kept above the screen before the oldest are dropped.
+const MAX_SCROLLBACK: usize = 10_000;
+
+// ============================================================================
+// Virtual Screen
+// ============================================================================
+
+/// One character cell: a grapheme (empty for the right half of a wide
+/// character) and the style active when it was written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Cell {
+    text: String,
+    style: Style,
+}
+
+impl Cell {
+    fn blank() -> Self {
+        Self {
+            text: " ".to_string(),
+            style: Style::default(),
+        }
+    }
+}
+
+/// A foreground or background color set by SGR.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Color {
+    /// Palette index: 0-7 normal, 8-15 bright, 16-255 extended.
+    Indexed(u8),
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    /// SGR parameters selecting this color; `base` is 30 for the foreground
+    /// and 40 for the background.
+    fn params(self, base: u8) -> String {
+        match self {
+            Self::Indexed(n @ 0..=7) => (base + n).to_string(),
+            Self::Indexed(n @ 8..=15) => (base + 60 + n - 8).to_string(),
+            Self::Indexed(n) => format!("{};5;{}", base + 8, n),
+            Self::Rgb(r, g, b) => format!("{};2;{};{};{}", base + 8, r, g, b),
+        }
+    }
+}
+
+/// SGR attributes of a cell.
+///
+/// Applying SGR parameters updates this fixed-size state rather than
+/// accumulating sequences, so output that only ever closes styles with
+/// `39`/`22` instead of a full reset stays cheap to render.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Style {
+    fg: Option<Color>,
+    bg: Option<Color>,
+    /// Bit `n` is set while attribute `n` (1 bold to 9 strikethrough) is on.
+    attrs: u16,
+}
+
+impl Style {
+    fn is_plain(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Apply the parameters of an SGR sequence (`1;31`, `38;5;208`,
+    /// `38:2:255:0:0`).
+    fn apply(&mut self, params: &str) {
+        let mut codes = params
+            .split([';', ':'])
+            .map(|p| p.parse::<u16>().unwrap_or(0));
+        while let Some(code) = codes.next() {
+            match code {
+                0 => *self = Self::default(),
+                1..=9 => self.attrs |= 1 << if code == 6 { 5 } else { code },
+                22 => self.attrs &= !(1 << 1 | 1 << 2),
+                23..=29 => self.attrs &= !(1 << (code - 20)),
+                30..=37 => self.fg = Some(Color::Indexed((code - 30) as u8)),
+                38 => self.fg = extended_color(&mut codes).or(self.fg),
+                39 => self.fg = None,
+                40..=47 => self.bg = Some(Color::Indexed((code - 40) as u8)),
+                48 => self.bg = extended_color(&mut codes).or(self.bg),
+                49 => self.bg = None,
+                90..=97 => self.fg = Some(Color::Indexed((code - 90 + 8) as u8)),
+                100..=107 => self.bg = Some(Color::Indexed((code - 100 + 8) as u8)),
+                _ => {}
+            }
+        }
+    }
+
+    /// The canonical SGR sequence for this style; empty when plain.
+    fn sequence(&self) -> String {
+        if self.is_plain() {
+            return String::new();
+        }
+        let mut params: Vec<String> = (1..=9)
+            .filter(|n| self.attrs & (1 << n) != 0)
+            .map(|n| n.to_string())
+            .collect();
+        params.extend(self.fg.map(|color| color.params(30)));
+        params.extend(self.bg.map(|color| color.params(40)));
+        format!("\x1b[{}m", params.join(";"))
+    }
+}
+
+/// The color after a `38`/`48` parameter: `5;n` or `2;r;g;b`.
+fn extended_color(codes: &mut impl Iterator<Item = u16>) -> Option<Color> {
+    let mut component = || codes.next().map(|n| n.min(255) as u8);
+    match component()? {
+        5 => Some(Color::Indexed(component()?)),
+        2 => Some(Color::Rgb(component()?, component()?, component()?)),
+        _ => None,
+    }
+}
+
+/// Escape sequence parser state, kept across chunks.
+#[derive(Debug, Clone, Default)]
+enum ParseState {
+    #[default]
+    Ground,
+    Escape,
+    /// Two-byte escape with intermediates (`ESC ( B`); the next char ends it.
+    EscapeIntermediate,
+    Csi(String),
+    Osc {
+        escape: bool,
+    },
+}
+
+/// A terminal screen that interprets ANSI/VT100 output.
+///
+/// Supports printable text with wrapping and wide characters, `\r`, `\n`,
+/// backspace and tabs, cursor movement (`CUU`/`CUD`/`CUF`/`CUB`/`CNL`/`CPL`/
+/// `CHA`/`CUP`/`VPA`, save and restore), erase in display and line, and SGR
+/// colors, which are kept in the rendered lines. Other sequences, including
+/// OSC, are consumed and ignored. The screen is the last `height` lines of an
+/// unbounded buffer, so output that scrolls off remains in [`lines`].
+///
+/// [`lines`]: VirtualScreen::lines
+#[derive(Debug, Clone)]
+pub struct VirtualScreen {
+    width: usize,
+    height: usize,
+    lines: Vec<Vec<Cell>>,
+    row: usize,
+    col: usize,
+    saved: Option<(usize, usize)>,
+    style: Style,
+    state: ParseState,
+}
+
+impl VirtualScreen {
+    /// Create an empty screen `width` columns wide and `height` rows tall.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width: width.max(1),
+            height: height.max(1),
+            lines: Vec::new(),
+            row: 0,
+            col: 0,
+            saved: None,
+            style: Style::default(),
+            state: ParseState::Ground,
+        }
+    }
+
+    /// Screen width in columns.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Cursor position as (line, column), both zero-based, where the line
+    /// indexes [`lines`](Self::lines).
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.row, self.col)
+    }
+
+    /// Interpret a chunk of output. Escape sequences may be split across
+    /// chunks.
+    pub fn feed(&mut self, data: &str) {
+        for c in data.chars() {
+            match std::mem::take(&mut self.state) {
+                ParseState::Ground => self.ground(c),
+                ParseState::Escape => self.escape(c),
+                ParseState::EscapeIntermediate => {}
+                ParseState::Csi(mut params) => {
+                    if ('\x40'..='\x7e').contains(&c) {
+                        self.csi(&params, c);
+                    } else if c == '\x1b' {
+                        // Malformed; start over
+                        self.state = ParseState::Escape;
+                    } else {
+                        params.push(c);
+                        self.state = ParseState::Csi(params);
+                    }
+                }
+                ParseState::Osc { escape } => match c {
+                    '\x07' => {}
+                    '\\' if escape => {}
+                    _ => {
+                        self.state = ParseState::Osc {
+                            escape: c == '\x1b',
+                        }
+                    }
+                },
+            }
+        }
+    }
+
+    /// Rendered lines, with SGR styling and trailing blank lines removed.
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines: Vec<String> = self.lines.iter().map(|cells| render_cells(cells)).collect();
+        while lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+        lines
+    }
+
+    fn ground(&mut self, c: char) {
+        match c {
+            '\x1b' => self.state = ParseState::Escape,
+            '\n' | '\x0b' | '\x0c' => {
+                // Output read back from a terminal has usually gone through
+                // ONLCR already; a bare LF from a pipe also starts a new line
+                self.row += 1;
+                self.col = 0;
+                self.trim_scrollback();
+            }
+            '\r' => self.col = 0,
+            '\x08' => self.col = self.col.saturating_sub(1),
+            '\t' => self.col = ((self.col / TAB_WIDTH + 1) * TAB_WIDTH).min(self.width - 1),
+            c if c.is_control() => {}
+            c => self.print(c),
+        }
+    }
+
+    fn escape(&mut self, c: char) {
+        match c {
+            '[' => self.state = ParseState::Csi(String::new()),
+            ']' | 'P' | '_' | '^' => self.state = ParseState::Osc { escape: false },
+            '7' => self.save_cursor(),
+            '8' => self.restore_cursor(),
+            'D' => self.row += 1,
+            'E' => {
+                self.row += 1;
+                self.col = 0;
+            }
+            'M' => self.row = self.row.saturating_sub(1).max(self.top()),
+            '\x20'..='\x2f' => self.state = ParseState::EscapeIntermediate,
+            _ => {}
+        }
+    }
+
+    fn csi(&mut self, params: &str, action: char) {
+        if params.starts_with(['?', '>', '<', '=']) {
+            // Private modes (cursor visibility, bracketed paste, ...)
+            return;
+        }
+        let args: Vec<usize> = params.split(';').map(|p| p.parse().unwrap_or(0)).collect();
+        let arg = |i: usize, default: usize| match args.get(i) {
+            Some(&0) | None => default,
+            Some(&n) => n,
+        };
+        let top = self.top();
+        // Cursor movement stops at the bottom of the screen; only output scrolls
+        let bottom = (top + self.height - 1).max(self.row);
+        match action {
+            'A' => self.row = self.row.saturating_sub(arg(0, 1)).max(top),
+            'B' => self.row = (self.row + arg(0, 1)).min(bottom),
+            'C' => self.col = (self.col + arg(0, 1)).min(self.width - 1),
+            'D' => self.col = self.col.saturating_sub(arg(0, 1)),
+            'E' => {
+                self.row = (self.row + arg(0, 1)).min(bottom);
+                self.col = 0;
+            }
+            'F' => {
+                self.row = self.row.saturating_sub(arg(0, 1)).max(top);
+                self.col = 0;
+            }
+            'G' | '`' => self.col = (arg(0, 1) - 1).min(self.width - 1),
+            'H' | 'f' => {
+                self.row = top + (arg(0, 1) - 1).min(self.height - 1);
+                self.col = (arg(1, 1) - 1).min(self.width - 1);
+            }
+            'd' => self.row = top + (arg(0, 1) - 1).min(self.height - 1),
+            'J' => self.erase_display(args[0]),
+            'K' => self.erase_line(args[0]),
+            'm' => self.style.apply(params),
+            's' => self.save_cursor(),
+            'u' => self.restore_cursor(),
+            _ => {}
+        }
+        self.trim_scrollback();
+    }
+
+    fn erase_display(&mut self, mode: usize) {
+        match mode {
+            0 => {
+                self.lines.truncate(self.row + 1);
+                if let Some(line) = self.lines.get_mut(self.row) {
+                    line.truncate(self.col);
+                }
+            }
+            1 => {
+                let top = self.top();
+                for row in top..self.row.min(self.lines.len()) {
+                    self.lines[row].clear();
+                }
+                self.erase_line(1);
+            }
+            2 => self.lines.truncate(self.top()),
+            3 => {
+                // Scrollback only; the screen stays as it is
+                let top = self.top().min(self.lines.len());
+                self.lines.drain(..top);
+                self.row -= top;
+            }
+            _ => {}
+        }
+    }
+
+    fn erase_line(&mut self, mode: usize) {
+        let col = self.col;
+        let Some(line) = self.lines.get_mut(self.row) else {
+            return;
+        };
+        match mode {
+            0 => line.truncate(col),
+            1 => {
+                for cell in line.iter_mut().take(col + 1) {
+                    *cell = Cell::blank();
+                }
+            }
+            _ => line.clear(),
+        }
+    }
+
+    fn print(&mut self, c: char) {
+        let width = c.width().unwrap_or(0);
+        if width == 0 {
+            // Combining mark: join the previous cell
+            if let Some(cell) = self
+                .lines
+                .get_mut(self.row)
+                .and_then(|line| line.get_mut(self.col.checked_sub(1)?))
+            {
+                cell.text.push(c);
+            }
+            return;
+        }
+
+        if self.col + width > self.width {
+            self.row += 1;
+            self.col = 0;
+            self.trim_scrollback();
+        }
+        while self.lines.len() <= self.row {
+            self.lines.push(Vec::new());
+        }
+        let line = &mut self.lines[self.row];
+        while line.len() < self.col + width {
+            line.push(Cell::blank());
+        }
+        line[self.col] = Cell {
+            text: c.to_string(),
+            style: self.style,
+        };
+        if width == 2 {
+            line[self.col + 1] = Cell {
+                text: String::new(),
+                style: self.style,
+            };
+        }
+        self.col += width;
+    }
+
+    /// First buffer line of the screen.
+    fn top(&self) -> usize {
+        self.lines
+            .len()
+            .max(self.row + 1)
+            .saturating_sub(self.height)
+    }
+
+    fn save_cursor(&mut self) {
+        self.saved = Some((self.row - self.top(), self.col));
+    }
+
+    fn restore_cursor(&mut self) {
+        if let Some((row, col)) = self.saved {
+            self.row = self.top() + row;
+            self.col = col;
+        }
+    }
+
+    fn trim_scrollback(&mut self) {
+        let excess = (self.row + 1).saturating_sub(MAX_SCROLLBACK + self.height);
+        if excess > 0 {
+            self.lines.drain(..excess.min(self.lines.len()));
+            self.row -= excess;
+        }
+    }
+}
+
+/// Render a line of cells, switching SGR styles as they change and trimming
+/// trailing unstyled blanks.
+fn render_cells(cells: &[Cell]) -> String {
+    let end = cells
+        .iter()
+        .rposition(|cell| cell.text != " " || !cell.style.is_plain())
+        .map_or(0, |i| i + 1);
+    let mut out = String::new();
+    let mut style = Style::default();
+    for cell in &cells[..end] {
+        if cell.style != style {
+            if !style.is_plain() {
+                out.push_str("\x1b[0m");
+            }
+            out.push_str(&cell.style.sequence());
+            style = cell.style;
+        }
+        out.push_str(&cell.text);
+    }
+    if !style.is_plain() {
+        out.push_str("\x1b[0m");
+    }
+    out
+}
+
+// ============================================================================
+// Terminal Pane
+// ============================================================================
+
+/// Live window onto an embedded terminal, shown under its tool call.
+///
+/// Each [`push`](Self::push) returns output that redraws the last rows of the
+/// screen in place. [`finish`](Self::finish) replaces the window with the
+/// whole output, folded like [`format_tool_output`].
+#[derive(Debug, Clone)]
+pub struct TerminalPane {
+    screen: VirtualScreen,
+    window_rows: usize,
+    region: LiveRegion,
+    fed: String,
+    preview: OutputPreviewConfig,
+}
+
+impl Default for TerminalPane {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TerminalPane {
+    /// Create a pane as wide as the terminal (less the `│ ` gutter) with an
+    /// 8-row window.
+    pub fn new() -> Self {
+        let columns = crossterm::terminal::size().map_or(80, |(columns, _)| columns as usize);
+        Self {
+            screen: VirtualScreen::new(columns.saturating_sub(2).max(20), 24),
+            window_rows: 8,
+            region: LiveRegion::new(),
+            fed: String::new(),
+            preview: OutputPreviewConfig::default(),
+        }
+    }
+
+    /// Set the virtual screen size the command sees.
+    pub fn with_size(mut self, width: usize, height: usize) -> Self {
+        self.screen = VirtualScreen::new(width, height);
+        self
+    }
+
+    /// Set how many rows the live window shows.
+    pub fn with_window_rows(mut self, rows: usize) -> Self {
+        self.window_rows = rows.max(1);
+        self
+    }
+
+    /// Set how the final snapshot is folded.
+    pub fn with_preview(mut self, preview: OutputPreviewConfig) -> Self {
+        self.preview = preview;
+        self
+    }
+
+    /// Use a fixed terminal size (columns, rows) for redrawing instead of
+    /// querying the terminal.
+    pub fn with_terminal_size(mut self, columns: u16, rows: u16) -> Self {
+        self.region = self.region.with_terminal_size(columns, rows);
+        self
+    }
+
+    /// The interpreted screen.
+    pub fn screen(&self) -> &VirtualScreen {
+        &self.screen
+    }
+
+    /// Feed a chunk of output and return the redrawn live window.
+    pub fn push(&mut self, output: &str) -> String {
+        self.screen.feed(output);
+        self.fed.push_str(output);
+        self.redraw()
+    }
+
+    /// Set the full output so far, as returned by ACP `terminal/output`, and
+    /// return the redrawn live window. Only new output is interpreted when
+    /// `output` extends what was seen before.
+    pub fn set_output(&mut self, output: &str) -> String {
+        match output.strip_prefix(self.fed.as_str()) {
+            Some(new) => {
+                let new = new.to_string();
+                self.push(&new)
+            }
+            None => {
+                // Truncated from the start, or replaced: start over
+                self.screen = VirtualScreen::new(self.screen.width, self.screen.height);
+                self.fed.clear();
+                self.push(output)
+            }
+        }
+    }
+
+    /// Replace the live window with the final snapshot of the output.
+    pub fn finish(&mut self) -> String {
+        let mut out = self.region.clear();
+        let snapshot = format_tool_output(&self.screen.lines().join("\n"), &self.preview);
+        if !snapshot.is_empty() {
+            out.push_str(&snapshot);
+            out.push('\n');
+        }
+        out
+    }
+
+    fn redraw(&mut self) -> String {
+        let lines = self.screen.lines();
+        if lines.is_empty() {
+            return self.region.clear();
+        }
+        let start = lines.len().saturating_sub(self.window_rows);
+        let window: String = lines[start..]
+            .iter()
+            .map(|line| format!("{} {}\n", "│".dimmed(), line))
+            .collect();
+        self.region.update(&window)
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen(data: &str) -> Vec<String> {
+        let mut screen = VirtualScreen::new(20, 5);
+        screen.feed(data);
+        screen.lines()
+    }
+
+    #[test]
+    fn test_carriage_return_progress() {
+        assert_eq!(screen("  0%\r 50%\r100%\ndone\n"), vec!["100%", "done"]);
+        assert_eq!(screen("abcdef\rxy"), vec!["xycdef"]);
+    }
+
+    #[test]
+    fn test_cursor_movement_and_erase() {
+        // Redraw the line above, as multi-line progress displays do
+        assert_eq!(
+            screen("step 1\nstep 2\n\x1b[2Aupdated\x1b[K\n"),
+            vec!["updated", "step 2"]
+        );
+        assert_eq!(screen("hello world\x1b[6D\x1b[K!"), vec!["hello!"]);
+        assert_eq!(screen("a\nb\nc\x1b[2;1Hx\x1b[3G!"), vec!["a", "x !", "c"]);
+        assert_eq!(screen("a\nb\x1b[2J\x1b[Hfresh"), vec!["fresh"]);
+        // Clearing the scrollback keeps the screen
+        assert_eq!(
+            screen("1\n2\n3\n4\n5\n6\n7\x1b[3J"),
+            vec!["3", "4", "5", "6", "7"]
+        );
+        assert_eq!(screen("old\n\x1b[1F\x1b[2Knew"), vec!["new"]);
+        assert_eq!(screen("ab\x1b7cd\x1b8X"), vec!["abXd"]);
+        assert_eq!(screen("a\x1b[99Bb"), vec!["a", "", "", "", " b"]);
+    }
+
+    #[test]
+    fn test_wrapping_and_wide_characters() {
+        assert_eq!(screen(&"x".repeat(25)), vec!["x".repeat(20), "x".repeat(5)]);
+        // Exactly full line followed by a newline does not leave a blank line
+        assert_eq!(screen(&format!("{}\nnext", "y".repeat(20))).len(), 2);
+        assert_eq!(
+            screen(&format!("{}日本", "z".repeat(19))),
+            vec!["z".repeat(19), "日本".to_string()]
+        );
+        assert_eq!(screen("e\u{301}!"), vec!["e\u{301}!"]);
+        assert_eq!(screen("a\tb"), vec!["a       b"]);
+    }
+
+    #[test]
+    fn test_styles_are_kept() {
+        assert_eq!(
+            screen("\x1b[31mred\x1b[0m plain \x1b[1;32mok\x1b[m"),
+            vec!["\x1b[31mred\x1b[0m plain \x1b[1;32mok\x1b[0m"]
+        );
+    }
+
+    #[test]
+    fn test_styles_closed_without_reset() {
+        // chalk-style output: each style is closed with its own off code
+        let line = "\x1b[1m\x1b[32mPASS\x1b[39m\x1b[22m x ".repeat(2);
+        assert_eq!(
+            screen(&format!("{line}\x1b[38;5;208mwarn\x1b[39m")),
+            vec!["\x1b[1;32mPASS\x1b[0m x \x1b[1;32mPASS\x1b[0m x \x1b[38;5;208mwarn\x1b[0m"]
+        );
+
+        let mut style = Style::default();
+        for _ in 0..1000 {
+            style.apply("1;32");
+            style.apply("39;22");
+        }
+        assert!(style.is_plain());
+        style.apply("38:2:255:128:0;48;5;17;4;7;27");
+        assert_eq!(style.sequence(), "\x1b[4;38;2;255;128;0;48;5;17m");
+        style.apply("97;104");
+        assert_eq!(style.sequence(), "\x1b[4;97;104m");
+    }
+
+    #[test]
+    fn test_sequences_split_across_chunks() {
+        let mut screen = VirtualScreen::new(20, 5);
+        for chunk in ["50%", "\x1b", "[", "2K\r", "\x1b]0;ti", "tle\x07", "100%"] {
+            screen.feed(chunk);
+        }
+        assert_eq!(screen.lines(), vec!["100%"]);
+        assert_eq!(screen.cursor(), (0, 4));
+    }
+
+    #[test]
+    fn test_pane_window_is_bounded_and_redrawn() {
+        colored::control::set_override(false);
+        let mut pane = TerminalPane::new()
+            .with_size(40, 10)
+            .with_window_rows(2)
+            .with_terminal_size(80, 24);
+        assert_eq!(pane.push(""), "");
+        assert_eq!(pane.push("one\n"), "│ one\n");
+        assert_eq!(pane.push("two\nthree\n"), "\x1b[1F\x1b[J│ two\n│ three\n");
+        assert_eq!(pane.finish(), "\x1b[2F\x1b[J   one\n   two\n   three\n");
+        colored::control::unset_override();
+    }
+
+    #[test]
+    fn test_set_output_feeds_only_new_output() {
+        colored::control::set_override(false);
+        let mut pane = TerminalPane::new()
+            .with_size(40, 10)
+            .with_terminal_size(80, 24);
+        pane.set_output("10%");
+        pane.set_output("10%\r20%");
+        assert_eq!(pane.screen().lines(), vec!["20%"]);
+        // Output truncated from the start: reinterpreted from scratch
+        pane.set_output("30%");
+        assert_eq!(pane.screen().lines(), vec!["30%"]);
+        colored::control::unset_override();
+    }
+}
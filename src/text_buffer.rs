@@ -2,10 +2,22 @@
 //!
 //! The [`TextBuffer`] collects text chunks from streaming responses and
 //! renders them with markdown formatting when flushed.
+//!
+//! Markdown links (`[text](url)`), bare URLs and file paths that exist
+//! relative to the working directory are emitted as OSC 8 hyperlinks when the
+//! terminal supports them (see [`LinkConfig`]). Without hyperlink support,
+//! markdown links fall back to `text (url)`.
 
+use std::env;
+use std::path::Path;
 use std::sync::LazyLock;
+
+use regex::{Captures, Regex};
 use termimad::MadSkin;
 
+use crate::hyperlink::{LinkConfig, link_config, osc8};
+use crate::table::{extract_tables, restore_tables};
+
 // ============================================================================
 // Markdown Rendering
 // ============================================================================
@@ -26,6 +38,212 @@ pub(crate) fn render_markdown_nowrap(text: &str) -> String {
     FmtText::from(&SKIN, text, Some(10000)).to_string()
 }
 
+// ============================================================================
+// Links
+// ============================================================================
+
+/// Markdown links, bare URLs and path-like tokens, in priority order.
+static LINK_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"\[(?P<text>[^\]\n]+)\]\((?P<url>[^)\s]+)\)|(?P<bare>https?://[^\s<>()\[\]`]+)|(?P<path>[\w.~/-]*[\w~]/[\w.~/-]*|[\w-]+\.[A-Za-z0-9]+)(?::(?P<line>\d+))?",
+    )
+    .expect("link pattern is valid")
+});
+
+/// File extensions that make a dotted word look like a path worth checking.
+const PATH_EXTENSIONS: &[&str] = &[
+    "c", "cc", "cfg", "conf", "cpp", "cs", "css", "csv", "go", "h", "hpp", "html", "ini", "java",
+    "js", "json", "jsx", "kt", "lock", "log", "lua", "md", "mjs", "php", "proto", "py", "rb", "rs",
+    "scss", "sh", "sql", "svelte", "swift", "toml", "ts", "tsx", "txt", "vue", "xml", "yaml",
+    "yml", "zig",
+];
+
+/// Placeholders survive termimad untouched, so link escapes can be swapped in
+/// after rendering without being styled or split.
+const PLACEHOLDER_START: char = '\u{E000}';
+const PLACEHOLDER_END: char = '\u{E001}';
+
+/// Trailing characters that end a sentence rather than a URL or path.
+const TRAILING_PUNCTUATION: &[char] = &['.', ',', ';', ':', '!', '?', '\'', '"'];
+
+/// Replace links in `text` with placeholders, returning the rewritten text and
+/// the replacement for each placeholder. Code blocks and inline code are left
+/// untouched.
+fn extract_links(text: &str, config: &LinkConfig, cwd: Option<&Path>) -> (String, Vec<String>) {
+    let enabled = config.links_enabled();
+    let mut links = Vec::new();
+    let mut out = String::with_capacity(text.len());
+    let mut fence: Option<&str> = None;
+
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+            out.push_str(line);
+            continue;
+        }
+        if let Some(marker) = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m)) {
+            fence = Some(marker);
+            out.push_str(line);
+            continue;
+        }
+
+        // Odd segments between backticks are inline code
+        for (i, segment) in line.split('`').enumerate() {
+            if i > 0 {
+                out.push('`');
+            }
+            if i % 2 == 1 {
+                out.push_str(segment);
+                continue;
+            }
+            let replaced = LINK_RE.replace_all(segment, |caps: &Captures| {
+                match link_for(caps, config, enabled, cwd) {
+                    Some((link, rest)) => {
+                        links.push(link);
+                        format!(
+                            "{PLACEHOLDER_START}{}{PLACEHOLDER_END}{rest}",
+                            links.len() - 1
+                        )
+                    }
+                    None => caps[0].to_string(),
+                }
+            });
+            out.push_str(&replaced);
+        }
+    }
+
+    (out, links)
+}
+
+/// Rendered link for a match, plus any trailing text that is not part of it.
+fn link_for(
+    caps: &Captures,
+    config: &LinkConfig,
+    enabled: bool,
+    cwd: Option<&Path>,
+) -> Option<(String, String)> {
+    if let (Some(text), Some(url)) = (caps.name("text"), caps.name("url")) {
+        let (text, url) = (text.as_str(), url.as_str());
+        let is_uri = url.contains("://") || url.starts_with("mailto:");
+        let link = match (enabled, is_uri) {
+            (true, true) => osc8(url, text),
+            (true, false) => match existing_path(url, cwd) {
+                Some(path) => config.link(path, None, text),
+                None => format!("{text} ({url})"),
+            },
+            _ if text == url => url.to_string(),
+            _ => format!("{text} ({url})"),
+        };
+        return Some((link, String::new()));
+    }
+
+    if let Some(bare) = caps.name("bare") {
+        let url = bare.as_str().trim_end_matches(TRAILING_PUNCTUATION);
+        let rest = &bare.as_str()[url.len()..];
+        let link = if enabled {
+            osc8(url, url)
+        } else {
+            url.to_string()
+        };
+        return Some((link, rest.to_string()));
+    }
+
+    let token = caps.name("path")?.as_str();
+    let line = caps.name("line").and_then(|m| m.as_str().parse().ok());
+    let path = if line.is_some() {
+        token
+    } else {
+        token.trim_end_matches(TRAILING_PUNCTUATION)
+    };
+    if !enabled || !looks_like_path(path) {
+        return None;
+    }
+    let resolved = existing_path(path, cwd)?;
+    let text = &caps[0][..caps[0].len() - (token.len() - path.len())];
+    let rest = &token[path.len()..];
+    Some((config.link(resolved, line, text), rest.to_string()))
+}
+
+/// Whether `token` is shaped like a path: explicitly relative or absolute, or
+/// ending in a known file extension. Words like "e.g", "v1.2" or "and/or"
+/// are not, so they never cost a filesystem lookup.
+fn looks_like_path(token: &str) -> bool {
+    if token.starts_with(['/', '~']) || token.starts_with("./") || token.starts_with("../") {
+        return true;
+    }
+    let name = token.rsplit('/').next().unwrap_or(token);
+    name.rsplit_once('.').is_some_and(|(stem, extension)| {
+        !stem.is_empty()
+            && PATH_EXTENSIONS
+                .iter()
+                .any(|known| extension.eq_ignore_ascii_case(known))
+    })
+}
+
+/// `path` resolved against `cwd` if it names an existing file or directory.
+fn existing_path(path: &str, cwd: Option<&Path>) -> Option<std::path::PathBuf> {
+    let resolved = cwd?.join(path);
+    resolved.exists().then_some(resolved)
+}
+
+/// Swap placeholders in rendered output for their links.
+fn restore_links(rendered: &str, links: &[String]) -> String {
+    if links.is_empty() {
+        return rendered.to_string();
+    }
+    let mut out = String::with_capacity(rendered.len());
+    let mut rest = rendered;
+    while let Some(start) = rest.find(PLACEHOLDER_START) {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + PLACEHOLDER_START.len_utf8()..];
+        let link = after.find(PLACEHOLDER_END).and_then(|end| {
+            let link = links.get(after[..end].parse::<usize>().ok()?)?;
+            Some((link, end))
+        });
+        match link {
+            Some((link, end)) => {
+                out.push_str(link);
+                rest = &after[end + PLACEHOLDER_END.len_utf8()..];
+            }
+            None => {
+                out.push(PLACEHOLDER_START);
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Render markdown like [`render_markdown_nowrap`], linking URLs and existing
+/// paths according to `config`.
+pub(crate) fn render_markdown_linked(
+    text: &str,
+    config: &LinkConfig,
+    cwd: Option<&Path>,
+) -> String {
+    let (text, links) = extract_links(text, config, cwd);
+    restore_links(&render_markdown_nowrap(&text), &links)
+}
+
+/// Render markdown like [`render_markdown_linked`], drawing tables with
+/// borders fitted to `width` columns.
+pub(crate) fn render_markdown(
+    text: &str,
+    config: &LinkConfig,
+    cwd: Option<&Path>,
+    width: usize,
+) -> String {
+    let (text, tables) = extract_tables(text);
+    let rendered = render_markdown_linked(&text, config, cwd);
+    restore_tables(&rendered, &tables, width, |cell| {
+        render_markdown_linked(cell, config, cwd).trim().to_string()
+    })
+}
+
 // ============================================================================
 // Text Buffer
 // ============================================================================
@@ -35,6 +253,9 @@ pub(crate) fn render_markdown_nowrap(text: &str) -> String {
 /// Text is buffered via `push()` during streaming, then flushed with markdown
 /// rendering at logical boundaries (e.g., before tool execution, on completion).
 /// The `flush()` method normalizes trailing newlines to exactly `\n\n`.
+/// Links are rendered according to the global [`LinkConfig`].
+/// Tables are drawn with box borders and fitted to the terminal width (see
+/// [`format_table`](crate::format_table)).
 ///
 /// # Example
 ///
@@ -53,28 +274,41 @@ pub(crate) fn render_markdown_nowrap(text: &str) -> String {
 /// assert!(buffer.is_empty());
 /// ```
 #[derive(Debug, Default)]
-pub struct TextBuffer(String);
+pub struct TextBuffer {
+    text: String,
+    width: Option<usize>,
+}
 
 impl TextBuffer {
     /// Create a new empty text buffer.
     pub fn new() -> Self {
-        Self(String::new())
+        Self::default()
+    }
+
+    /// Fit tables to `columns` instead of the terminal width.
+    pub fn with_width(mut self, columns: usize) -> Self {
+        self.width = Some(columns);
+        self
     }
 
     /// Append text to the buffer.
     pub fn push(&mut self, text: &str) {
-        self.0.push_str(text);
+        self.text.push_str(text);
     }
 
     /// Flush buffered text with markdown rendering, normalized to `\n\n`.
     /// Returns rendered text, or None if buffer was empty or whitespace-only.
     pub fn flush(&mut self) -> Option<String> {
-        if self.0.is_empty() {
+        if self.text.is_empty() {
             return None;
         }
 
-        let text = std::mem::take(&mut self.0);
-        let rendered = render_markdown_nowrap(&text);
+        let text = std::mem::take(&mut self.text);
+        let cwd = env::current_dir().ok();
+        let width = self.width.unwrap_or_else(|| {
+            crossterm::terminal::size().map_or(80, |(columns, _)| columns as usize)
+        });
+        let rendered = render_markdown(&text, &link_config(), cwd.as_deref(), width);
 
         // Normalize trailing newlines to exactly \n\n
         let trimmed = rendered.trim_end_matches('\n');
@@ -87,7 +321,7 @@ impl TextBuffer {
 
     /// Check if the buffer is empty.
     pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+        self.text.is_empty()
     }
 }
 
@@ -98,6 +332,8 @@ impl TextBuffer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::hyperlink::EditorScheme;
+    use crate::width::{display_width, strip_ansi};
 
     #[test]
     fn test_text_buffer_accumulates() {
@@ -202,6 +438,147 @@ mod tests {
         assert!(rendered.contains("plain text"));
     }
 
+    fn links_on() -> LinkConfig {
+        LinkConfig::default().with_enabled(true)
+    }
+
+    fn links_off() -> LinkConfig {
+        LinkConfig::default().with_enabled(false)
+    }
+
+    fn crate_dir() -> &'static Path {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+    }
+
+    #[test]
+    fn test_markdown_link_emits_osc8() {
+        let out = render_markdown_linked("see [docs](https://example.com/a_b)", &links_on(), None);
+        assert!(
+            out.contains("see \x1b]8;;https://example.com/a_b\x1b\\docs\x1b]8;;\x1b\\"),
+            "got: {:?}",
+            out
+        );
+        assert!(!out.contains('\u{E000}'));
+    }
+
+    #[test]
+    fn test_markdown_link_fallback() {
+        let out = render_markdown_linked("see [docs](https://example.com)", &links_off(), None);
+        assert!(
+            out.contains("see docs (https://example.com)"),
+            "got: {:?}",
+            out
+        );
+        assert!(!out.contains("\x1b]8"));
+
+        let out = render_markdown_linked("[https://a.io](https://a.io)", &links_off(), None);
+        assert!(
+            out.contains("https://a.io") && !out.contains("("),
+            "got: {:?}",
+            out
+        );
+    }
+
+    #[test]
+    fn test_bare_url_trailing_punctuation() {
+        let out = render_markdown_linked("visit https://example.com/x.", &links_on(), None);
+        assert!(
+            out.contains("\x1b]8;;https://example.com/x\x1b\\https://example.com/x\x1b]8;;\x1b\\."),
+            "got: {:?}",
+            out
+        );
+
+        let out = render_markdown_linked("visit https://example.com/x.", &links_off(), None);
+        assert!(
+            out.contains("visit https://example.com/x."),
+            "got: {:?}",
+            out
+        );
+    }
+
+    #[test]
+    fn test_existing_paths_are_linked() {
+        let out = render_markdown_linked(
+            "edit src/text_buffer.rs:12, not src/missing.rs or Cargo.toml.",
+            &links_on(),
+            Some(crate_dir()),
+        );
+        let uri = EditorScheme::File.uri(crate_dir().join("src/text_buffer.rs"), Some(12));
+        assert!(
+            out.contains(&osc8(&uri, "src/text_buffer.rs:12")),
+            "got: {:?}",
+            out
+        );
+        assert!(out.contains(&osc8(
+            &EditorScheme::File.uri(crate_dir().join("Cargo.toml"), None),
+            "Cargo.toml"
+        )));
+        assert!(out.contains("Cargo.toml\x1b]8;;\x1b\\."));
+        assert!(out.contains("not src/missing.rs or"));
+
+        let out =
+            render_markdown_linked("edit src/text_buffer.rs", &links_off(), Some(crate_dir()));
+        assert!(!out.contains("\x1b]8"));
+        assert!(out.contains("edit src/text_buffer.rs"));
+    }
+
+    #[test]
+    fn test_only_path_shaped_words_are_linked() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("and")).unwrap();
+        for name in ["and/or", "e.g", "v1.2", "notes.md"] {
+            std::fs::write(dir.path().join(name), "").unwrap();
+        }
+        let (_, links) = extract_links(
+            "Use and/or, e.g v1.2 or 1/2.",
+            &links_on(),
+            Some(dir.path()),
+        );
+        assert!(links.is_empty(), "got: {:?}", links);
+        let (_, links) = extract_links("See notes.md and ./and/or", &links_on(), Some(dir.path()));
+        assert_eq!(links.len(), 2, "got: {:?}", links);
+
+        assert!(looks_like_path("src/lib.rs"));
+        assert!(looks_like_path("README.MD"));
+        assert!(looks_like_path("../docs"));
+        assert!(!looks_like_path("src/components"));
+        assert!(!looks_like_path(".rs"));
+    }
+
+    #[test]
+    fn test_code_is_not_linked() {
+        let text = "`https://a.io` and\n```\nhttps://b.io [x](https://c.io)\n```\nhttps://d.io\n";
+        let (out, links) = extract_links(text, &links_on(), None);
+        assert_eq!(links.len(), 1);
+        assert!(out.contains("`https://a.io`"));
+        assert!(out.contains("https://b.io [x](https://c.io)"));
+        assert!(!out.contains("https://d.io"));
+    }
+
+    #[test]
+    fn test_tables_fit_width() {
+        colored::control::set_override(false);
+        let mut buffer = TextBuffer::new().with_width(30);
+        buffer.push(
+            "Compare:\n\n| Tool | Notes |\n|---|---|\n| `rg` | fast recursive search tool |\n",
+        );
+        let out = strip_ansi(&buffer.flush().unwrap()).into_owned();
+        colored::control::unset_override();
+        assert_eq!(
+            out,
+            concat!(
+                "Compare:\n\n",
+                "┌──────┬─────────────────────┐\n",
+                "│ Tool │ Notes               │\n",
+                "├──────┼─────────────────────┤\n",
+                "│ rg   │ fast recursive      │\n",
+                "│      │ search tool         │\n",
+                "└──────┴─────────────────────┘\n\n",
+            )
+        );
+        assert!(out.lines().all(|line| display_width(line) <= 30));
+    }
+
     #[test]
     fn test_render_markdown_nowrap_headers() {
         // Headers should be rendered (SKIN left-aligns them)
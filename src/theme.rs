@@ -0,0 +1,367 @@
+//! Tool kinds and display theme.
+//!
+//! ACP tool calls carry a [`ToolKind`] describing what the tool does (read,
+//! edit, execute, ...). Formatters use it to pick an icon and a color from the
+//! active [`Theme`], so tools the client has never seen (such as MCP tools)
+//! still render meaningfully.
+//!
+//! # Usage
+//!
+//! ```
+//! use clemitui::{Theme, ToolKind, set_theme};
+//! use colored::Color;
+//! use std::sync::Arc;
+//!
+//! let theme = Theme::default()
+//!     .with_tool_kind_color(ToolKind::Execute, Color::BrightGreen)
+//!     .with_kind_style(clemitui::KindStyle::Labels);
+//! set_theme(Arc::new(theme));
+//! ```
+
+use std::sync::{Arc, LazyLock, RwLock};
+
+use colored::Color;
+use serde::Deserialize;
+
+// ============================================================================
+// Tool Kinds
+// ============================================================================
+
+/// Category of a tool call, as reported in ACP `tool_call` updates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolKind {
+    /// Reading files or data.
+    Read,
+    /// Modifying files or content.
+    Edit,
+    /// Removing files or data.
+    Delete,
+    /// Moving or renaming files.
+    Move,
+    /// Searching for information.
+    Search,
+    /// Running commands or code.
+    Execute,
+    /// Internal reasoning or planning.
+    Think,
+    /// Retrieving external data.
+    Fetch,
+    /// Anything else, including kinds this version does not know.
+    #[default]
+    #[serde(other)]
+    Other,
+}
+
+impl ToolKind {
+    /// All kinds, in declaration order.
+    pub const ALL: [ToolKind; 9] = [
+        ToolKind::Read,
+        ToolKind::Edit,
+        ToolKind::Delete,
+        ToolKind::Move,
+        ToolKind::Search,
+        ToolKind::Execute,
+        ToolKind::Think,
+        ToolKind::Fetch,
+        ToolKind::Other,
+    ];
+
+    /// Parse an ACP kind string. Unknown kinds map to [`ToolKind::Other`].
+    pub fn parse(kind: &str) -> Self {
+        match kind {
+            "read" => Self::Read,
+            "edit" => Self::Edit,
+            "delete" => Self::Delete,
+            "move" => Self::Move,
+            "search" => Self::Search,
+            "execute" => Self::Execute,
+            "think" => Self::Think,
+            "fetch" => Self::Fetch,
+            _ => Self::Other,
+        }
+    }
+
+    /// ACP name of the kind (`"read"`, `"execute"`, ...).
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::Edit => "edit",
+            Self::Delete => "delete",
+            Self::Move => "move",
+            Self::Search => "search",
+            Self::Execute => "execute",
+            Self::Think => "think",
+            Self::Fetch => "fetch",
+            Self::Other => "other",
+        }
+    }
+
+    /// Single-column icon for the kind.
+    pub fn icon(self) -> &'static str {
+        match self {
+            Self::Read => "◎",
+            Self::Edit => "✎",
+            Self::Delete => "✗",
+            Self::Move => "⇄",
+            Self::Search => "⌕",
+            Self::Execute => "▶",
+            Self::Think => "✻",
+            Self::Fetch => "⇣",
+            Self::Other => "•",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+// ============================================================================
+// Theme
+// ============================================================================
+
+/// How a tool kind is marked on the tool lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KindStyle {
+    /// A single-column icon such as `✎` (default).
+    #[default]
+    Icons,
+    /// A bracketed label such as `[edit]`, for terminals without good glyphs.
+    Labels,
+    /// No marker; only the tool name color reflects the kind.
+    None,
+}
+
+/// Characters used to draw boxes, such as the borders of markdown tables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoxGlyphs {
+    /// Horizontal line.
+    pub horizontal: char,
+    /// Vertical line.
+    pub vertical: char,
+    /// Top-left corner.
+    pub top_left: char,
+    /// Top-right corner.
+    pub top_right: char,
+    /// Bottom-left corner.
+    pub bottom_left: char,
+    /// Bottom-right corner.
+    pub bottom_right: char,
+    /// Junction on the top edge.
+    pub top_tee: char,
+    /// Junction on the bottom edge.
+    pub bottom_tee: char,
+    /// Junction on the left edge.
+    pub left_tee: char,
+    /// Junction on the right edge.
+    pub right_tee: char,
+    /// Inner junction.
+    pub cross: char,
+}
+
+impl BoxGlyphs {
+    /// Light box-drawing lines: `┌─┬─┐` (default).
+    pub const LIGHT: Self = Self {
+        horizontal: '─',
+        vertical: '│',
+        top_left: '┌',
+        top_right: '┐',
+        bottom_left: '└',
+        bottom_right: '┘',
+        top_tee: '┬',
+        bottom_tee: '┴',
+        left_tee: '├',
+        right_tee: '┤',
+        cross: '┼',
+    };
+
+    /// Light lines with rounded corners: `╭─┬─╮`.
+    pub const ROUNDED: Self = Self {
+        top_left: '╭',
+        top_right: '╮',
+        bottom_left: '╰',
+        bottom_right: '╯',
+        ..Self::LIGHT
+    };
+
+    /// Plain ASCII: `+-+-+`, for terminals without box-drawing glyphs.
+    pub const ASCII: Self = Self {
+        horizontal: '-',
+        vertical: '|',
+        top_left: '+',
+        top_right: '+',
+        bottom_left: '+',
+        bottom_right: '+',
+        top_tee: '+',
+        bottom_tee: '+',
+        left_tee: '+',
+        right_tee: '+',
+        cross: '+',
+    };
+}
+
+impl Default for BoxGlyphs {
+    fn default() -> Self {
+        Self::LIGHT
+    }
+}
+
+/// Colors and markers used by the formatters.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    /// Tool name color when no kind is known.
+    pub tool_name: Color,
+    /// How tool kinds are marked.
+    pub kind_style: KindStyle,
+    /// Glyphs for table borders.
+    pub box_glyphs: BoxGlyphs,
+    tool_kinds: [Color; ToolKind::ALL.len()],
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        let mut tool_kinds = [Color::Cyan; ToolKind::ALL.len()];
+        for kind in ToolKind::ALL {
+            tool_kinds[kind.index()] = match kind {
+                ToolKind::Read => Color::Blue,
+                ToolKind::Edit => Color::Yellow,
+                ToolKind::Delete => Color::Red,
+                ToolKind::Move => Color::Magenta,
+                ToolKind::Search => Color::Cyan,
+                ToolKind::Execute => Color::Green,
+                ToolKind::Think => Color::BrightBlack,
+                ToolKind::Fetch => Color::BrightBlue,
+                ToolKind::Other => Color::Cyan,
+            };
+        }
+        Self {
+            tool_name: Color::Cyan,
+            kind_style: KindStyle::Icons,
+            box_glyphs: BoxGlyphs::default(),
+            tool_kinds,
+        }
+    }
+}
+
+impl Theme {
+    /// Set the color used for tools of `kind`.
+    pub fn with_tool_kind_color(mut self, kind: ToolKind, color: Color) -> Self {
+        self.tool_kinds[kind.index()] = color;
+        self
+    }
+
+    /// Set how tool kinds are marked.
+    pub fn with_kind_style(mut self, style: KindStyle) -> Self {
+        self.kind_style = style;
+        self
+    }
+
+    /// Set the glyphs used for table borders.
+    pub fn with_box_glyphs(mut self, glyphs: BoxGlyphs) -> Self {
+        self.box_glyphs = glyphs;
+        self
+    }
+
+    /// Color for tools of `kind`, or the plain tool name color without one.
+    pub fn tool_color(&self, kind: Option<ToolKind>) -> Color {
+        kind.map_or(self.tool_name, |kind| self.tool_kinds[kind.index()])
+    }
+
+    /// Uncolored marker for `kind` according to [`kind_style`](Self::kind_style).
+    pub fn kind_marker(&self, kind: ToolKind) -> Option<String> {
+        match self.kind_style {
+            KindStyle::Icons => Some(kind.icon().to_string()),
+            KindStyle::Labels => Some(format!("[{}]", kind.as_str())),
+            KindStyle::None => None,
+        }
+    }
+}
+
+// ============================================================================
+// Global Theme
+// ============================================================================
+
+static DEFAULT_THEME: LazyLock<Arc<Theme>> = LazyLock::new(|| Arc::new(Theme::default()));
+
+static THEME: RwLock<Option<Arc<Theme>>> = RwLock::new(None);
+
+/// Set the global theme used by the formatters.
+pub fn set_theme(theme: Arc<Theme>) {
+    if let Ok(mut guard) = THEME.write() {
+        *guard = Some(theme);
+    }
+}
+
+/// Reset the global theme to the default.
+pub fn reset_theme() {
+    if let Ok(mut guard) = THEME.write() {
+        *guard = None;
+    }
+}
+
+/// Get the current global theme.
+pub fn theme() -> Arc<Theme> {
+    THEME
+        .read()
+        .ok()
+        .and_then(|guard| guard.clone())
+        .unwrap_or_else(|| Arc::clone(&DEFAULT_THEME))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::width::display_width;
+
+    #[test]
+    fn test_parse_round_trips() {
+        for kind in ToolKind::ALL {
+            assert_eq!(ToolKind::parse(kind.as_str()), kind);
+        }
+        assert_eq!(ToolKind::parse("switch_mode"), ToolKind::Other);
+        assert_eq!(ToolKind::parse(""), ToolKind::Other);
+    }
+
+    #[test]
+    fn test_deserialize_from_acp() {
+        let kind: ToolKind = serde_json::from_str("\"execute\"").unwrap();
+        assert_eq!(kind, ToolKind::Execute);
+        let unknown: ToolKind = serde_json::from_str("\"teleport\"").unwrap();
+        assert_eq!(unknown, ToolKind::Other);
+    }
+
+    #[test]
+    fn test_icons_are_single_column_and_distinct() {
+        let mut icons: Vec<&str> = ToolKind::ALL.iter().map(|k| k.icon()).collect();
+        assert!(icons.iter().all(|icon| display_width(icon) == 1));
+        icons.sort();
+        icons.dedup();
+        assert_eq!(icons.len(), ToolKind::ALL.len());
+    }
+
+    #[test]
+    fn test_theme_colors() {
+        let theme = Theme::default();
+        assert_eq!(theme.tool_color(None), Color::Cyan);
+        assert_eq!(theme.tool_color(Some(ToolKind::Delete)), Color::Red);
+
+        let theme = theme.with_tool_kind_color(ToolKind::Delete, Color::BrightRed);
+        assert_eq!(theme.tool_color(Some(ToolKind::Delete)), Color::BrightRed);
+        assert_eq!(theme.tool_color(Some(ToolKind::Read)), Color::Blue);
+    }
+
+    #[test]
+    fn test_kind_markers() {
+        let theme = Theme::default();
+        assert_eq!(theme.kind_marker(ToolKind::Edit).as_deref(), Some("✎"));
+        let theme = theme.with_kind_style(KindStyle::Labels);
+        assert_eq!(theme.kind_marker(ToolKind::Edit).as_deref(), Some("[edit]"));
+        let theme = theme.with_kind_style(KindStyle::None);
+        assert_eq!(theme.kind_marker(ToolKind::Edit), None);
+    }
+}
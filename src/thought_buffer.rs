@@ -0,0 +1,286 @@
+//! Thought buffer for rendering agent reasoning separately from the answer.
+//!
+//! ACP agents stream their reasoning as `agent_thought_chunk` updates, distinct
+//! from `agent_message_chunk`. A [`ThoughtBuffer`] renders those chunks dimmed
+//! and italic under a `thinking…` header, and when the answer starts,
+//! [`finish`](ThoughtBuffer::finish) replaces the block with a one-line summary
+//! such as `✻ thought for 12s`.
+//!
+//! # Usage
+//!
+//! ```
+//! use clemitui::{ThoughtBuffer, ThoughtDisplay};
+//!
+//! let mut thoughts = ThoughtBuffer::new().with_display(ThoughtDisplay::Expanded);
+//! thoughts.push("The user wants a fix for the parser.");
+//! if let Some(rendered) = thoughts.flush() {
+//!     print!("{}", rendered);
+//! }
+//!
+//! // The first answer chunk arrived
+//! if let Some(summary) = thoughts.finish() {
+//!     print!("{}", summary);
+//! }
+//! ```
+
+use std::time::{Duration, Instant};
+
+use colored::Colorize;
+
+use crate::format::format_elapsed;
+use crate::live_region::erase_rows;
+use crate::width::wrapped_rows;
+
+/// Indentation for thought text under the header.
+const THOUGHT_INDENT: &str = "  ";
+
+// ============================================================================
+// Display Mode
+// ============================================================================
+
+/// How agent thoughts are shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ThoughtDisplay {
+    /// Stream thoughts, then collapse them to a one-line summary when the
+    /// answer starts (default).
+    #[default]
+    Collapsed,
+    /// Stream thoughts and keep them, followed by the summary line.
+    Expanded,
+    /// Show nothing.
+    Hidden,
+}
+
+// ============================================================================
+// Thought Buffer
+// ============================================================================
+
+/// Buffer for streaming agent thoughts until event boundaries.
+///
+/// Chunks are buffered with `push()` and rendered by `flush()`, like
+/// [`TextBuffer`](crate::TextBuffer); each flush ends on a complete line. The
+/// elapsed time is measured from the first chunk to `finish()`, which must be
+/// called when the thought ends (the first answer chunk or tool call arrives).
+#[derive(Debug, Default)]
+pub struct ThoughtBuffer {
+    pending: String,
+    display: ThoughtDisplay,
+    started: Option<Instant>,
+    header_shown: bool,
+    /// Terminal rows printed since the header, for collapsing.
+    rows: usize,
+    terminal_size: Option<(u16, u16)>,
+}
+
+impl ThoughtBuffer {
+    /// Create a new empty thought buffer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set how thoughts are shown.
+    pub fn with_display(mut self, display: ThoughtDisplay) -> Self {
+        self.display = display;
+        self
+    }
+
+    /// Use a fixed terminal size (columns, rows) for collapsing instead of
+    /// querying the terminal.
+    pub fn with_terminal_size(mut self, columns: u16, rows: u16) -> Self {
+        self.terminal_size = Some((columns, rows));
+        self
+    }
+
+    /// The current display mode.
+    pub fn display(&self) -> ThoughtDisplay {
+        self.display
+    }
+
+    /// Append a thought chunk. The first chunk starts the thought timer.
+    pub fn push(&mut self, text: &str) {
+        self.started.get_or_insert_with(Instant::now);
+        self.pending.push_str(text);
+    }
+
+    /// Whether a thought is in progress (pushed but not finished).
+    pub fn is_active(&self) -> bool {
+        self.started.is_some()
+    }
+
+    /// Render buffered thought text, preceded by the `thinking…` header on the
+    /// first flush. Returns None if nothing is buffered or thoughts are hidden.
+    pub fn flush(&mut self) -> Option<String> {
+        let text = std::mem::take(&mut self.pending);
+        if self.display == ThoughtDisplay::Hidden || text.trim().is_empty() {
+            return None;
+        }
+
+        let columns = self.columns();
+        let mut out = String::new();
+        if !self.header_shown {
+            self.header_shown = true;
+            let header = format!("{} {}", "✻".dimmed(), "thinking…".dimmed().italic());
+            self.rows += wrapped_rows(&header, columns);
+            out.push_str(&header);
+            out.push('\n');
+        }
+
+        for line in text.trim_matches('\n').lines() {
+            self.rows += wrapped_rows(&format!("{THOUGHT_INDENT}{line}"), columns);
+            if !line.is_empty() {
+                out.push_str(THOUGHT_INDENT);
+                out.push_str(&line.dimmed().italic().to_string());
+            }
+            out.push('\n');
+        }
+        Some(out)
+    }
+
+    /// End the thought, returning the `✻ thought for 12s` summary line. In
+    /// [`ThoughtDisplay::Collapsed`] mode the summary replaces the streamed
+    /// block when it is still on screen. Returns None if no thought was
+    /// started or thoughts are hidden.
+    pub fn finish(&mut self) -> Option<String> {
+        let elapsed = self.started?.elapsed();
+        self.finish_with(elapsed)
+    }
+
+    fn finish_with(&mut self, elapsed: Duration) -> Option<String> {
+        let mut out = String::new();
+        match self.display {
+            ThoughtDisplay::Hidden => {
+                self.reset();
+                return None;
+            }
+            ThoughtDisplay::Expanded => {
+                if let Some(rest) = self.flush() {
+                    out.push_str(&rest);
+                }
+            }
+            ThoughtDisplay::Collapsed => {
+                let screen_rows = self.terminal_size().map_or(0, |(_, rows)| rows as usize);
+                if self.rows > 0 && self.rows < screen_rows {
+                    // Move to the header line and clear everything below it
+                    out.push_str(&erase_rows(self.rows));
+                }
+            }
+        }
+        out.push_str(&format_thought_summary(elapsed));
+        out.push_str("\n\n");
+        self.reset();
+        Some(out)
+    }
+
+    fn reset(&mut self) {
+        let display = self.display;
+        let terminal_size = self.terminal_size;
+        *self = Self {
+            display,
+            terminal_size,
+            ..Self::default()
+        };
+    }
+
+    fn terminal_size(&self) -> Option<(u16, u16)> {
+        self.terminal_size
+            .or_else(|| crossterm::terminal::size().ok())
+    }
+
+    fn columns(&self) -> usize {
+        self.terminal_size()
+            .map_or(usize::MAX, |(columns, _)| (columns as usize).max(1))
+    }
+}
+
+/// Format the one-line summary shown after a thought: `✻ thought for 12s`.
+pub fn format_thought_summary(elapsed: Duration) -> String {
+    format!(
+        "{} {}",
+        "✻".dimmed(),
+        format!("thought for {}", format_elapsed(elapsed))
+            .dimmed()
+            .italic()
+    )
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buffer(display: ThoughtDisplay) -> ThoughtBuffer {
+        ThoughtBuffer::new()
+            .with_display(display)
+            .with_terminal_size(40, 24)
+    }
+
+    #[test]
+    fn test_flush_renders_header_once() {
+        colored::control::set_override(false);
+        let mut thoughts = buffer(ThoughtDisplay::Expanded);
+        thoughts.push("first idea\n");
+        assert_eq!(
+            thoughts.flush().as_deref(),
+            Some("✻ thinking…\n  first idea\n")
+        );
+        thoughts.push("second\n\nthird");
+        assert_eq!(thoughts.flush().as_deref(), Some("  second\n\n  third\n"));
+        assert!(thoughts.flush().is_none());
+        colored::control::unset_override();
+    }
+
+    #[test]
+    fn test_collapsed_erases_block() {
+        colored::control::set_override(false);
+        let mut thoughts = buffer(ThoughtDisplay::Collapsed);
+        thoughts.push("short\n");
+        thoughts.flush();
+        // Wider than 40 columns: wraps to two rows
+        thoughts.push(&"x".repeat(60));
+        thoughts.flush();
+        let out = thoughts.finish_with(Duration::from_secs(12)).unwrap();
+        assert_eq!(out, "\x1b[4F\x1b[J✻ thought for 12s\n\n");
+        assert!(!thoughts.is_active());
+        colored::control::unset_override();
+    }
+
+    #[test]
+    fn test_collapsed_keeps_block_taller_than_screen() {
+        colored::control::set_override(false);
+        let mut thoughts = ThoughtBuffer::new().with_terminal_size(40, 3);
+        thoughts.push("a\nb\nc\n");
+        thoughts.flush();
+        let out = thoughts.finish_with(Duration::from_secs(2)).unwrap();
+        assert_eq!(out, "✻ thought for 2s\n\n");
+        colored::control::unset_override();
+    }
+
+    #[test]
+    fn test_expanded_flushes_rest_before_summary() {
+        colored::control::set_override(false);
+        let mut thoughts = buffer(ThoughtDisplay::Expanded);
+        thoughts.push("unflushed");
+        let out = thoughts.finish_with(Duration::from_secs(75)).unwrap();
+        assert_eq!(out, "✻ thinking…\n  unflushed\n✻ thought for 1m 15s\n\n");
+        colored::control::unset_override();
+    }
+
+    #[test]
+    fn test_hidden_shows_nothing() {
+        let mut thoughts = buffer(ThoughtDisplay::Hidden);
+        thoughts.push("secret plan");
+        assert!(thoughts.is_active());
+        assert!(thoughts.flush().is_none());
+        assert!(thoughts.finish().is_none());
+        assert!(!thoughts.is_active());
+    }
+
+    #[test]
+    fn test_finish_without_thought() {
+        let mut thoughts = buffer(ThoughtDisplay::Collapsed);
+        assert!(thoughts.finish().is_none());
+    }
+}
@@ -0,0 +1,445 @@
+//! Per-tool argument formatting.
+//!
+//! Tools differ in what is worth showing: an edit's `old_string` belongs in a
+//! diff rather than the header, and a custom tool may want a one-line summary
+//! instead of raw `key=value` pairs. A [`ToolFormatterRegistry`] maps tool
+//! names (or glob patterns such as `mcp__github__*`) to a [`ToolFormatter`]
+//! that renders the header arguments and an optional body shown below the
+//! `┌─` line.
+//!
+//! # Usage
+//!
+//! ```
+//! use clemitui::{HiddenArgs, ToolFormatterRegistry, set_tool_formatters};
+//! use serde_json::Value;
+//! use std::sync::Arc;
+//!
+//! let mut registry = ToolFormatterRegistry::with_builtins();
+//! registry.register("deploy", |args: &Value| {
+//!     format!("to {} ", args["env"].as_str().unwrap_or("?"))
+//! });
+//! registry.register("mcp__*", HiddenArgs::new(["token"]));
+//!
+//! // Applies to format_tool_args and format_tool_executing
+//! set_tool_formatters(Arc::new(registry));
+//! ```
+
+use std::sync::{Arc, LazyLock, RwLock};
+
+use serde_json::Value;
+
+use crate::format::{format_arg_pairs, format_json_block};
+use crate::redact::redactor;
+
+// ============================================================================
+// Formatter Trait
+// ============================================================================
+
+/// Renders a tool call's arguments for display.
+pub trait ToolFormatter: Send + Sync {
+    /// Arguments shown after the tool name on the `┌─` line.
+    ///
+    /// Returns an empty string or text ending in a space, matching
+    /// [`format_arg_pairs`].
+    fn format_args(&self, args: &Value) -> String;
+
+    /// Optional multi-line body shown below the header. Defaults to none.
+    fn format_body(&self, _args: &Value) -> Option<String> {
+        None
+    }
+}
+
+/// Closures format the header arguments and have no body.
+impl<F> ToolFormatter for F
+where
+    F: Fn(&Value) -> String + Send + Sync,
+{
+    fn format_args(&self, args: &Value) -> String {
+        self(args)
+    }
+}
+
+/// Default `key=value` formatting with some keys left out.
+///
+/// The built-in formatters use this to hide arguments that are rendered
+/// elsewhere (diffs, todo lists, questions).
+#[derive(Debug, Clone, Default)]
+pub struct HiddenArgs {
+    hidden: Vec<String>,
+}
+
+impl HiddenArgs {
+    /// Hide the given argument keys.
+    pub fn new<I, S>(keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            hidden: keys.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl ToolFormatter for HiddenArgs {
+    fn format_args(&self, args: &Value) -> String {
+        let hidden: Vec<&str> = self.hidden.iter().map(String::as_str).collect();
+        format_arg_pairs(args, &hidden)
+    }
+}
+
+// ============================================================================
+// Registry
+// ============================================================================
+
+/// Match `name` against a glob `pattern` supporting `*` and `?`.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    n = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Whether a registration key is a glob pattern rather than an exact name.
+fn is_pattern(key: &str) -> bool {
+    key.contains(['*', '?'])
+}
+
+/// Maps tool names and glob patterns to formatters.
+///
+/// Exact names take precedence over patterns; among patterns the most recently
+/// registered match wins. Tools with no match use plain [`format_arg_pairs`].
+#[derive(Clone, Default)]
+pub struct ToolFormatterRegistry {
+    entries: Vec<(String, Arc<dyn ToolFormatter>)>,
+    verbose: bool,
+}
+
+impl ToolFormatterRegistry {
+    /// Create an empty registry with no built-in formatters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry with the built-in formatters for `edit`,
+    /// `todo_write` and `ask_user`.
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        // Shown in the diff
+        registry.register("edit", HiddenArgs::new(["old_string", "new_string"]));
+        // Rendered below as a todo list
+        registry.register("todo_write", HiddenArgs::new(["todos"]));
+        // Rendered below as the question prompt
+        registry.register("ask_user", HiddenArgs::new(["question", "options"]));
+        registry
+    }
+
+    /// Register a formatter for a tool name or glob pattern (`*`, `?`).
+    ///
+    /// Registering the same name or pattern again replaces the earlier entry.
+    pub fn register(&mut self, name: impl Into<String>, formatter: impl ToolFormatter + 'static) {
+        let name = name.into();
+        self.entries.retain(|(key, _)| *key != name);
+        self.entries.push((name, Arc::new(formatter)));
+    }
+
+    /// Remove the formatter registered under exactly `name`.
+    pub fn unregister(&mut self, name: &str) -> bool {
+        let before = self.entries.len();
+        self.entries.retain(|(key, _)| key != name);
+        self.entries.len() != before
+    }
+
+    /// Find the formatter for `tool_name`.
+    pub fn get(&self, tool_name: &str) -> Option<&dyn ToolFormatter> {
+        let exact = self
+            .entries
+            .iter()
+            .find(|(key, _)| !is_pattern(key) && key == tool_name);
+        let entry = exact.or_else(|| {
+            self.entries
+                .iter()
+                .rev()
+                .find(|(key, _)| is_pattern(key) && glob_match(key, tool_name))
+        });
+        entry.map(|(_, formatter)| formatter.as_ref())
+    }
+
+    /// Format header arguments for `tool_name`.
+    ///
+    /// Arguments pass through the global [`Redactor`](crate::Redactor) first,
+    /// so formatters never see secrets.
+    pub fn format_args(&self, tool_name: &str, args: &Value) -> String {
+        let args = redactor().redact_value(args);
+        match self.get(tool_name) {
+            Some(formatter) => formatter.format_args(&args),
+            None => format_arg_pairs(&args, &[]),
+        }
+    }
+
+    /// Also show the full arguments as pretty-printed JSON below the header.
+    pub fn with_verbose(mut self, verbose: bool) -> Self {
+        self.verbose = verbose;
+        self
+    }
+
+    /// Set whether the full arguments are shown below the header.
+    pub fn set_verbose(&mut self, verbose: bool) {
+        self.verbose = verbose;
+    }
+
+    /// Whether verbose argument display is enabled.
+    pub fn is_verbose(&self) -> bool {
+        self.verbose
+    }
+
+    /// Format the body shown below the header for `tool_name`, if any.
+    ///
+    /// In verbose mode the formatter's body is followed by the full arguments
+    /// as pretty-printed JSON.
+    pub fn format_body(&self, tool_name: &str, args: &Value) -> Option<String> {
+        let args = redactor().redact_value(args);
+        let body = self
+            .get(tool_name)
+            .and_then(|formatter| formatter.format_body(&args));
+        let has_args = args.as_object().is_some_and(|obj| !obj.is_empty());
+        if !self.verbose || !has_args {
+            return body;
+        }
+        let block = format_json_block(&args);
+        Some(match body {
+            Some(body) => format!("{body}\n{block}"),
+            None => block,
+        })
+    }
+
+    /// Whether no formatters are registered.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl std::fmt::Debug for ToolFormatterRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ToolFormatterRegistry")
+            .field(
+                "entries",
+                &self.entries.iter().map(|(key, _)| key).collect::<Vec<_>>(),
+            )
+            .field("verbose", &self.verbose)
+            .finish()
+    }
+}
+
+// ============================================================================
+// Global Registry
+// ============================================================================
+
+static BUILTIN_FORMATTERS: LazyLock<Arc<ToolFormatterRegistry>> =
+    LazyLock::new(|| Arc::new(ToolFormatterRegistry::with_builtins()));
+
+static TOOL_FORMATTERS: RwLock<Option<Arc<ToolFormatterRegistry>>> = RwLock::new(None);
+
+/// Set the global tool formatter registry used by [`format_tool_args`](crate::format_tool_args)
+/// and [`format_tool_executing`](crate::format_tool_executing).
+///
+/// Start from [`ToolFormatterRegistry::with_builtins`] to keep the default
+/// handling of `edit`, `todo_write` and `ask_user`.
+pub fn set_tool_formatters(registry: Arc<ToolFormatterRegistry>) {
+    if let Ok(mut guard) = TOOL_FORMATTERS.write() {
+        *guard = Some(registry);
+    }
+}
+
+/// Reset the global registry to the built-in formatters.
+pub fn reset_tool_formatters() {
+    if let Ok(mut guard) = TOOL_FORMATTERS.write() {
+        *guard = None;
+    }
+}
+
+/// Get the current global registry (built-ins unless one was set).
+pub fn tool_formatters() -> Arc<ToolFormatterRegistry> {
+    TOOL_FORMATTERS
+        .read()
+        .ok()
+        .and_then(|guard| guard.clone())
+        .unwrap_or_else(|| Arc::clone(&BUILTIN_FORMATTERS))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    struct Summary;
+
+    impl ToolFormatter for Summary {
+        fn format_args(&self, args: &Value) -> String {
+            format!("{} items ", args["items"].as_array().map_or(0, Vec::len))
+        }
+
+        fn format_body(&self, args: &Value) -> Option<String> {
+            let items = args["items"].as_array()?;
+            Some(
+                items
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            )
+        }
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*", "anything"));
+        assert!(glob_match("mcp__*", "mcp__github__search"));
+        assert!(glob_match("mcp__*__search", "mcp__github__search"));
+        assert!(glob_match("read_?", "read_x"));
+        assert!(!glob_match("read_?", "read_xy"));
+        assert!(!glob_match("mcp__*", "bash"));
+        assert!(glob_match("*_file", "write_file"));
+        assert!(!glob_match("*_file", "write_files"));
+    }
+
+    #[test]
+    fn test_builtins_hide_rendered_keys() {
+        let registry = ToolFormatterRegistry::with_builtins();
+        let args = json!({"file_path": "a.rs", "old_string": "x", "new_string": "y"});
+        assert_eq!(registry.format_args("edit", &args), "file_path=\"a.rs\" ");
+        // Other tools show every key
+        assert!(registry.format_args("write", &args).contains("old_string="));
+    }
+
+    #[test]
+    fn test_empty_registry_uses_default_pairs() {
+        let registry = ToolFormatterRegistry::new();
+        let args = json!({"old_string": "x"});
+        assert_eq!(registry.format_args("edit", &args), "old_string=\"x\" ");
+        assert_eq!(registry.format_body("edit", &args), None);
+    }
+
+    #[test]
+    fn test_closure_formatter() {
+        let mut registry = ToolFormatterRegistry::new();
+        registry.register("deploy", |args: &Value| {
+            format!("to {} ", args["env"].as_str().unwrap_or("?"))
+        });
+        assert_eq!(
+            registry.format_args("deploy", &json!({"env": "prod"})),
+            "to prod "
+        );
+    }
+
+    #[test]
+    fn test_exact_name_beats_pattern() {
+        let mut registry = ToolFormatterRegistry::new();
+        registry.register("mcp__github__search", |_: &Value| "exact ".to_string());
+        registry.register("mcp__*", |_: &Value| "glob ".to_string());
+        assert_eq!(
+            registry.format_args("mcp__github__search", &json!({})),
+            "exact "
+        );
+        assert_eq!(
+            registry.format_args("mcp__slack__post", &json!({})),
+            "glob "
+        );
+    }
+
+    #[test]
+    fn test_later_pattern_wins() {
+        let mut registry = ToolFormatterRegistry::new();
+        registry.register("mcp__*", |_: &Value| "any ".to_string());
+        registry.register("mcp__github__*", |_: &Value| "github ".to_string());
+        assert_eq!(
+            registry.format_args("mcp__github__search", &json!({})),
+            "github "
+        );
+        assert_eq!(registry.format_args("mcp__slack__post", &json!({})), "any ");
+    }
+
+    #[test]
+    fn test_register_replaces_and_unregister() {
+        let mut registry = ToolFormatterRegistry::with_builtins();
+        registry.register("edit", HiddenArgs::new(["file_path"]));
+        let args = json!({"file_path": "a.rs", "old_string": "x"});
+        assert_eq!(registry.format_args("edit", &args), "old_string=\"x\" ");
+
+        assert!(registry.unregister("edit"));
+        assert!(!registry.unregister("edit"));
+        assert!(registry.format_args("edit", &args).contains("file_path="));
+    }
+
+    #[test]
+    fn test_verbose_appends_pretty_args() {
+        let mut registry = ToolFormatterRegistry::new().with_verbose(true);
+        let args = json!({"paths": ["a.rs", "b.rs"]});
+        assert_eq!(
+            registry.format_body("glob", &args).unwrap(),
+            "{\n  \"paths\": [\n    \"a.rs\",\n    \"b.rs\"\n  ]\n}"
+        );
+        // No block for empty arguments
+        assert_eq!(registry.format_body("glob", &json!({})), None);
+
+        registry.register("checklist", Summary);
+        let body = registry
+            .format_body("checklist", &json!({"items": ["one"]}))
+            .unwrap();
+        assert!(body.starts_with("one\n{"), "{body}");
+
+        registry.set_verbose(false);
+        assert_eq!(registry.format_body("glob", &args), None);
+    }
+
+    #[test]
+    fn test_args_are_redacted_before_formatting() {
+        let mut registry = ToolFormatterRegistry::new().with_verbose(true);
+        registry.register("login", |args: &Value| format!("{} ", args["password"]));
+        let args = json!({"password": "hunter2"});
+        assert_eq!(registry.format_args("login", &args), "\"[REDACTED]\" ");
+        assert!(
+            !registry
+                .format_body("login", &args)
+                .unwrap()
+                .contains("hunter2")
+        );
+    }
+
+    #[test]
+    fn test_body_formatter() {
+        let mut registry = ToolFormatterRegistry::new();
+        registry.register("checklist", Summary);
+        let args = json!({"items": ["one", "two"]});
+        assert_eq!(registry.format_args("checklist", &args), "2 items ");
+        assert_eq!(
+            registry.format_body("checklist", &args),
+            Some("one\ntwo".to_string())
+        );
+    }
+}
@@ -0,0 +1,426 @@
+//! Display width measurement and truncation.
+//!
+//! Terminal layout depends on columns, not bytes or chars: CJK characters and
+//! most emoji take two columns, combining marks take none, and ANSI escape
+//! sequences take no space at all. These helpers measure text in columns and
+//! only ever cut between grapheme clusters, so truncation never splits a
+//! multi-byte character or an emoji sequence.
+
+use std::borrow::Cow;
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Ellipsis used for middle and path truncation.
+const ELLIPSIS: &str = "…";
+
+// ============================================================================
+// Measurement
+// ============================================================================
+
+/// Byte length of the escape sequence starting at `s` (which begins with ESC).
+///
+/// Handles CSI (`ESC [ ... final`), string sequences such as OSC and APC
+/// (`ESC ] ... BEL` or `ESC _ ... ESC \`), and two-byte escapes.
+fn escape_len(s: &str) -> usize {
+    let bytes = s.as_bytes();
+    match bytes.get(1) {
+        Some(b'[') => bytes[2..]
+            .iter()
+            .position(|b| (0x40..=0x7e).contains(b))
+            .map_or(bytes.len(), |i| i + 3),
+        Some(b']' | b'P' | b'_' | b'^' | b'X') => {
+            let mut i = 2;
+            while i < bytes.len() {
+                match bytes[i] {
+                    0x07 => return i + 1,
+                    0x1b if bytes.get(i + 1) == Some(&b'\\') => return i + 2,
+                    _ => i += 1,
+                }
+            }
+            bytes.len()
+        }
+        Some(_) => 1 + s[1..].chars().next().map_or(0, char::len_utf8),
+        None => 1,
+    }
+}
+
+/// Split `s` into alternating text and escape-sequence segments.
+///
+/// Yields `(segment, is_escape)` pairs covering the whole string.
+pub(crate) fn segments(s: &str) -> impl Iterator<Item = (&str, bool)> {
+    let mut rest = s;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let (segment, is_escape) = if rest.starts_with('\x1b') {
+            (&rest[..escape_len(rest)], true)
+        } else {
+            let end = rest.find('\x1b').unwrap_or(rest.len());
+            (&rest[..end], false)
+        };
+        rest = &rest[segment.len()..];
+        Some((segment, is_escape))
+    })
+}
+
+/// Columns taken by a single grapheme cluster.
+fn grapheme_width(grapheme: &str) -> usize {
+    UnicodeWidthStr::width(grapheme)
+}
+
+/// Number of terminal columns `s` occupies.
+///
+/// ANSI escape sequences (colors, cursor movement, OSC 8 hyperlinks) count as
+/// zero width.
+///
+/// # Example
+///
+/// ```
+/// use clemitui::width::display_width;
+///
+/// assert_eq!(display_width("abc"), 3);
+/// assert_eq!(display_width("日本"), 4);
+/// assert_eq!(display_width("\x1b[31mred\x1b[0m"), 3);
+/// ```
+pub fn display_width(s: &str) -> usize {
+    segments(s)
+        .filter(|(_, is_escape)| !is_escape)
+        .map(|(text, _)| text.graphemes(true).map(grapheme_width).sum::<usize>())
+        .sum()
+}
+
+/// Terminal rows `text` occupies when printed `columns` wide.
+///
+/// Each line takes at least one row; a trailing newline does not start a new
+/// row.
+///
+/// # Example
+///
+/// ```
+/// use clemitui::width::wrapped_rows;
+///
+/// assert_eq!(wrapped_rows("short\n", 10), 1);
+/// assert_eq!(wrapped_rows("0123456789abc\n\nend", 10), 4);
+/// ```
+pub fn wrapped_rows(text: &str, columns: usize) -> usize {
+    let columns = columns.max(1);
+    text.strip_suffix('\n')
+        .unwrap_or(text)
+        .split('\n')
+        .map(|line| display_width(line).div_ceil(columns).max(1))
+        .sum()
+}
+
+/// Remove ANSI escape sequences from `s`.
+pub fn strip_ansi(s: &str) -> Cow<'_, str> {
+    if !s.contains('\x1b') {
+        return Cow::Borrowed(s);
+    }
+    Cow::Owned(
+        segments(s)
+            .filter(|(_, is_escape)| !is_escape)
+            .map(|(text, _)| text)
+            .collect(),
+    )
+}
+
+// ============================================================================
+// Truncation
+// ============================================================================
+
+/// Longest prefix of `s` (plain text) that fits in `max_width` columns.
+fn take_width(s: &str, max_width: usize) -> &str {
+    let mut width = 0;
+    for (i, grapheme) in s.grapheme_indices(true) {
+        width += grapheme_width(grapheme);
+        if width > max_width {
+            return &s[..i];
+        }
+    }
+    s
+}
+
+/// Longest suffix of `s` (plain text) that fits in `max_width` columns.
+fn take_width_from_end(s: &str, max_width: usize) -> &str {
+    let mut width = 0;
+    for (i, grapheme) in s.grapheme_indices(true).rev() {
+        width += grapheme_width(grapheme);
+        if width > max_width {
+            return &s[i + grapheme.len()..];
+        }
+    }
+    s
+}
+
+/// Truncate text to `max_width` columns, ending with `ellipsis`.
+///
+/// The ellipsis counts towards `max_width`. Text that already fits is
+/// returned unchanged. ANSI escape sequences before the cut are kept, and a
+/// reset (`ESC [0m`) is added before the ellipsis if any were present so
+/// styling does not bleed past the truncated text. An OSC 8 hyperlink open at
+/// the cut is closed there as well.
+///
+/// # Example
+///
+/// ```
+/// use clemitui::width::truncate_end;
+///
+/// assert_eq!(truncate_end("hello world", 8, "..."), "hello...");
+/// assert_eq!(truncate_end("日本語テキスト", 7, "…"), "日本語…");
+/// assert_eq!(truncate_end("\x1b[31mred text\x1b[0m", 4, "…"), "\x1b[31mred\x1b[0m…");
+/// ```
+pub fn truncate_end<'a>(s: &'a str, max_width: usize, ellipsis: &str) -> Cow<'a, str> {
+    if display_width(s) <= max_width {
+        return Cow::Borrowed(s);
+    }
+    let available = max_width.saturating_sub(display_width(ellipsis));
+    let mut out = String::new();
+    let mut width = 0;
+    let mut styled = false;
+    let mut link_open = false;
+    'segments: for (segment, is_escape) in segments(s) {
+        if is_escape {
+            out.push_str(segment);
+            styled = true;
+            if let Some(params) = segment.strip_prefix("\x1b]8;") {
+                let uri = params.split_once(';').map_or("", |(_, rest)| rest);
+                link_open = !uri
+                    .trim_end_matches(['\x07', '\\'])
+                    .trim_end_matches('\x1b')
+                    .is_empty();
+            }
+            continue;
+        }
+        for grapheme in segment.graphemes(true) {
+            width += grapheme_width(grapheme);
+            if width > available {
+                break 'segments;
+            }
+            out.push_str(grapheme);
+        }
+    }
+    if link_open {
+        out.push_str("\x1b]8;;\x1b\\");
+    }
+    if styled {
+        out.push_str("\x1b[0m");
+    }
+    out.push_str(ellipsis);
+    Cow::Owned(out)
+}
+
+/// Truncate plain text to `max_width` columns by replacing its middle with `…`.
+///
+/// # Example
+///
+/// ```
+/// use clemitui::width::truncate_middle;
+///
+/// assert_eq!(truncate_middle("abcdefghij", 7), "abc…hij");
+/// ```
+pub fn truncate_middle(s: &str, max_width: usize) -> Cow<'_, str> {
+    if display_width(s) <= max_width {
+        return Cow::Borrowed(s);
+    }
+    let available = max_width.saturating_sub(display_width(ELLIPSIS));
+    let head = take_width(s, available.div_ceil(2));
+    let tail = take_width_from_end(s, available - display_width(head));
+    Cow::Owned(format!("{}{}{}", head, ELLIPSIS, tail))
+}
+
+/// Truncate a file path to `max_width` columns, eliding middle directories.
+///
+/// Keeps the first directory and as many trailing components as fit, e.g.
+/// `src/format/tool/mod.rs` becomes `src/…/tool/mod.rs`. Falls back to
+/// `…/name` and finally to [`truncate_middle`] when even that is too wide.
+///
+/// # Example
+///
+/// ```
+/// use clemitui::width::truncate_path;
+///
+/// assert_eq!(truncate_path("src/format/tool/mod.rs", 17), "src/…/tool/mod.rs");
+/// assert_eq!(truncate_path("src/format/tool/mod.rs", 12), "src/…/mod.rs");
+/// ```
+pub fn truncate_path(path: &str, max_width: usize) -> Cow<'_, str> {
+    if display_width(path) <= max_width {
+        return Cow::Borrowed(path);
+    }
+
+    let (root, relative) = match path.strip_prefix('/') {
+        Some(relative) => ("/", relative),
+        None => ("", path),
+    };
+    let components: Vec<&str> = relative.split('/').collect();
+    if components.len() > 2 {
+        let head = format!("{}{}/{}", root, components[0], ELLIPSIS);
+        let mut tail: Vec<&str> = Vec::new();
+        for component in components[1..].iter().rev() {
+            let mut candidate = tail.clone();
+            candidate.insert(0, component);
+            if candidate.len() == components.len() - 1 {
+                break;
+            }
+            let joined = format!("{}/{}", head, candidate.join("/"));
+            if display_width(&joined) > max_width {
+                break;
+            }
+            tail = candidate;
+        }
+        if !tail.is_empty() {
+            return Cow::Owned(format!("{}/{}", head, tail.join("/")));
+        }
+    }
+
+    if let Some(name) = components.last().filter(|_| components.len() > 1) {
+        let short = format!("{}/{}", ELLIPSIS, name);
+        if display_width(&short) <= max_width {
+            return Cow::Owned(short);
+        }
+    }
+    truncate_middle(path, max_width)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_width_unicode() {
+        assert_eq!(display_width(""), 0);
+        assert_eq!(display_width("héllo"), 5);
+        // Combining acute accent takes no column
+        assert_eq!(display_width("e\u{301}"), 1);
+        assert_eq!(display_width("日本語"), 6);
+        assert_eq!(display_width("🦀"), 2);
+        assert_eq!(display_width("█░"), 2);
+    }
+
+    #[test]
+    fn test_display_width_ignores_escapes() {
+        assert_eq!(display_width("\x1b[1;31mred\x1b[0m"), 3);
+        // OSC 8 hyperlink with BEL and ST terminators
+        assert_eq!(
+            display_width("\x1b]8;;https://example.com\x07link\x1b]8;;\x07"),
+            4
+        );
+        assert_eq!(display_width("\x1b]8;;file:///a\x1b\\a\x1b]8;;\x1b\\"), 1);
+        // Save/restore cursor
+        assert_eq!(display_width("\x1b7x\x1b8"), 1);
+    }
+
+    #[test]
+    fn test_strip_ansi() {
+        assert_eq!(strip_ansi("plain"), "plain");
+        assert_eq!(strip_ansi("\x1b[32mgreen\x1b[0m text"), "green text");
+        assert_eq!(strip_ansi("\x1b]8;;u\x07link\x1b]8;;\x07"), "link");
+    }
+
+    #[test]
+    fn test_wrapped_rows() {
+        assert_eq!(wrapped_rows("", 10), 1);
+        assert_eq!(wrapped_rows("abc\n", 10), 1);
+        assert_eq!(wrapped_rows("0123456789", 10), 1);
+        assert_eq!(wrapped_rows("\x1b[1m0123456789a\x1b[0m\nb", 10), 3);
+        assert_eq!(wrapped_rows("日本語", 4), 2);
+        assert_eq!(wrapped_rows("abc", 0), 3);
+    }
+
+    #[test]
+    fn test_truncate_end() {
+        assert_eq!(truncate_end("short", 10, "..."), "short");
+        assert_eq!(truncate_end("abcdefghij", 10, "..."), "abcdefghij");
+        assert_eq!(truncate_end("abcdefghijk", 10, "..."), "abcdefg...");
+    }
+
+    #[test]
+    fn test_truncate_end_multibyte_boundary() {
+        // Byte slicing at 77 would split "é"
+        let s = format!("{}{}", "a".repeat(76), "é".repeat(10));
+        let truncated = truncate_end(&s, 80, "...");
+        assert_eq!(truncated, format!("{}é...", "a".repeat(76)));
+    }
+
+    #[test]
+    fn test_truncate_end_wide_chars() {
+        // A wide char that would straddle the limit is dropped entirely
+        assert_eq!(truncate_end("日本語テキスト", 8, "..."), "日本...");
+        assert_eq!(
+            display_width(&truncate_end(&"日".repeat(50), 80, "...")),
+            79
+        );
+    }
+
+    #[test]
+    fn test_truncate_end_keeps_graphemes_whole() {
+        // Family emoji is a single ZWJ grapheme; never split it
+        let family = "👨\u{200d}👩\u{200d}👧";
+        let s = format!("ab{}cdef", family);
+        let truncated = truncate_end(&s, 4, "…");
+        assert_eq!(truncated, "ab…");
+    }
+
+    #[test]
+    fn test_truncate_end_preserves_ansi() {
+        let colored = "\x1b[32mgreen\x1b[0m and \x1b[1mbold\x1b[0m";
+        assert_eq!(truncate_end(colored, 20, "…"), colored);
+        assert_eq!(
+            truncate_end(colored, 8, "…"),
+            "\x1b[32mgreen\x1b[0m a\x1b[0m…"
+        );
+        assert_eq!(display_width(&truncate_end(colored, 8, "…")), 8);
+    }
+
+    #[test]
+    fn test_truncate_end_closes_hyperlink() {
+        assert_eq!(
+            truncate_end("\x1b]8;;https://x\x07link text\x1b]8;;\x07", 5, "…"),
+            "\x1b]8;;https://x\x07link\x1b]8;;\x1b\\\x1b[0m…"
+        );
+        // A link closed before the cut is not closed again
+        let closed = "\x1b]8;;https://x\x1b\\ab\x1b]8;;\x1b\\ cdef";
+        assert_eq!(
+            truncate_end(closed, 5, "…"),
+            "\x1b]8;;https://x\x1b\\ab\x1b]8;;\x1b\\ c\x1b[0m…"
+        );
+    }
+
+    #[test]
+    fn test_truncate_middle() {
+        assert_eq!(truncate_middle("abcdefghij", 10), "abcdefghij");
+        assert_eq!(truncate_middle("abcdefghij", 6), "abc…ij");
+        assert_eq!(truncate_middle("日本語テキスト", 7), "日…スト");
+    }
+
+    #[test]
+    fn test_truncate_path() {
+        let path = "src/format/tool/mod.rs";
+        assert_eq!(truncate_path(path, 40), path);
+        assert_eq!(truncate_path(path, 17), "src/…/tool/mod.rs");
+        assert_eq!(truncate_path(path, 12), "src/…/mod.rs");
+        assert_eq!(truncate_path(path, 9), "…/mod.rs");
+        assert_eq!(truncate_path(path, 5), "sr…rs");
+    }
+
+    #[test]
+    fn test_truncate_path_absolute() {
+        let path = "/home/user/projects/clemitui/src/text_buffer.rs";
+        assert_eq!(truncate_path(path, 30), "/home/…/src/text_buffer.rs");
+        assert!(display_width(&truncate_path(path, 30)) <= 30);
+    }
+
+    #[test]
+    fn test_truncate_path_few_components() {
+        assert_eq!(
+            truncate_path("directory/file_name.rs", 15),
+            "…/file_name.rs"
+        );
+        assert_eq!(truncate_path("a_very_long_file_name.rs", 10), "a_ver…e.rs");
+    }
+}
@@ -9,9 +9,15 @@
 mod common;
 
 use clemitui::{
-    TextBuffer, enable_logging, format_cancelled, format_context_warning, format_ctrl_c,
-    format_error_detail, format_retry, format_tool_args, format_tool_executing, format_tool_result,
-    log_event, log_event_line, set_output_sink,
+    ContentBlock, FileDiff, FsHandler, FullscreenView, ImageProtocol, LiveRegion, NotifyConfig,
+    NotifyEvent, NotifyMethod, OutputPreviewConfig, OutputSink, PagerConfig, PagerMode, Plan,
+    TerminalExitStatus, TerminalManager, TerminalPane, TextBuffer, ThoughtBuffer, ToolKind,
+    ToolStatus, TranscriptSearch, enable_logging, format_cancelled, format_content_block_with,
+    format_context_warning, format_ctrl_c, format_diff, format_error_detail, format_error_message,
+    format_notification_with, format_plan, format_retry, format_terminal_exit, format_tool_args,
+    format_tool_executing, format_tool_executing_with_kind, format_tool_output, format_tool_result,
+    format_tool_result_with_kind, log_event, log_event_line, print_paged, reset_pager_config,
+    set_output_sink, set_pager_config,
 };
 use common::{
     CaptureSink, DisableColors, LoggingGuard, flush_to_output, format_tool_block, strip_ansi,
@@ -345,6 +351,562 @@ fn test_interleaved_explanation_and_tools() {
     assert!(output.contains("Line 42"), "Should have final explanation");
 }
 
+/// Simulates `agent_thought_chunk` updates followed by the answer and a tool
+/// call, with thoughts collapsed to a summary once the answer starts.
+#[test]
+fn test_thoughts_before_answer() {
+    let _guard = DisableColors::new();
+
+    let mut thoughts = ThoughtBuffer::new().with_terminal_size(80, 24);
+    let mut buffer = TextBuffer::new();
+    let mut output = String::new();
+
+    for chunk in ["I should read ", "the config first.\n", "Then patch it."] {
+        thoughts.push(chunk);
+    }
+    output.push_str(&thoughts.flush().unwrap());
+    assert!(output.contains("  I should read the config first.\n  Then patch it.\n"));
+
+    // First answer chunk ends the thought
+    output.push_str(&thoughts.finish().expect("summary"));
+    assert!(thoughts.finish().is_none(), "Finished only once");
+    buffer.push("Reading the config.\n\n");
+    flush_to_output(&mut buffer, &mut output);
+    output.push_str(&format_tool_executing(
+        "read_file",
+        &json!({"file_path": "config.toml"}),
+    ));
+
+    assert!(
+        output.contains("\x1b[3F\x1b[J✻ thought for 0s\n\n"),
+        "got: {:?}",
+        output
+    );
+    let plain = strip_ansi(&output);
+    let summary = plain.find("✻ thought for").unwrap();
+    assert!(summary < plain.find("Reading the config").unwrap());
+    assert!(plain.contains("┌─"));
+}
+
+/// Simulates ACP `plan` updates interleaved with tool calls: the plan is
+/// re-rendered in place until tool output is printed below it.
+#[test]
+fn test_plan_updates_between_tools() {
+    let _guard = DisableColors::new();
+
+    let mut region = LiveRegion::new().with_terminal_size(80, 24);
+    let mut output = String::new();
+    let entries = |first: &str, second: &str| {
+        json!({"sessionUpdate": "plan", "entries": [
+            {"content": "Create auth middleware", "priority": "high", "status": first},
+            {"content": "Add JWT validation", "priority": "medium", "status": second}
+        ]})
+    };
+
+    output.push_str(&region.update(&format_plan(&Plan::from_acp(&entries(
+        "in_progress",
+        "pending",
+    )))));
+    output.push_str(&region.update(&format_plan(&Plan::from_acp(&entries(
+        "completed",
+        "pending",
+    )))));
+    assert_eq!(output.matches("\x1b[3F\x1b[J").count(), 1);
+
+    // Tool output lands below the plan; the next update starts fresh
+    output.push_str(&format_tool_executing(
+        "write_file",
+        &json!({"file_path": "src/auth.rs"}),
+    ));
+    region.detach();
+    let plan = Plan::from_acp(&entries("completed", "completed"));
+    assert!(plan.is_complete());
+    output.push_str(&region.update(&format_plan(&plan)));
+
+    assert_eq!(output.matches("\x1b[3F\x1b[J").count(), 1);
+    let plain = strip_ansi(&output);
+    assert!(plain.contains("Plan [█████░░░░░] 1/2\n  ✓ ! Create auth middleware\n  ○   Add JWT"));
+    assert!(plain.ends_with(
+        "Plan [██████████] 2/2\n  ✓ ! Create auth middleware\n  ✓   Add JWT validation\n"
+    ));
+}
+
+/// Simulates polling ACP `terminal/output` for a command that draws a
+/// progress bar, then exits.
+#[test]
+fn test_embedded_terminal_polling() {
+    let _guard = DisableColors::new();
+
+    let mut pane = TerminalPane::new()
+        .with_size(40, 10)
+        .with_window_rows(2)
+        .with_terminal_size(80, 24);
+    let mut output = format_tool_executing("bash", &json!({"command": "npm install"}));
+
+    // Each poll returns the whole output so far
+    let mut full = String::new();
+    for chunk in [
+        "added 12 packages\n",
+        "[##  ] 50%",
+        "\r[####] 100%",
+        "\r\x1b[Kaudited 12 packages\n",
+    ] {
+        full.push_str(chunk);
+        output.push_str(&pane.set_output(&full));
+    }
+    output.push_str(&pane.finish());
+    output.push_str(&format_tool_result(
+        "bash",
+        Duration::from_secs(3),
+        40,
+        false,
+    ));
+
+    assert_eq!(
+        pane.screen().lines(),
+        vec!["added 12 packages", "audited 12 packages"]
+    );
+    assert!(output.contains("│ [####] 100%\n"), "got: {:?}", output);
+    assert!(
+        output.ends_with(
+            "\x1b[2F\x1b[J   added 12 packages\n   audited 12 packages\n└─ bash 3.00s ~40 tok"
+        ),
+        "got: {:?}",
+        output
+    );
+}
+
+/// Runs a real command through the client-side `terminal/*` methods the way
+/// an agent would: create, poll output, kill, wait for exit and release.
+#[cfg(unix)]
+#[test]
+fn test_kill_terminal_via_manager() {
+    let _guard = DisableColors::new();
+
+    let manager = TerminalManager::new();
+    let mut pane = TerminalPane::new()
+        .with_size(40, 10)
+        .with_terminal_size(80, 24);
+    let mut output = strip_ansi(&format_tool_executing(
+        "bash",
+        &json!({"command": "cargo build --all-targets", "background": true}),
+    ));
+
+    let created = manager
+        .handle(
+            "terminal/create",
+            &json!({
+                "sessionId": "sess-1",
+                "command": "sh",
+                "args": ["-c", "echo Compiling; exec sleep 30"],
+                "outputByteLimit": 1024
+            }),
+        )
+        .unwrap();
+    let params = json!({"sessionId": "sess-1", "terminalId": created["terminalId"]});
+    let id = params["terminalId"].as_str().unwrap();
+
+    // Poll until the first line arrives
+    let started = std::time::Instant::now();
+    while !manager.output(id).unwrap().output.contains("Compiling") {
+        assert!(started.elapsed() < Duration::from_secs(5), "no output");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    let polled = manager.handle("terminal/output", &params).unwrap();
+    assert_eq!(polled["truncated"], false);
+    assert!(polled.get("exitStatus").is_none(), "still running");
+    output.push_str(&pane.set_output(polled["output"].as_str().unwrap()));
+
+    // Agent kills the task, then waits for it
+    manager.handle("terminal/kill", &params).unwrap();
+    let exit: TerminalExitStatus =
+        serde_json::from_value(manager.handle("terminal/wait_for_exit", &params).unwrap()).unwrap();
+    output.push_str(&strip_ansi(&pane.finish()));
+    output.push_str(&strip_ansi(&format_tool_result(
+        "bash",
+        Duration::from_millis(10),
+        15,
+        true,
+    )));
+    output.push('\n');
+    output.push_str(&strip_ansi(&format_terminal_exit(id, &exit)));
+    manager.handle("terminal/release", &params).unwrap();
+
+    assert_eq!(exit.signal.as_deref(), Some("SIGKILL"));
+    assert!(
+        output.contains("│ Compiling\n"),
+        "live window: {:?}",
+        output
+    );
+    assert!(
+        output.contains("   Compiling\n└─ bash"),
+        "snapshot: {:?}",
+        output
+    );
+    assert!(
+        output.ends_with("  term-1 killed by SIGKILL\n"),
+        "got: {:?}",
+        output
+    );
+    assert!(manager.terminal_ids().is_empty());
+}
+
+/// Simulates an agent reading a file the user is editing, then writing an
+/// edit that the client previews as a diff and approves.
+#[test]
+fn test_fs_write_with_diff_preview() {
+    let _guard = DisableColors::new();
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, "[server]\nport = 8080\n").unwrap();
+
+    let previews = Arc::new(std::sync::Mutex::new(String::new()));
+    let sink = Arc::clone(&previews);
+    let handler = FsHandler::new().with_approver(move |diff: &FileDiff| {
+        sink.lock().unwrap().push_str(&format_diff(diff));
+        true
+    });
+    // The user has unsaved changes in their editor
+    handler.set_override(&path, "[server]\nhost = \"0.0.0.0\"\nport = 8080\n");
+
+    let read = handler
+        .handle(
+            "fs/read_text_file",
+            &json!({"sessionId": "sess-1", "path": path, "line": 2, "limit": 1}),
+        )
+        .unwrap();
+    assert_eq!(read["content"], "host = \"0.0.0.0\"\n");
+
+    handler
+        .handle(
+            "fs/write_text_file",
+            &json!({
+                "sessionId": "sess-1",
+                "path": path,
+                "content": "[server]\nhost = \"0.0.0.0\"\nport = 9090\n"
+            }),
+        )
+        .unwrap();
+
+    let preview = previews
+        .lock()
+        .unwrap()
+        .replace(&path.display().to_string(), "config.toml");
+    assert_eq!(
+        preview,
+        "  config.toml +1 -1\n  @@ -1,3 +1,3 @@\n  1   [server]\n  2   host = \"0.0.0.0\"\n  3 - port = 8080\n  3 + port = 9090\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "[server]\nhost = \"0.0.0.0\"\nport = 9090\n"
+    );
+}
+
+/// Simulates an agent answering with mixed `agent_message_chunk` content:
+/// streamed text, a screenshot and a link to the file it changed.
+#[test]
+fn test_message_with_content_blocks() {
+    let _guard = DisableColors::new();
+
+    let chunks = [
+        json!({"sessionUpdate": "agent_message_chunk", "content": {"type": "text", "text": "The login page now "}}),
+        json!({"sessionUpdate": "agent_message_chunk", "content": {"type": "text", "text": "renders **correctly**:"}}),
+        json!({"sessionUpdate": "agent_message_chunk", "content": {
+            "type": "image",
+            "mimeType": "image/gif",
+            "data": "R0lGODlhgALgAQ=="
+        }}),
+        json!({"sessionUpdate": "agent_message_chunk", "content": {
+            "type": "resource_link",
+            "uri": "file:///work/src/login.tsx",
+            "name": "login.tsx",
+            "mimeType": "text/tsx"
+        }}),
+        json!({"sessionUpdate": "agent_message_chunk", "content": {"type": "unknown"}}),
+    ];
+
+    // Text chunks stream through the buffer; other blocks flush it first
+    let mut buffer = TextBuffer::new();
+    let mut output = String::new();
+    for chunk in &chunks {
+        match ContentBlock::from_acp(&chunk["content"]) {
+            Some(ContentBlock::Text { text }) => buffer.push(&text),
+            Some(block) => {
+                flush_to_output(&mut buffer, &mut output);
+                output.push_str(&format_content_block_with(&block, ImageProtocol::None));
+            }
+            None => {}
+        }
+    }
+    flush_to_output(&mut buffer, &mut output);
+
+    assert_eq!(
+        strip_ansi(&output),
+        "The login page now renders correctly:\n\n[image: 640×480 gif]\n↗ login.tsx · text/tsx file:///work/src/login.tsx\n"
+    );
+}
+
+/// Simulates an agent streaming a comparison table in small chunks, split
+/// mid-row, followed by a tool call.
+#[test]
+fn test_streamed_table_before_tool() {
+    let _guard = DisableColors::new();
+
+    let answer = "Benchmarks:\n\n| Parser | Throughput | Notes |\n|:--|--:|:-:|\n| nom | 1.2 GB/s | zero-copy |\n| pest | 310 MB/s | PEG grammar |\n\nRunning the suite now.";
+    let mut buffer = TextBuffer::new().with_width(40);
+    let mut output = String::new();
+    for chunk in answer.as_bytes().chunks(7) {
+        buffer.push(std::str::from_utf8(chunk).unwrap());
+    }
+    flush_to_output(&mut buffer, &mut output);
+    output.push_str(&strip_ansi(&format_tool_executing(
+        "bash",
+        &json!({"command": "cargo bench"}),
+    )));
+
+    assert!(
+        output.starts_with(concat!(
+            "Benchmarks:\n\n",
+            "┌────────┬────────────┬─────────────┐\n",
+            "│ Parser │ Throughput │    Notes    │\n",
+            "├────────┼────────────┼─────────────┤\n",
+            "│ nom    │   1.2 GB/s │  zero-copy  │\n",
+            "│ pest   │   310 MB/s │ PEG grammar │\n",
+            "└────────┴────────────┴─────────────┘\n\n",
+            "Running the suite now.\n\n",
+        )),
+        "got: {output}"
+    );
+    assert!(output.contains("bash"));
+}
+
+/// Simulates a full-screen session receiving overlapping tool calls, one of
+/// which fails, through the same sink line mode uses.
+#[test]
+fn test_fullscreen_tool_sidebar() {
+    let _guard = DisableColors::new();
+
+    let mut view = FullscreenView::new("> ").with_size(70, 12);
+    let sink = view.sink();
+    sink.emit("Let me look around the project.");
+    sink.emit_line(&format_tool_executing("grep", &json!({"pattern": "TODO"})));
+    sink.emit_line(&format_tool_executing_with_kind(
+        "run_tests",
+        &json!({"command": "cargo test"}),
+        Some(ToolKind::Execute),
+    ));
+    sink.emit(&format_tool_result(
+        "grep",
+        Duration::from_millis(40),
+        120,
+        false,
+    ));
+    sink.emit(&format!(
+        "{}\n{}",
+        format_tool_result_with_kind(
+            "run_tests",
+            Duration::from_millis(900),
+            40,
+            true,
+            Some(ToolKind::Execute),
+        ),
+        format_error_detail("2 tests failed"),
+    ));
+    sink.emit_line(&format_tool_executing(
+        "read_file",
+        &json!({"path": "src/lib.rs"}),
+    ));
+
+    let tools: Vec<_> = view
+        .tools()
+        .into_iter()
+        .map(|tool| (tool.name, tool.status))
+        .collect();
+    assert_eq!(
+        tools,
+        vec![
+            ("grep".to_string(), ToolStatus::Done),
+            ("▶ run_tests".to_string(), ToolStatus::Failed),
+            ("read_file".to_string(), ToolStatus::Running),
+        ]
+    );
+
+    let frame = strip_ansi(&view.render());
+    assert!(frame.contains("Tools 1/3"), "got: {frame}");
+    assert!(frame.contains("✗ ▶ run_tests"), "got: {frame}");
+    assert!(!frame.contains("2 tests failed"), "folded: {frame}");
+
+    // The error detail is part of the failed tool's block
+    view.set_all_expanded(true);
+    let frame = strip_ansi(&view.render());
+    assert!(frame.contains("└─ error: 2 tests failed"), "got: {frame}");
+}
+
+/// Simulates a full-screen session where an edit with a diff preview and a
+/// test run are folded away, leaving the agent's prose readable.
+#[test]
+fn test_fullscreen_folded_tool_blocks() {
+    let _guard = DisableColors::new();
+
+    let mut view = FullscreenView::new("> ")
+        .with_size(60, 30)
+        .with_sidebar(false);
+    let sink = view.sink();
+    sink.emit("I'll rename the helper.");
+    sink.emit_line(&format_tool_executing(
+        "edit_file",
+        &json!({"path": "/work/src/lib.rs"}),
+    ));
+    sink.emit_line(&format_tool_result(
+        "edit_file",
+        Duration::from_millis(30),
+        80,
+        false,
+    ));
+    let diff = FileDiff::new("/work/src/lib.rs", "fn old() {}\n", "fn new() {}\n");
+    sink.emit(format_diff(&diff).trim_end());
+    sink.emit_line(&format_tool_executing(
+        "bash",
+        &json!({"command": "cargo test"}),
+    ));
+    sink.emit_line(&format_tool_result(
+        "bash",
+        Duration::from_millis(2100),
+        300,
+        false,
+    ));
+    sink.emit(&format_tool_output(
+        "running 12 tests\ntest result: ok",
+        &OutputPreviewConfig::default(),
+    ));
+    sink.emit("The helper is renamed and the tests pass.");
+
+    let frame = strip_ansi(&view.render());
+    assert!(frame.contains("I'll rename the helper."));
+    assert!(frame.contains("The helper is renamed and the tests pass."));
+    assert!(frame.contains("└─ edit_file"));
+    assert!(!frame.contains("fn new() {}"), "diff folded away: {frame}");
+    assert!(
+        !frame.contains("running 12 tests"),
+        "output folded away: {frame}"
+    );
+
+    view.set_all_expanded(true);
+    let frame = strip_ansi(&view.render());
+    assert!(frame.contains("fn new() {}"), "got: {frame}");
+    assert!(frame.contains("running 12 tests"), "got: {frame}");
+}
+
+/// Simulates searching a recorded session with a regex that hits agent text,
+/// tool arguments and tool output.
+#[test]
+fn test_transcript_search_across_sources() {
+    let _guard = DisableColors::new();
+
+    let mut view = FullscreenView::viewer().with_size(80, 24);
+    let sink = view.sink();
+    sink.emit("I'll check config.toml and settings.toml for the port.");
+    sink.emit_line(&format_tool_executing(
+        "read_file",
+        &json!({"path": "config.toml"}),
+    ));
+    sink.emit_line(&format_tool_result(
+        "read_file",
+        Duration::from_millis(4),
+        30,
+        false,
+    ));
+    sink.emit(&format_tool_output(
+        "[server]\nport = 8080\n# see settings.toml",
+        &OutputPreviewConfig::default(),
+    ));
+    sink.emit("The port is 8080.");
+
+    let lines = view.lines();
+    let search = TranscriptSearch::new(r"\w+\.toml").unwrap();
+    let hits: Vec<_> = search
+        .matches(&lines)
+        .iter()
+        .map(|found| strip_ansi(&lines[found.line])[found.start..found.end].to_string())
+        .collect();
+    assert_eq!(
+        hits,
+        [
+            "config.toml",
+            "settings.toml",
+            "config.toml",
+            "settings.toml"
+        ]
+    );
+
+    // Jumping to the match in the folded output expands the read_file block
+    view.start_search("see settings");
+    let frame = strip_ansi(&view.render());
+    assert!(frame.contains("# see settings.toml"), "got: {frame}");
+    assert!(frame.contains("/see settings  1/1"), "got: {frame}");
+    assert!(view.is_expanded(0));
+}
+
+/// Simulates an agent dumping a long file next to a short answer: only the
+/// dump crosses the pager threshold, and neither blocks without a terminal.
+#[test]
+fn test_pager_threshold_for_streamed_output() {
+    let _guard = DisableColors::new();
+    let config = PagerConfig::default()
+        .with_mode(PagerMode::External)
+        .with_threshold(100);
+
+    let mut dump = TextBuffer::new();
+    dump.push("Here is `src/big.rs`:\n\n```rust\n");
+    for i in 0..150 {
+        dump.push(&format!("fn f{i}() {{}}\n"));
+    }
+    dump.push("```\n");
+    let rendered = dump.flush().unwrap();
+    assert!(config.should_page(&rendered));
+
+    let mut answer = TextBuffer::new();
+    answer.push("The file defines **150** functions.");
+    let short = answer.flush().unwrap();
+    assert!(!config.should_page(&short));
+
+    // Test stdout is not a terminal, so both are printed directly
+    set_pager_config(Arc::new(config));
+    print_paged(&rendered).unwrap();
+    print_paged(&short).unwrap();
+    reset_pager_config();
+}
+
+/// Simulates a session with a quick turn, a long turn that asks permission and
+/// a failing turn, notifying only where the user is likely to have looked away.
+#[test]
+fn test_notifications_across_turns() {
+    let config = NotifyConfig::default()
+        .with_method(NotifyMethod::Osc9)
+        .with_min_duration(Duration::from_secs(20));
+    let no_env = |_: &str| None;
+
+    let events = [
+        NotifyEvent::TurnComplete(Duration::from_secs(3)),
+        NotifyEvent::PermissionRequested("bash".into()),
+        NotifyEvent::TurnComplete(Duration::from_secs(240)),
+        NotifyEvent::Error(format_error_message("\x1b[1mconnection reset\x1b[0m")),
+    ];
+    let sent: Vec<String> = events
+        .iter()
+        .filter_map(|event| format_notification_with(event, &config, no_env))
+        .collect();
+    assert_eq!(
+        sent,
+        [
+            "\x1b]9;Permission needed for bash\x07",
+            "\x1b]9;Turn finished after 4m 00s\x07",
+            "\x1b]9;Error: connection reset\x07",
+        ]
+    );
+}
+
 /// Simulates an agent providing step-by-step progress updates.
 #[test]
 fn test_step_by_step_progress() {
@@ -756,6 +1318,38 @@ fn test_background_task_lifecycle() {
     assert!(output.contains("223 passed"), "Should report results");
 }
 
+/// Simulates a search whose output is too long to show in full.
+#[test]
+fn test_long_tool_output_is_folded() {
+    let _guard = DisableColors::new();
+
+    let mut output = String::new();
+    let matches: String = (1..=250)
+        .map(|i| format!("src/module_{i}.rs:{i}: TODO handle error\n"))
+        .collect();
+
+    let grep_args = json!({"pattern": "TODO", "path": "src"});
+    output.push_str(&strip_ansi(&format_tool_executing("grep", &grep_args)));
+    output.push_str(&strip_ansi(&format_tool_result(
+        "grep",
+        Duration::from_millis(40),
+        3000,
+        false,
+    )));
+    output.push('\n');
+    output.push_str(&strip_ansi(&format_tool_output(
+        &matches,
+        &OutputPreviewConfig::default(),
+    )));
+
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 13, "header, result and 11 preview lines");
+    assert!(lines[1].starts_with("└─ grep"));
+    assert_eq!(lines[2], "   src/module_1.rs:1: TODO handle error");
+    assert_eq!(lines[7], "   … 240 more lines …");
+    assert_eq!(lines[12], "   src/module_250.rs:250: TODO handle error");
+}
+
 /// Simulates multiple concurrent background tasks.
 #[test]
 fn test_multiple_background_tasks() {